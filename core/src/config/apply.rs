@@ -0,0 +1,57 @@
+//! "Apply" and "Revert" buttons of
+//! [ConfigMenu::with_apply_revert][super::ConfigMenu::with_apply_revert].
+//!
+//! The menu is staged, so changes only reach storage when they are applied (or the menu is
+//! closed). Before every apply, the keys about to be written are captured in a [Snapshot], so
+//! revert can restore the state of the keys from when the menu was opened.
+
+use super::{Attachable, Button, MenuError, MenuRoot, staging};
+use crate::{
+    storage::{Snapshot, StorageError},
+    sync::Mutex,
+};
+use wut::{string::String, vec::Vec};
+
+/// Values from before each apply of the current session, oldest first.
+static SNAPSHOTS: Mutex<Vec<Snapshot>> = Mutex::new(Vec::new());
+
+/// Add both buttons to `root`.
+pub(super) fn attach(root: &MenuRoot) -> Result<(), MenuError> {
+    root.add(Button::fallible("Apply", apply))?;
+    root.add(Button::fallible("Revert", revert))
+}
+
+/// Write the staged changes in one transaction, after capturing the values they replace.
+fn apply() -> Result<(), StorageError> {
+    let ids = staging::pending_ids();
+    let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+    let snapshot = Snapshot::capture(&ids)?;
+
+    // if the commit fails, the changes stay staged and can be applied again
+    staging::commit()?;
+    staging::begin();
+
+    SNAPSHOTS.lock().unwrap().push(snapshot);
+    Ok(())
+}
+
+/// Drop the staged changes and undo the applied ones.
+fn revert() -> Result<(), StorageError> {
+    staging::discard();
+
+    let mut snapshots = SNAPSHOTS.lock().unwrap();
+    // newest first, so a key applied several times ends up with its oldest value
+    while let Some(snapshot) = snapshots.pop() {
+        if let Err(e) = snapshot.restore() {
+            snapshots.push(snapshot);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Forget the snapshots of the session, when the menu is closed and in
+/// [reset_statics][crate::reset_statics].
+pub(super) fn reset() {
+    SNAPSHOTS.lock().unwrap().clear();
+}
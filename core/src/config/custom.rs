@@ -0,0 +1,157 @@
+//! Items implemented in Rust on top of the generic `WUPSConfigAPI_Item_Create`.
+//!
+//! The built-in C items (stub, boolean, integer range, multiple values) cover the common cases.
+//! Anything else is a [CustomItem], which receives the raw callbacks of the config menu.
+
+use super::MenuError;
+use alloc::boxed::Box;
+use wups_sys as sys;
+use wut::{
+    ffi::{CStr, c_char, c_void},
+    string::String,
+};
+
+/// Behavior of an item created via [attach].
+pub(crate) trait CustomItem: 'static {
+    /// Value displayed next to the item's text.
+    fn display(&self) -> String;
+
+    /// Value displayed while the item is selected.
+    fn selected_display(&self) -> String {
+        self.display()
+    }
+
+    /// Buttons which were pressed (not held) while the item is selected.
+    fn on_input(&mut self, pressed: sys::WUPSConfigButtons::Type);
+
+    /// Called when the user restores the default values of the menu.
+    fn restore_default(&mut self);
+
+    /// Called when the config menu is closed.
+    fn on_close(&mut self) {}
+
+    /// Whether the user may move to other items. While `false`, all input (including B) goes to
+    /// this item.
+    fn is_movement_allowed(&self) -> bool {
+        true
+    }
+}
+
+/// Create `item` and add it to the category `handle`.
+///
+/// The item is owned by the config menu from here on and dropped when the menu is closed.
+pub(crate) fn attach(
+    handle: sys::WUPSConfigCategoryHandle,
+    text: &CStr,
+    item: Box<dyn CustomItem>,
+) -> Result<(), MenuError> {
+    let context = Box::into_raw(Box::new(item)) as *mut c_void;
+
+    let callbacks = sys::WUPSConfigAPIItemCallbacksV2 {
+        getCurrentValueDisplay: Some(get_current_value_display),
+        getCurrentValueSelectedDisplay: Some(get_current_value_selected_display),
+        onSelected: Some(on_selected),
+        restoreDefault: Some(restore_default),
+        isMovementAllowed: Some(is_movement_allowed),
+        onCloseCallback: Some(on_close),
+        onInput: Some(on_input),
+        onInputEx: Some(on_input_ex),
+        onDelete: Some(on_delete),
+    };
+    let options = super::raw::item_options_v2(text, context, callbacks);
+
+    let mut item = sys::WUPSConfigItemHandle::default();
+    let status = unsafe { sys::WUPSConfigAPI_Item_Create(options, &mut item) };
+    if let Err(e) = super::check_options(status, sys::WUPS_API_ITEM_OPTION_VERSION_V2) {
+        // no item owns the context, so `on_delete` won't be called
+        drop(unsafe { Box::from_raw(context as *mut Box<dyn CustomItem>) });
+        return Err(e);
+    }
+
+    let status = unsafe { sys::WUPSConfigAPI_Category_AddItem(handle, item) };
+    if let Err(e) = MenuError::try_from(status) {
+        // frees the context via `on_delete`
+        unsafe { sys::WUPSConfigAPI_Item_Destroy(item) };
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+// region: Callbacks
+
+/// # Safety
+///
+/// `context` must be a context created by [attach] which wasn't deleted yet.
+unsafe fn item<'a>(context: *mut c_void) -> &'a mut dyn CustomItem {
+    unsafe { &mut **(context as *mut Box<dyn CustomItem>) }
+}
+
+/// Copy `text` into the C buffer, truncated at a char boundary if necessary.
+fn write_display(text: &str, out_buf: *mut c_char, out_size: i32) -> i32 {
+    use sys::WUPSConfigAPIStatus as S;
+
+    if out_buf.is_null() || out_size <= 0 {
+        return S::WUPSCONFIG_API_RESULT_INVALID_ARGUMENT;
+    }
+
+    let mut len = text.len().min(out_size as usize - 1);
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    unsafe {
+        core::ptr::copy_nonoverlapping(text.as_ptr(), out_buf as *mut u8, len);
+        *out_buf.add(len) = 0;
+    }
+
+    S::WUPSCONFIG_API_RESULT_SUCCESS
+}
+
+unsafe extern "C" fn get_current_value_display(
+    context: *mut c_void,
+    out_buf: *mut c_char,
+    out_size: i32,
+) -> i32 {
+    write_display(&unsafe { item(context) }.display(), out_buf, out_size)
+}
+
+unsafe extern "C" fn get_current_value_selected_display(
+    context: *mut c_void,
+    out_buf: *mut c_char,
+    out_size: i32,
+) -> i32 {
+    write_display(
+        &unsafe { item(context) }.selected_display(),
+        out_buf,
+        out_size,
+    )
+}
+
+unsafe extern "C" fn on_selected(_context: *mut c_void, _is_selected: bool) {}
+
+unsafe extern "C" fn restore_default(context: *mut c_void) {
+    unsafe { item(context) }.restore_default();
+}
+
+unsafe extern "C" fn is_movement_allowed(context: *mut c_void) -> bool {
+    unsafe { item(context) }.is_movement_allowed()
+}
+
+unsafe extern "C" fn on_close(context: *mut c_void) {
+    unsafe { item(context) }.on_close();
+}
+
+unsafe extern "C" fn on_input(context: *mut c_void, input: sys::WUPSConfigSimplePadData) {
+    if input.buttons_d != sys::WUPSConfigButtons::WUPS_CONFIG_BUTTON_NONE {
+        unsafe { item(context) }.on_input(input.buttons_d);
+    }
+}
+
+unsafe extern "C" fn on_input_ex(_context: *mut c_void, _input: sys::WUPSConfigComplexPadData) {}
+
+unsafe extern "C" fn on_delete(context: *mut c_void) {
+    drop(unsafe { Box::from_raw(context as *mut Box<dyn CustomItem>) });
+}
+
+// endregion
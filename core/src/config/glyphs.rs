@@ -0,0 +1,50 @@
+//! Symbols for item texts.
+//!
+//! The font of the config menu is the console's system font, which covers the Unicode arrows, box
+//! drawing and block elements.
+
+use wut::string::String;
+
+pub const ARROW_LEFT: &str = "\u{2190}";
+pub const ARROW_UP: &str = "\u{2191}";
+pub const ARROW_RIGHT: &str = "\u{2192}";
+pub const ARROW_DOWN: &str = "\u{2193}";
+
+/// Filled part of a [bar].
+pub const BLOCK_FULL: &str = "\u{2588}";
+/// Empty part of a [bar].
+pub const BLOCK_LIGHT: &str = "\u{2591}";
+
+/// Horizontal line around the header of a [Group][super::Group].
+pub const LINE: &str = "\u{2500}";
+
+/// Bar of `width` glyphs, of which `ratio` (from 0.0 to 1.0, rounded to the nearest glyph) are
+/// [BLOCK_FULL] and the rest [BLOCK_LIGHT]. Used by [Gauge][super::Gauge] and
+/// [ProgressBar][super::ProgressBar].
+///
+/// Ratios outside of 0.0–1.0 are clamped, NaN is drawn as an empty bar.
+///
+/// # Example
+///
+/// ```
+/// use wups::config::glyphs::bar;
+///
+/// assert_eq!(bar(0.0, 10), "░░░░░░░░░░");
+/// assert_eq!(bar(0.5, 10), "█████░░░░░");
+/// assert_eq!(bar(1.0, 10), "██████████");
+/// assert_eq!(bar(2.5, 4), "████");
+/// assert_eq!(bar(f64::NAN, 4), "░░░░");
+/// ```
+pub fn bar(ratio: f64, width: usize) -> String {
+    let ratio = if ratio.is_nan() {
+        0.0
+    } else {
+        ratio.clamp(0.0, 1.0)
+    };
+    let filled = (ratio * width as f64 + 0.5) as usize;
+
+    let mut bar = String::with_capacity(width * BLOCK_FULL.len());
+    bar.extend(core::iter::repeat_n(BLOCK_FULL, filled));
+    bar.extend(core::iter::repeat_n(BLOCK_LIGHT, width - filled));
+    bar
+}
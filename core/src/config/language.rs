@@ -0,0 +1,123 @@
+//! Localized labels of config items.
+
+use crate::sync::{Mutex, OnceLock};
+use wut::{ffi::c_void, sys};
+
+/// System language of the console.
+///
+/// Read from the console settings (`cafe.language` of the user config, via `UCReadSysConfig`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Language {
+    Japanese = 0,
+    English = 1,
+    French = 2,
+    German = 3,
+    Italian = 4,
+    Spanish = 5,
+    SimplifiedChinese = 6,
+    Korean = 7,
+    Dutch = 8,
+    Portuguese = 9,
+    Russian = 10,
+    TraditionalChinese = 11,
+}
+
+impl Language {
+    /// Language from the console settings, or `None` if it can't be read.
+    pub fn system() -> Option<Self> {
+        static LANGUAGE: OnceLock<Option<Language>> = OnceLock::new();
+        *LANGUAGE.get_or_init(|| read_language().and_then(Self::from_u32))
+    }
+
+    fn from_u32(value: u32) -> Option<Self> {
+        use Language::*;
+        Some(match value {
+            0 => Japanese,
+            1 => English,
+            2 => French,
+            3 => German,
+            4 => Italian,
+            5 => Spanish,
+            6 => SimplifiedChinese,
+            7 => Korean,
+            8 => Dutch,
+            9 => Portuguese,
+            10 => Russian,
+            11 => TraditionalChinese,
+            _ => return None,
+        })
+    }
+}
+
+fn read_language() -> Option<u32> {
+    let handle = unsafe { sys::UCOpen() };
+    if handle < 0 {
+        return None;
+    }
+
+    let mut language = 0u32;
+    let mut config: sys::UCSysConfig = unsafe { core::mem::zeroed() };
+    config.access = 0x777;
+    config.dataType = sys::UCDataType::UC_DATATYPE_UNSIGNED_INT;
+    config.dataSize = core::mem::size_of::<u32>() as u32;
+    config.data = &mut language as *mut u32 as *mut c_void;
+    for (dst, src) in config.name.iter_mut().zip(c"cafe.language".to_bytes()) {
+        *dst = *src as _;
+    }
+
+    let status = unsafe { sys::UCReadSysConfig(handle, 1, &mut config) };
+    unsafe { sys::UCClose(handle) };
+
+    (status == sys::UCError::UC_ERROR_OK).then_some(language)
+}
+
+/// Looks up the label for a key in a language. `None` displays the key itself.
+pub type LabelProvider = fn(language: Language, key: &str) -> Option<&'static str>;
+
+static PROVIDER: Mutex<Option<LabelProvider>> = Mutex::new(None);
+
+/// Translate the text of all items with `provider`.
+///
+/// With a provider, the text of every item (and option) is used as a key: the provider is asked for
+/// its label in the [system language][Language::system] and the text itself is displayed if it
+/// returns `None`. Writing the keys in English therefore makes English the fallback for missing
+/// translations. Values shown with [Label::value][super::Label::value] aren't translated.
+///
+/// Items look up their labels when they are attached, so this should be called before the menu is
+/// opened, e.g. in [on_initialize][crate::on_initialize]. `None` removes the provider.
+///
+/// # Example
+///
+/// ```
+/// use config::Language;
+///
+/// fn labels(language: Language, key: &str) -> Option<&'static str> {
+///     match (language, key) {
+///         (Language::German, "Enabled") => Some("Aktiviert"),
+///         (Language::German, "Speed") => Some("Geschwindigkeit"),
+///         (Language::French, "Enabled") => Some("Activé"),
+///         _ => None,
+///     }
+/// }
+///
+/// config::set_label_provider(Some(labels));
+/// root.add(config::Toggle::new("Enabled", "enabled", true, "On", "Off"))?;
+/// ```
+pub fn set_label_provider(provider: Option<LabelProvider>) {
+    *PROVIDER.lock().unwrap() = provider;
+}
+
+/// Remove the provider, see [reset_statics][crate::reset_statics].
+pub(crate) fn reset() {
+    set_label_provider(None);
+}
+
+/// Label displayed for `key`, which is `key` itself if there is no translation.
+pub(crate) fn label(key: &str) -> &str {
+    let Some(provider) = *PROVIDER.lock().unwrap() else {
+        return key;
+    };
+    Language::system()
+        .and_then(|language| provider(language, key))
+        .unwrap_or(key)
+}
@@ -0,0 +1,710 @@
+//! Menu trees which can be stored, for plugins whose users edit the menu.
+//!
+//! The items of this module are consumed when they are attached, so a menu built from them can't
+//! be saved. A [Layout] describes the same tree as plain data instead: it can be edited, stored
+//! like any other value and attached when the menu is opened. Closures can't be stored, so buttons
+//! refer to a handler by name, which the plugin [registers][register_handler] at startup.
+
+use super::{Button, Label, MenuBuilder, MenuError, MenuItem, Range, Select, Toggle};
+use crate::{
+    storage::{StorageCompatible, StorageError},
+    sync::Mutex,
+};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use wups_sys as sys;
+use wut::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Handlers of [LayoutItem::Button], by name.
+static HANDLERS: Mutex<BTreeMap<String, Arc<dyn Fn() + Send + Sync>>> = Mutex::new(BTreeMap::new());
+
+/// Register `handler` under `name`, for buttons of a [Layout] which refer to it.
+///
+/// Registering a name again replaces its handler. Handlers stay registered when the menu is
+/// closed, so they are usually registered once in [on_initialize][crate::on_initialize].
+///
+/// # Example
+///
+/// ```
+/// config::register_handler("reset_volume", || {
+///     let _ = storage::delete("volume");
+/// });
+///
+/// let layout = config::Layout::new().item(config::LayoutItem::Button {
+///     text: "Reset volume".into(),
+///     handler: "reset_volume".into(),
+/// });
+/// root.add(layout)?;
+/// ```
+pub fn register_handler(name: &str, handler: impl Fn() + Send + Sync + 'static) {
+    HANDLERS
+        .lock()
+        .unwrap()
+        .insert(name.to_string(), Arc::new(handler));
+}
+
+fn handler(name: &str) -> Result<Arc<dyn Fn() + Send + Sync>, MenuError> {
+    HANDLERS
+        .lock()
+        .unwrap()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| MenuError::UnknownHandler(name.to_string()))
+}
+
+/// Forget all handlers, see [reset_statics][crate::reset_statics].
+pub(super) fn reset() {
+    HANDLERS.lock().unwrap().clear();
+}
+
+// region: Layout
+
+/// Menu tree which can be edited and [stored][crate::storage], see the [module](self) docs.
+///
+/// Adding a layout to a menu adds its items, so a layout added to the root describes the whole
+/// menu. Restored layouts are [validated][MenuItem::validate] when they are attached, as they may
+/// come from an older version of the plugin: an invalid item fails with the same error as the
+/// item it describes, a button whose handler isn't registered with
+/// [UnknownHandler][MenuError::UnknownHandler].
+///
+/// # Storage
+///
+/// A layout is stored as a binary item, which has to fit into one item (less than
+/// [max_item_size][crate::storage::max_item_size] bytes) like a [BTreeMap]. Texts and lists are
+/// limited to `u16::MAX` bytes or items; longer ones fail to store with
+/// [BufferTooSmall][StorageError::BufferTooSmall]. Data which isn't a layout of this version fails
+/// to load with [UnexpectedDataType][StorageError::UnexpectedDataType], so plugins can fall back
+/// to their default layout.
+///
+/// # Example
+///
+/// ```
+/// use config::LayoutItem;
+///
+/// fn default_layout() -> config::Layout {
+///     config::Layout::new()
+///         .item(LayoutItem::Toggle {
+///             text: "Enabled".into(),
+///             id: "enabled".into(),
+///             default: true,
+///             true_value: "On".into(),
+///             false_value: "Off".into(),
+///         })
+///         .item(LayoutItem::Menu {
+///             text: "Audio".into(),
+///             items: vec![LayoutItem::Range {
+///                 text: "Volume".into(),
+///                 id: "volume".into(),
+///                 default: 50,
+///                 min: 0,
+///                 max: 100,
+///             }],
+///         })
+/// }
+///
+/// let mut layout = storage::load::<config::Layout>("layout").unwrap_or_else(|_| default_layout());
+/// // e.g. the user moved "Audio" to the top
+/// layout.items_mut().swap(0, 1);
+/// storage::store::<config::Layout>("layout", layout.clone())?;
+///
+/// root.add(layout)?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layout {
+    items: Vec<LayoutItem>,
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an item to the layout.
+    pub fn item(mut self, item: LayoutItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Top-level items, in the order they are displayed.
+    pub fn items(&self) -> &[LayoutItem] {
+        &self.items
+    }
+
+    /// Top-level items, to rearrange or edit them.
+    pub fn items_mut(&mut self) -> &mut Vec<LayoutItem> {
+        &mut self.items
+    }
+
+    /// Check all items (including nested menus) without creating anything in the UI, like
+    /// [MenuBuilder::validate].
+    pub fn validate(&self) -> Result<(), Vec<MenuError>> {
+        let mut ids = BTreeSet::new();
+        let mut errors = Vec::new();
+        MenuItem::validate_into(self, &mut ids, &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl MenuItem for Layout {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        for item in self.items {
+            item.attach(handle)?;
+        }
+        Ok(())
+    }
+
+    fn validate_into(&self, ids: &mut BTreeSet<String>, errors: &mut Vec<MenuError>) {
+        for item in &self.items {
+            item.validate_into(ids, errors);
+        }
+    }
+}
+
+/// Encoded as a version byte followed by the items, see [LayoutItem].
+///
+/// ```
+/// use config::{Layout, LayoutItem};
+/// use wups::storage::StorageCompatible;
+///
+/// let layout = Layout::new()
+///     .item(LayoutItem::Label {
+///         text: "Version".into(),
+///         value: Some("1.0".into()),
+///     })
+///     .item(LayoutItem::Menu {
+///         text: "Video".into(),
+///         items: vec![
+///             LayoutItem::Select {
+///                 text: "Refresh rate".into(),
+///                 id: "refresh_rate".into(),
+///                 default: 60,
+///                 options: vec![(30, "30 Hz".into()), (60, "60 Hz".into())],
+///             },
+///             LayoutItem::Button {
+///                 text: "Reset".into(),
+///                 handler: "reset_video".into(),
+///             },
+///             LayoutItem::Menu {
+///                 text: "Empty".into(),
+///                 items: vec![],
+///             },
+///         ],
+///     });
+/// assert_eq!(Layout::decode(&Layout::encode(&layout)).unwrap(), layout);
+/// assert_eq!(Layout::decode(&Layout::encode(&Layout::new())).unwrap(), Layout::new());
+///
+/// // truncated data
+/// let bytes = Layout::encode(&layout);
+/// assert!(Layout::decode(&bytes[..bytes.len() - 1]).is_err());
+/// ```
+impl StorageCompatible for Layout {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        Self::decode(&Vec::<u8>::load(name)?)
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        Vec::<u8>::store(name, encode(&value)?)
+    }
+
+    /// Empty if a text or list is too long, see [storage](Layout#storage), which fails to decode.
+    fn encode(value: &Self::T) -> Vec<u8> {
+        encode(value).unwrap_or_default()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        let Some((&VERSION, mut bytes)) = bytes.split_first() else {
+            return Err(StorageError::UnexpectedDataType);
+        };
+        let items = Reader(&mut bytes).items(0)?;
+        if !bytes.is_empty() {
+            return Err(StorageError::UnexpectedDataType);
+        }
+        Ok(Self { items })
+    }
+}
+
+// endregion
+
+// region: LayoutItem
+
+/// Item of a [Layout], with the arguments of the item it describes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutItem {
+    /// [Label], with a value on the right like [Label::value].
+    Label { text: String, value: Option<String> },
+    /// [Toggle].
+    Toggle {
+        text: String,
+        id: String,
+        default: bool,
+        true_value: String,
+        false_value: String,
+    },
+    /// `i32` [Range].
+    Range {
+        text: String,
+        id: String,
+        default: i32,
+        min: i32,
+        max: i32,
+    },
+    /// [Select] whose options are stored as the given value, like [Select::with_values].
+    Select {
+        text: String,
+        id: String,
+        default: u32,
+        options: Vec<(u32, String)>,
+    },
+    /// [Button] which runs the handler [registered][register_handler] as `handler`.
+    Button { text: String, handler: String },
+    /// Sub menu, like [MenuBuilder].
+    Menu {
+        text: String,
+        items: Vec<LayoutItem>,
+    },
+}
+
+impl MenuItem for LayoutItem {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        // `Menu` validates its items when they are attached
+        if !matches!(self, Self::Menu { .. }) {
+            self.validate()?;
+        }
+
+        match self {
+            Self::Label { text, value: None } => Label::new(&text).attach(handle),
+            Self::Label {
+                text,
+                value: Some(value),
+            } => Label::value(&text, &value).attach(handle),
+            Self::Toggle {
+                text,
+                id,
+                default,
+                true_value,
+                false_value,
+            } => Toggle::new(&text, &id, default, &true_value, &false_value).attach(handle),
+            Self::Range {
+                text,
+                id,
+                default,
+                min,
+                max,
+            } => Range::new(&text, &id, default, min, max).attach(handle),
+            Self::Select {
+                text,
+                id,
+                default,
+                options,
+            } => Select::with_values(&text, &id, default, options).attach(handle),
+            Self::Button { text, handler } => {
+                let handler = self::handler(&handler)?;
+                Button::new(&text, move || handler()).attach(handle)
+            }
+            Self::Menu { text, items } => items
+                .into_iter()
+                .fold(MenuBuilder::new(&text), MenuBuilder::item)
+                .attach(handle),
+        }
+    }
+
+    fn id(&self) -> Option<&str> {
+        match self {
+            Self::Toggle { id, .. } | Self::Range { id, .. } | Self::Select { id, .. } => Some(id),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), MenuError> {
+        match self {
+            // checked here, as `Range::new` asserts it
+            &Self::Range {
+                ref id,
+                default,
+                min,
+                max,
+                ..
+            } if !(min < max && min <= default && default <= max) => Err(MenuError::InvalidRange {
+                id: id.clone(),
+                min,
+                default,
+                max,
+            }),
+            Self::Select {
+                text,
+                id,
+                default,
+                options,
+            } => Select::with_values(text, id, *default, options.clone()).validate(),
+            Self::Button { handler, .. } => self::handler(handler).map(drop),
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_into(&self, ids: &mut BTreeSet<String>, errors: &mut Vec<MenuError>) {
+        if let Self::Menu { items, .. } = self {
+            for item in items {
+                item.validate_into(ids, errors);
+            }
+            return;
+        }
+
+        if let Some(id) = self.id()
+            && !ids.insert(id.to_string())
+        {
+            errors.push(MenuError::DuplicateIdentifier(id.to_string()));
+        }
+        if let Err(e) = self.validate() {
+            errors.push(e);
+        }
+    }
+}
+
+// endregion
+
+// region: Encoding
+
+// Items are encoded as a tag byte followed by their fields in declaration order. Strings and lists
+// are prefixed with their length as a big-endian `u16`, numbers are big-endian.
+
+/// Version of the encoding, which is the first byte of a stored layout.
+const VERSION: u8 = 1;
+
+/// Deepest nesting of menus which is decoded, so malformed data can't overflow the stack.
+const MAX_DEPTH: usize = 16;
+
+const LABEL: u8 = 0;
+const TOGGLE: u8 = 1;
+const RANGE: u8 = 2;
+const SELECT: u8 = 3;
+const BUTTON: u8 = 4;
+const MENU: u8 = 5;
+
+fn encode(layout: &Layout) -> Result<Vec<u8>, StorageError> {
+    let mut bytes = alloc::vec![VERSION];
+    encode_items(&mut bytes, &layout.items)?;
+    Ok(bytes)
+}
+
+/// Fails with [BufferTooSmall][StorageError::BufferTooSmall] if `len` doesn't fit into the prefix.
+fn encode_len(bytes: &mut Vec<u8>, len: usize) -> Result<(), StorageError> {
+    let len = u16::try_from(len).map_err(|_| StorageError::BufferTooSmall)?;
+    bytes.extend_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+fn encode_str(bytes: &mut Vec<u8>, s: &str) -> Result<(), StorageError> {
+    encode_len(bytes, s.len())?;
+    bytes.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn encode_items(bytes: &mut Vec<u8>, items: &[LayoutItem]) -> Result<(), StorageError> {
+    encode_len(bytes, items.len())?;
+    for item in items {
+        encode_item(bytes, item)?;
+    }
+    Ok(())
+}
+
+fn encode_item(bytes: &mut Vec<u8>, item: &LayoutItem) -> Result<(), StorageError> {
+    match item {
+        LayoutItem::Label { text, value } => {
+            bytes.push(LABEL);
+            encode_str(bytes, text)?;
+            match value {
+                Some(value) => {
+                    bytes.push(1);
+                    encode_str(bytes, value)?;
+                }
+                None => bytes.push(0),
+            }
+        }
+        LayoutItem::Toggle {
+            text,
+            id,
+            default,
+            true_value,
+            false_value,
+        } => {
+            bytes.push(TOGGLE);
+            encode_str(bytes, text)?;
+            encode_str(bytes, id)?;
+            bytes.push(*default as u8);
+            encode_str(bytes, true_value)?;
+            encode_str(bytes, false_value)?;
+        }
+        LayoutItem::Range {
+            text,
+            id,
+            default,
+            min,
+            max,
+        } => {
+            bytes.push(RANGE);
+            encode_str(bytes, text)?;
+            encode_str(bytes, id)?;
+            for value in [default, min, max] {
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        LayoutItem::Select {
+            text,
+            id,
+            default,
+            options,
+        } => {
+            bytes.push(SELECT);
+            encode_str(bytes, text)?;
+            encode_str(bytes, id)?;
+            bytes.extend_from_slice(&default.to_be_bytes());
+            encode_len(bytes, options.len())?;
+            for (value, text) in options {
+                bytes.extend_from_slice(&value.to_be_bytes());
+                encode_str(bytes, text)?;
+            }
+        }
+        LayoutItem::Button { text, handler } => {
+            bytes.push(BUTTON);
+            encode_str(bytes, text)?;
+            encode_str(bytes, handler)?;
+        }
+        LayoutItem::Menu { text, items } => {
+            bytes.push(MENU);
+            encode_str(bytes, text)?;
+            encode_items(bytes, items)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decodes from the front of the slice, failing with
+/// [UnexpectedDataType][StorageError::UnexpectedDataType] if it ends early.
+struct Reader<'a, 'b>(&'b mut &'a [u8]);
+
+impl Reader<'_, '_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], StorageError> {
+        let (chunk, rest) = self
+            .0
+            .split_first_chunk::<N>()
+            .ok_or(StorageError::UnexpectedDataType)?;
+        *self.0 = rest;
+        Ok(*chunk)
+    }
+
+    fn len(&mut self) -> Result<usize, StorageError> {
+        Ok(u16::from_be_bytes(self.take()?) as usize)
+    }
+
+    fn bool(&mut self) -> Result<bool, StorageError> {
+        match self.take::<1>()? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(StorageError::UnexpectedDataType),
+        }
+    }
+
+    fn i32(&mut self) -> Result<i32, StorageError> {
+        Ok(i32::from_be_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, StorageError> {
+        Ok(u32::from_be_bytes(self.take()?))
+    }
+
+    fn string(&mut self) -> Result<String, StorageError> {
+        let len = self.len()?;
+        if self.0.len() < len {
+            return Err(StorageError::UnexpectedDataType);
+        }
+        let (s, rest) = self.0.split_at(len);
+        *self.0 = rest;
+        core::str::from_utf8(s)
+            .map(ToString::to_string)
+            .map_err(|_| StorageError::UnexpectedDataType)
+    }
+
+    fn items(&mut self, depth: usize) -> Result<Vec<LayoutItem>, StorageError> {
+        if depth > MAX_DEPTH {
+            return Err(StorageError::UnexpectedDataType);
+        }
+        (0..self.len()?).map(|_| self.item(depth)).collect()
+    }
+
+    fn item(&mut self, depth: usize) -> Result<LayoutItem, StorageError> {
+        let [tag] = self.take()?;
+        Ok(match tag {
+            LABEL => LayoutItem::Label {
+                text: self.string()?,
+                value: self.bool()?.then(|| self.string()).transpose()?,
+            },
+            TOGGLE => LayoutItem::Toggle {
+                text: self.string()?,
+                id: self.string()?,
+                default: self.bool()?,
+                true_value: self.string()?,
+                false_value: self.string()?,
+            },
+            RANGE => LayoutItem::Range {
+                text: self.string()?,
+                id: self.string()?,
+                default: self.i32()?,
+                min: self.i32()?,
+                max: self.i32()?,
+            },
+            SELECT => LayoutItem::Select {
+                text: self.string()?,
+                id: self.string()?,
+                default: self.u32()?,
+                options: (0..self.len()?)
+                    .map(|_| Ok((self.u32()?, self.string()?)))
+                    .collect::<Result<_, StorageError>>()?,
+            },
+            BUTTON => LayoutItem::Button {
+                text: self.string()?,
+                handler: self.string()?,
+            },
+            MENU => LayoutItem::Menu {
+                text: self.string()?,
+                items: self.items(depth + 1)?,
+            },
+            _ => return Err(StorageError::UnexpectedDataType),
+        })
+    }
+}
+
+// endregion
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{self, mock};
+    use alloc::vec;
+
+    fn layout() -> Layout {
+        let s = |s: &str| s.to_string();
+        Layout::new()
+            .item(LayoutItem::Label {
+                text: s("Version"),
+                value: Some(s("1.0")),
+            })
+            .item(LayoutItem::Label {
+                text: s("Café"),
+                value: None,
+            })
+            .item(LayoutItem::Toggle {
+                text: s("Enabled"),
+                id: s("enabled"),
+                default: true,
+                true_value: s("On"),
+                false_value: s("Off"),
+            })
+            .item(LayoutItem::Range {
+                text: s("Offset"),
+                id: s("offset"),
+                default: -5,
+                min: i32::MIN,
+                max: 7,
+            })
+            .item(LayoutItem::Menu {
+                text: s("Video"),
+                items: vec![
+                    LayoutItem::Select {
+                        text: s("Refresh rate"),
+                        id: s("refresh_rate"),
+                        default: 60,
+                        options: vec![(30, s("30 Hz")), (60, s("60 Hz"))],
+                    },
+                    LayoutItem::Button {
+                        text: s("Reset"),
+                        handler: s("reset_video"),
+                    },
+                    LayoutItem::Menu {
+                        text: s("Empty"),
+                        items: vec![],
+                    },
+                ],
+            })
+    }
+
+    #[test]
+    fn round_trip() {
+        let layout = layout();
+        assert_eq!(Layout::decode(&Layout::encode(&layout)), Ok(layout));
+        assert_eq!(
+            Layout::decode(&Layout::encode(&Layout::new())),
+            Ok(Layout::new())
+        );
+    }
+
+    #[test]
+    fn store_and_load() {
+        let _lock = mock::lock();
+
+        storage::store::<Layout>("layout", layout()).unwrap();
+        assert_eq!(storage::load::<Layout>("layout"), Ok(layout()));
+    }
+
+    #[test]
+    fn malformed() {
+        let bytes = Layout::encode(&layout());
+        for len in 0..bytes.len() {
+            assert!(Layout::decode(&bytes[..len]).is_err(), "{len} bytes");
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Layout::decode(&trailing).is_err());
+
+        let mut version = bytes;
+        version[0] = VERSION + 1;
+        assert!(Layout::decode(&version).is_err());
+    }
+
+    #[test]
+    fn too_deep() {
+        // one top-level menu, nested in itself
+        let mut bytes = vec![VERSION, 0, 1];
+        for _ in 0..=MAX_DEPTH {
+            bytes.extend([MENU, 0, 0, 0, 1]);
+        }
+        assert_eq!(
+            Layout::decode(&bytes),
+            Err(StorageError::UnexpectedDataType)
+        );
+    }
+
+    #[test]
+    fn too_long() {
+        let _lock = mock::lock();
+
+        let layout = Layout::new().item(LayoutItem::Label {
+            text: "a".repeat(u16::MAX as usize + 1),
+            value: None,
+        });
+        storage::set_max_item_size(u32::MAX);
+        assert_eq!(
+            storage::store::<Layout>("layout", layout.clone()),
+            Err(StorageError::BufferTooSmall)
+        );
+        assert!(Layout::encode(&layout).is_empty());
+        assert_eq!(
+            storage::load::<Layout>("layout"),
+            Err(StorageError::NotFound)
+        );
+    }
+}
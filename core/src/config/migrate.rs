@@ -0,0 +1,122 @@
+//! Values of items whose type changed between versions, see
+//! [storage types](super#storage-types).
+
+use crate::storage::{self, StorageCompatible, StorageError, StorageType};
+
+/// Load the value of the item `id` like [storage::load], but handle a value of another type.
+///
+/// If the stored value can be represented exactly as `T`, it is converted and stored again.
+/// Otherwise the key is deleted and [NotFound][StorageError::NotFound] is returned, so the item
+/// falls back to its default like on the first run.
+///
+/// Values of an open [SettingsMenu][super::SettingsMenu] are taken from its settings instead.
+pub(super) fn load<T: StorageCompatible>(id: &str) -> Result<T::T, StorageError> {
+    if let Some(value) = super::preload::get::<T>(id) {
+        return value;
+    }
+
+    let (expected, actual) = match storage::load::<T>(id) {
+        Err(StorageError::TypeMismatch { expected, actual }) => (expected, actual),
+        result => return result,
+    };
+
+    if let Some(value) = read_integer(id, actual)?
+        && convert(id, expected, value)?
+    {
+        wut::println!("Config item \"{id}\" was stored as {actual:?}, migrated to {expected:?}");
+        return storage::load::<T>(id);
+    }
+
+    wut::println!("Config item \"{id}\" was stored as {actual:?}, reset to its default");
+    storage::delete(id)?;
+    Err(StorageError::NotFound)
+}
+
+/// Stored value as an integer, or `None` if it isn't one (or a float without fraction).
+fn read_integer(id: &str, actual: StorageType) -> Result<Option<i64>, StorageError> {
+    let float = |value: f64| Some(value as i64).filter(|v| *v as f64 == value);
+    Ok(match actual {
+        StorageType::Bool => Some(storage::load::<bool>(id)?.into()),
+        StorageType::S32 => Some(storage::load::<i32>(id)?.into()),
+        StorageType::U32 => Some(storage::load::<u32>(id)?.into()),
+        StorageType::S64 => Some(storage::load::<i64>(id)?),
+        StorageType::U64 => i64::try_from(storage::load::<u64>(id)?).ok(),
+        StorageType::Float => float(storage::load::<f32>(id)?.into()),
+        StorageType::Double => float(storage::load::<f64>(id)?),
+        StorageType::String | StorageType::Binary => None,
+    })
+}
+
+/// Store `value` as `expected`. Returns `false` if it doesn't fit, e.g. `-1` as `U32`.
+fn convert(id: &str, expected: StorageType, value: i64) -> Result<bool, StorageError> {
+    let stored = match expected {
+        StorageType::Bool => {
+            (value == 0 || value == 1).then(|| storage::store::<bool>(id, value == 1))
+        }
+        StorageType::S32 => i32::try_from(value)
+            .ok()
+            .map(|v| storage::store::<i32>(id, v)),
+        StorageType::U32 => u32::try_from(value)
+            .ok()
+            .map(|v| storage::store::<u32>(id, v)),
+        StorageType::S64 => Some(storage::store::<i64>(id, value)),
+        StorageType::U64 => u64::try_from(value)
+            .ok()
+            .map(|v| storage::store::<u64>(id, v)),
+        // integers up to 2^24 are exact
+        StorageType::Float => {
+            (value.unsigned_abs() <= 1 << 24).then(|| storage::store::<f32>(id, value as f32))
+        }
+        _ => None,
+    };
+    stored.transpose().map(|stored| stored.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::mock;
+    use wut::string::String;
+
+    #[test]
+    fn converts_exact_values() {
+        let _lock = mock::lock();
+        storage::store::<bool>("enabled", true).unwrap();
+        storage::store::<f32>("volume", 80.0).unwrap();
+        storage::store::<i32>("count", 3).unwrap();
+
+        // a toggle which became a select
+        assert_eq!(load::<u32>("enabled"), Ok(1));
+        assert_eq!(storage::load::<u32>("enabled"), Ok(1));
+        // a float range which became an integer one
+        assert_eq!(load::<i32>("volume"), Ok(80));
+        assert_eq!(storage::load::<i32>("volume"), Ok(80));
+        assert_eq!(load::<f32>("count"), Ok(3.0));
+        assert_eq!(load::<i32>("count"), Ok(3));
+    }
+
+    #[test]
+    fn resets_other_values() {
+        let _lock = mock::lock();
+        storage::store::<i32>("offset", -1).unwrap();
+        storage::store::<f32>("volume", 0.5).unwrap();
+        storage::store::<u32>("mode", 2).unwrap();
+        storage::store::<String>("name", "Mario".into()).unwrap();
+
+        for id in ["offset", "volume", "name"] {
+            assert_eq!(load::<u32>(id), Err(StorageError::NotFound));
+            assert_eq!(storage::load::<u32>(id), Err(StorageError::NotFound));
+        }
+        assert_eq!(load::<bool>("mode"), Err(StorageError::NotFound));
+        assert_eq!(storage::load::<u32>("mode"), Err(StorageError::NotFound));
+    }
+
+    #[test]
+    fn passes_other_results_through() {
+        let _lock = mock::lock();
+        storage::store::<i32>("count", -3).unwrap();
+
+        assert_eq!(load::<i32>("count"), Ok(-3));
+        assert_eq!(load::<i32>("missing"), Err(StorageError::NotFound));
+    }
+}
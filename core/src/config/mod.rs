@@ -1,8 +1,99 @@
 //! Config Menu in the Aroma Plugin Menu
+//!
+//! # Navigation
+//!
+//! The menu always opens at its root and the user navigates it; the config API
+//! (`WUPSConfigAPI_*`) has no function to open or focus a category, so there can't be something like
+//! a `focus_category`. To guide users to an important category, put it first: items and categories
+//! are shown in the order they are added, and [MenuBuilder::weighted_item] moves an item to the
+//! front regardless of where it is added. Related items which don't need their own screen can
+//! be put under a header with [Group] instead of a sub menu.
+//!
+//! ```
+//! root.add(
+//!     config::MenuBuilder::new("Settings")
+//!         .item(config::Toggle::new("Enabled", "enabled", true, "On", "Off"))
+//!         .weighted_item(-100, config::MenuBuilder::new("Start here")),
+//! )?;
+//! ```
+//!
+//! # Storage types
+//!
+//! Each item stores its value as a fixed type: [Toggle] as `bool`, [Range], [NumberInput] and
+//! [RangePair] as `i32` (`Range<f32>` as `f32`), [Select], [Stepper] and [MultiSelect] as `u32`
+//! and [TextInput] as `String`. If a new version of the plugin reuses an id for an item of another
+//! type (e.g. a `Range` becomes a `Select`), the value from the previous version has the wrong
+//! type. Instead of failing the whole menu with
+//! [TypeMismatch][storage::StorageError::TypeMismatch], the item checks the stored type when it is
+//! attached and
+//!
+//! - **migrates** the value if it can be represented exactly in the new type, e.g. `2i32` becomes
+//!   `2u32` and `1u32` becomes `true`; floats only if they have no fraction
+//! - **clears** the key otherwise, e.g. `-1i32` for a `Select` or a string, so the item starts at
+//!   its default as on the first run
+//!
+//! Both cases log a warning. A migrated value still has to be a valid value of the item, otherwise
+//! the item falls back to its default like for any other out of range value. Code which reads the
+//! id with [storage::load] before the menu was opened still gets the error, so plugins changing
+//! the type of an id should read it with [storage::load_or_default] or use a new id.
+//!
+//! ```
+//! storage::store::<i32>("quality", 2)?;
+//! storage::store::<i32>("mode", -1)?;
+//!
+//! root.add(config::Select::new("Quality", "quality", 0, vec!["Low", "Medium", "High"]))?;
+//! root.add(config::Select::new("Mode", "mode", 0, vec!["A", "B"]))?;
+//!
+//! assert_eq!(storage::load::<u32>("quality"), Ok(2)); // migrated
+//! assert_eq!(storage::load::<u32>("mode"), Ok(0)); // cleared, then the default was stored
+//! ```
+//!
+//! # Testing
+//!
+//! There is no host-side harness which renders a [ConfigMenu]: items call the config API as soon
+//! as they are attached, and this crate has no mock or in-memory backend for it (nor for storage)
+//! to run against. What can be checked without a console is the definition of a menu built with
+//! [MenuBuilder], as [validate][MenuBuilder::validate] doesn't touch the UI or storage. Building
+//! the menu in a separate function lets `open` and the check share it:
+//!
+//! ```
+//! fn menu() -> config::MenuBuilder {
+//!     config::MenuBuilder::new("Settings")
+//!         .item(config::Toggle::new("Enabled", "enabled", true, "On", "Off"))
+//!         .item(config::Range::new("Volume", "volume", 50, 0, 100))
+//! }
+//!
+//! // duplicate ids, empty selects, defaults outside of their range, ...
+//! assert_eq!(menu().validate(), Ok(()));
+//! ```
 
 // this is quite kinda swag frfr ngl but overkill for now: https://github.com/dkosmari/libwupsxx
 
-use crate::storage;
+mod apply;
+mod custom;
+pub mod glyphs;
+mod language;
+mod layout;
+mod migrate;
+mod open;
+mod preload;
+pub mod raw;
+mod registry;
+mod staging;
+
+pub use language::{LabelProvider, Language, set_label_provider};
+pub use layout::{Layout, LayoutItem, register_handler};
+pub use open::{inject_open_request, is_menu_open, request_open_menu};
+
+use crate::{
+    storage,
+    sync::{Mutex, OnceLock},
+};
+use alloc::{boxed::Box, collections::BTreeSet, sync::Arc};
+use core::{
+    any::Any,
+    sync::atomic::{AtomicI32, AtomicU32, Ordering},
+};
 use thiserror::Error;
 use wups_sys as sys;
 use wut::{
@@ -13,7 +104,10 @@ use wut::{
 
 // region: MenuError
 
-#[derive(Debug, Error)]
+/// Errors of the config API.
+///
+/// All variants can be compared; [Unknown][MenuError::Unknown] compares the raw status code.
+#[derive(Debug, Error, PartialEq, Eq)]
 pub enum MenuError {
     #[error("Unknown error")]
     Unknown(sys::WUPSConfigAPIStatus::Type),
@@ -35,6 +129,10 @@ pub enum MenuError {
     ModuleMissingExport,
     #[error("")]
     UnsupportedVersion,
+    #[error(
+        "Plugin loader (config API version {api:#x}) doesn't support options version {options}, Aroma needs to be updated"
+    )]
+    UnsupportedOptions { options: u32, api: u32 },
     #[error("")]
     UnsupportedCommand,
     #[error("")]
@@ -43,6 +141,49 @@ pub enum MenuError {
     STORAGE(#[from] storage::StorageError),
     #[error("Internal 0-bytes")]
     InternalNullByte(#[from] NulError),
+    #[error("Storage id \"{0}\" is used by multiple items")]
+    DuplicateIdentifier(String),
+    #[error(
+        "Range \"{id}\" requires min <= default <= max and min < max (got {min}, {default}, {max})"
+    )]
+    InvalidRange {
+        id: String,
+        min: i32,
+        default: i32,
+        max: i32,
+    },
+    #[error("Float range \"{0}\" requires min <= default <= max, min < max and at least one step")]
+    InvalidFloatRange(String),
+    #[error("Select \"{0}\" has no options")]
+    EmptySelect(String),
+    #[error("Default of select \"{0}\" is not one of its options")]
+    InvalidSelectDefault(String),
+    #[error("Multi-select \"{0}\" has more than 32 options")]
+    TooManyOptions(String),
+    #[error(
+        "Text input \"{id}\" requires default <= max_length < 1024 (got {default}, {max_length})"
+    )]
+    InvalidTextLength {
+        id: String,
+        default: usize,
+        max_length: usize,
+    },
+    /// Two items registered a change callback for the same identifier, e.g. because they were
+    /// attached with the same id. Without this error, changing one item would run the callback of
+    /// the other.
+    ///
+    /// ```
+    /// root.add(config::RangePair::new("Delay", "delay", (2, 5), 0, 10))?;
+    /// assert_eq!(
+    ///     root.add(config::RangePair::new("Other delay", "delay", (1, 3), 0, 10)),
+    ///     Err(config::MenuError::DuplicateCallback("delay.min".into()))
+    /// );
+    /// ```
+    #[error("Item \"{0}\" already has a change callback")]
+    DuplicateCallback(String),
+    /// A button of a [Layout] refers to a handler which wasn't [registered][register_handler].
+    #[error("No handler \"{0}\" is registered")]
+    UnknownHandler(String),
 }
 
 impl TryFrom<sys::WUPSConfigAPICallbackStatus::Type> for MenuError {
@@ -82,10 +223,59 @@ impl TryFrom<sys::WUPSConfigAPICallbackStatus::Type> for MenuError {
 
 // endregion
 
+// region: Version
+
+static API_VERSION: OnceLock<sys::WUPSConfigAPIVersion> = OnceLock::new();
+
+/// Version of the config API implemented by the plugin loader.
+///
+/// The version is queried once and cached afterwards.
+///
+/// Menus are created with the newest options this crate knows (categories V1, items V2). There is
+/// no older version of them to fall back to, so if the loader rejects them the call fails with
+/// [UnsupportedOptions][MenuError::UnsupportedOptions] instead of the bare
+/// [UnsupportedVersion][MenuError::UnsupportedVersion], which tells the user to update Aroma.
+///
+/// # Example
+///
+/// ```
+/// if config::api_version()? < 2 {
+///     // skip custom items on old loaders
+/// }
+/// ```
+pub fn api_version() -> Result<sys::WUPSConfigAPIVersion, MenuError> {
+    if let Some(version) = API_VERSION.get() {
+        return Ok(*version);
+    }
+
+    let mut version = sys::WUPS_CONFIG_API_VERSION_ERROR;
+    let status = unsafe { sys::WUPSConfigAPI_GetVersion(&mut version) };
+    MenuError::try_from(status)?;
+
+    Ok(*API_VERSION.get_or_init(|| version))
+}
+
+/// Check the `status` of a call which used `options` as options version.
+fn check_options(status: sys::WUPSConfigAPIStatus::Type, options: u32) -> Result<(), MenuError> {
+    match MenuError::try_from(status) {
+        Err(MenuError::UnsupportedVersion) => Err(MenuError::UnsupportedOptions {
+            options,
+            api: api_version().unwrap_or(sys::WUPS_CONFIG_API_VERSION_ERROR),
+        }),
+        result => result.map(|_| ()),
+    }
+}
+
+// endregion
+
 /// Used for creating **stateless** config menu. Information is stored via [storage][crate::storage].
 ///
 /// Open the menu by pressing "↓ + L + Minus" on the gamepad.
 ///
+/// The open combo is a setting of the Aroma plugin loader itself (changeable in its own config
+/// menu) and applies to all plugins. The WUPS config API doesn't expose it, so a plugin can
+/// neither read nor remap it and there is no storage-backed item for it.
+///
 /// # Example
 /// ```
 /// struct MyMenu;
@@ -102,15 +292,13 @@ pub trait ConfigMenu {
     ///
     /// **Should not be overwritten unless special control is required.**
     fn init(name: &str) -> Result<(), MenuError> {
-        let name = CString::new(name).unwrap();
-        let opt = sys::WUPSConfigAPIOptionsV1 {
-            name: name.as_ptr(),
-        };
+        let name = CString::new(name)?;
+        let opt = raw::init_options_v1(&name);
 
         let status = unsafe {
             sys::WUPSConfigAPI_Init(opt, Some(Self::_open_callback), Some(Self::_close_callback))
         };
-        MenuError::try_from(status)?;
+        check_options(status, 1)?;
 
         Ok(())
     }
@@ -122,7 +310,27 @@ pub trait ConfigMenu {
         root: sys::WUPSConfigCategoryHandle,
     ) -> sys::WUPSConfigAPICallbackStatus::Type {
         use sys::WUPSConfigAPICallbackStatus as S;
-        match Self::open(MenuRoot::from(root)) {
+
+        open::opened();
+        if Self::staged_transactional() || Self::with_apply_revert() {
+            staging::begin();
+        }
+
+        let root = MenuRoot::from(root);
+        let result = match Self::header() {
+            Some(header) => root.add(header),
+            None => Ok(()),
+        }
+        .and_then(|_| {
+            if Self::with_apply_revert() {
+                apply::attach(&root)
+            } else {
+                Ok(())
+            }
+        })
+        .and_then(|_| Self::open(root));
+
+        match result {
             Ok(_) => S::WUPSCONFIG_API_CALLBACK_RESULT_SUCCESS,
             Err(_) => S::WUPSCONFIG_API_CALLBACK_RESULT_ERROR,
         }
@@ -131,7 +339,116 @@ pub trait ConfigMenu {
     /// C callback function for config menu
     ///
     /// **Should not be overwritten unless special control is required.**
-    extern "C" fn _close_callback() {}
+    extern "C" fn _close_callback() {
+        registry::clear();
+        open::closed();
+        apply::reset();
+
+        let result = staging::finish()
+            .map_err(MenuError::from)
+            .and_then(|_| Self::close());
+        if let Err(e) = result {
+            Self::close_failed(e);
+        }
+    }
+
+    /// Whether changes are staged and committed together when the menu is closed.
+    ///
+    /// By default (`false`), items write into storage as soon as the user changes them and the
+    /// storage is saved on close. When `true`, changes are only collected while the menu is open
+    /// and written in a single [transaction][storage::transaction] when it is closed.
+    ///
+    /// # Failure semantics
+    ///
+    /// If any of the writes fails, all of them are rolled back: storage is left exactly as it was
+    /// before the menu was opened and none of the user's changes are applied.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// struct MyMenu;
+    /// impl ConfigMenu for MyMenu {
+    ///     fn staged_transactional() -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn open(root: config::MenuRoot) -> Result<(), config::MenuError> {
+    ///         root.add(config::Toggle::new("Enabled", "enabled", true, "On", "Off"))?;
+    ///         root.add(config::Range::new("Volume", "volume", 50, 0, 100))?;
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    fn staged_transactional() -> bool {
+        false
+    }
+
+    /// Whether "Apply" and "Revert" buttons are added at the top of the menu (below the
+    /// [header][ConfigMenu::header]).
+    ///
+    /// This implies [staged_transactional][ConfigMenu::staged_transactional]: changes are collected
+    /// while the menu is open instead of being written right away.
+    ///
+    /// - **Apply** writes the collected changes in one [transaction][storage::transaction], like
+    ///   closing the menu would, and remembers the values they replaced in a
+    ///   [Snapshot][storage::Snapshot]. If the transaction fails, the changes stay collected.
+    /// - **Revert** discards the collected changes and restores the values from before every apply,
+    ///   so the keys of the items are back to how they were when the menu was opened.
+    ///
+    /// # Closing the menu
+    ///
+    /// The default save-on-close still applies: closing the menu commits the changes which are
+    /// still collected and saves the storage, so **Revert is the only way to discard changes**.
+    /// Snapshots are dropped on close, changes applied in an earlier session can't be reverted.
+    ///
+    /// The items themselves can't be reset by the plugin, so after Revert they keep showing the
+    /// discarded values until the menu is opened again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// struct MyMenu;
+    /// impl ConfigMenu for MyMenu {
+    ///     fn with_apply_revert() -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn open(root: config::MenuRoot) -> Result<(), config::MenuError> {
+    ///         root.add(config::Range::new("Volume", "volume", 50, 0, 100))?;
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    fn with_apply_revert() -> bool {
+        false
+    }
+
+    /// Line shown above the items of [open][ConfigMenu::open], see [Header].
+    ///
+    /// Called every time the menu is opened. `None` (the default) shows no header.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// WUPS_PLUGIN_NAME!("My Plugin");
+    ///
+    /// struct MyMenu;
+    /// impl ConfigMenu for MyMenu {
+    ///     fn header() -> Option<config::Header> {
+    ///         let enabled = storage::load_or_default::<bool>("enabled");
+    ///         let status = if enabled { "Active" } else { "Off" };
+    ///         Some(config::Header::new(PLUGIN_NAME, PLUGIN_VERSION).status(|| status.into()))
+    ///     }
+    ///
+    ///     fn open(root: config::MenuRoot) -> Result<(), config::MenuError> {
+    ///         root.add(config::Toggle::new("Enabled", "enabled", true, "On", "Off"))?;
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    fn header() -> Option<Header> {
+        None
+    }
 
     /// Open callback.
     ///
@@ -142,442 +459,3450 @@ pub trait ConfigMenu {
 
     /// Close callback.
     ///
-    /// Called when the plugin menu is closed.
+    /// Called when the plugin menu is closed, after [staged][ConfigMenu::staged_transactional]
+    /// changes were committed.
     fn close() -> Result<(), MenuError> {
         storage::save(false)?;
         Ok(())
     }
-}
-
-pub trait MenuItem {
-    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError>;
-}
-
-pub trait Attachable {
-    fn add(&self, item: impl MenuItem) -> Result<(), MenuError>;
-}
-
-// region: MenuRoot
-
-pub struct MenuRoot(sys::WUPSConfigCategoryHandle);
 
-impl From<sys::WUPSConfigCategoryHandle> for MenuRoot {
-    fn from(value: sys::WUPSConfigCategoryHandle) -> Self {
-        Self(value)
+    /// Called with the error if closing the menu failed, e.g. because the storage couldn't be
+    /// saved. The C API offers no way to report it, so by default it is only logged.
+    fn close_failed(error: MenuError) {
+        report_close_error(&error);
     }
 }
 
-impl Attachable for MenuRoot {
-    fn add(&self, item: impl MenuItem) -> Result<(), MenuError> {
-        item.attach(self.0)
-    }
+/// Default of `close_failed` of the menu traits.
+fn report_close_error(error: &MenuError) {
+    wut::println!("Closing the config menu failed: {error:?}");
 }
 
-// endregion
-
-// region: Menu
-
-/// Sub menu containing items.
+/// Config menu operating on a typed [Storable][storage::Storable] settings struct.
+///
+/// Instead of every item reading storage on its own, the settings are loaded once and the items
+/// take their current values from them.
+///
+/// # Lifecycle
+///
+/// 1. Menu is opened: `Settings` are loaded from storage.
+/// 2. [prepare][SettingsMenu::prepare] can modify them, e.g. to clamp values.
+/// 3. [open][SettingsMenu::open] adds the items. Items whose id is a key of the settings (see
+///    [Storable::entries][storage::Storable::entries]) show its value without reading storage;
+///    other items read storage as usual.
+/// 4. The user interacts with the items, which write into storage as usual.
+/// 5. Menu is closed: `Settings` are loaded again (now containing the user's changes) and
+///    prepared again, [close][SettingsMenu::close] is called, the settings are stored and the
+///    storage is saved.
+///
+/// Opening the menu doesn't write anything, so the settings are only stored when it is closed.
+/// Values changed by `prepare` are shown right away, but only stored then, which is why it runs
+/// again on the values the user didn't change; it should give the same result when it is applied
+/// twice, like clamping does.
 ///
 /// # Example
 ///
 /// ```
-/// let sub = config::Menu::new("Menu 1")?;
-/// sub.add(config::Label::new("Label 1"))?;
-/// sub.add(config::Label::new("Label 2"))?;
-/// root.add(sub)?;
+/// #[derive(Default, Storable)]
+/// struct Settings {
+///     enabled: bool,
+///     volume: i32,
+/// }
+///
+/// struct MyMenu;
+/// impl SettingsMenu for MyMenu {
+///     type Settings = Settings;
+///
+///     fn prepare(settings: &mut Settings) -> Result<(), config::MenuError> {
+///         settings.volume = settings.volume.clamp(0, 100);
+///         Ok(())
+///     }
+///
+///     fn open(settings: &Settings, root: config::MenuRoot) -> Result<(), config::MenuError> {
+///         root.add(config::Toggle::new("Enabled", "enabled", false, "On", "Off"))?;
+///         if settings.enabled {
+///             root.add(config::Range::new("Volume", "volume", 50, 0, 100))?;
+///         }
+///         Ok(())
+///     }
+/// }
 /// ```
-pub struct Menu {
-    text: String,
-    handle: sys::WUPSConfigCategoryHandle,
-}
+pub trait SettingsMenu {
+    type Settings: storage::Storable;
 
-impl Menu {
-    pub fn new(text: &str) -> Result<Self, MenuError> {
-        let mut handle = sys::WUPSConfigCategoryHandle::default();
-        let c_text = CString::new(text).unwrap();
-
-        let opt = sys::WUPSConfigAPICreateCategoryOptions {
-            version: sys::WUPS_API_CATEGORY_OPTION_VERSION_V1,
-            data: sys::WUPSConfigAPICreateCategoryOptions__bindgen_ty_1 {
-                v1: sys::WUPSConfigAPICreateCategoryOptionsV1 {
-                    name: c_text.as_ptr(),
-                },
-            },
+    /// Initialize the config menu
+    ///
+    /// Should be called inside the [on_initialize][crate::on_initialize] function.
+    ///
+    /// **Should not be overwritten unless special control is required.**
+    fn init(name: &str) -> Result<(), MenuError> {
+        let name = CString::new(name)?;
+        let opt = raw::init_options_v1(&name);
+
+        let status = unsafe {
+            sys::WUPSConfigAPI_Init(opt, Some(Self::_open_callback), Some(Self::_close_callback))
         };
+        check_options(status, 1)?;
 
-        let status = unsafe { sys::WUPSConfigAPI_Category_CreateEx(opt, &mut handle) };
-        MenuError::try_from(status)?;
+        Ok(())
+    }
 
-        Ok(Self {
-            text: text.to_string(),
-            handle: handle,
-        })
+    /// C callback function for config menu
+    ///
+    /// **Should not be overwritten unless special control is required.**
+    extern "C" fn _open_callback(
+        root: sys::WUPSConfigCategoryHandle,
+    ) -> sys::WUPSConfigAPICallbackStatus::Type {
+        use storage::Storable;
+        use sys::WUPSConfigAPICallbackStatus as S;
+
+        open::opened();
+        let result = Self::Settings::load()
+            .map_err(MenuError::from)
+            .and_then(|mut settings| {
+                Self::prepare(&mut settings)?;
+                preload::set(settings.entries());
+                let root = MenuRoot::from(root);
+                let result = Self::header()
+                    .map_or(Ok(()), |header| root.add(header))
+                    .and_then(|_| Self::open(&settings, root));
+                preload::clear();
+                result
+            });
+
+        match result {
+            Ok(_) => S::WUPSCONFIG_API_CALLBACK_RESULT_SUCCESS,
+            Err(_) => S::WUPSCONFIG_API_CALLBACK_RESULT_ERROR,
+        }
     }
 
-    pub fn text(&self) -> String {
-        self.text.clone()
+    /// C callback function for config menu
+    ///
+    /// **Should not be overwritten unless special control is required.**
+    extern "C" fn _close_callback() {
+        use storage::Storable;
+
+        registry::clear();
+        open::closed();
+        let result = staging::finish()
+            .and_then(|_| Self::Settings::load())
+            .map_err(MenuError::from)
+            .and_then(|mut settings| {
+                Self::prepare(&mut settings)?;
+                Self::close(&mut settings)?;
+                settings.store()?;
+                storage::save(false)?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            Self::close_failed(e);
+        }
     }
-}
 
-impl Attachable for Menu {
-    fn add(&self, item: impl MenuItem) -> Result<(), MenuError> {
-        item.attach(self.handle)
+    /// Line shown above the items of [open][SettingsMenu::open], see [ConfigMenu::header].
+    fn header() -> Option<Header> {
+        None
     }
-}
 
-impl MenuItem for Menu {
-    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
-        let status = unsafe { sys::WUPSConfigAPI_Category_AddCategory(handle, self.handle) };
-        MenuError::try_from(status)?;
+    /// Called with the freshly loaded settings when the plugin menu is opened, before the items
+    /// are added, and when it is closed, before [close][SettingsMenu::close]. See
+    /// [lifecycle](SettingsMenu#lifecycle).
+    fn prepare(settings: &mut Self::Settings) -> Result<(), MenuError> {
+        let _ = settings;
         Ok(())
     }
-}
 
-// endregion
+    /// Open callback.
+    ///
+    /// Called with the [prepared][SettingsMenu::prepare] settings, which the items take their
+    /// current values from.
+    ///
+    /// All items must be rooted in the `root` node to be added to the UI.
+    fn open(settings: &Self::Settings, root: MenuRoot) -> Result<(), MenuError>;
 
-// region: Label
+    /// Close callback.
+    ///
+    /// Called when the plugin menu is closed with the settings containing the user's changes.
+    fn close(settings: &mut Self::Settings) -> Result<(), MenuError> {
+        let _ = settings;
+        Ok(())
+    }
 
-/// Text display
+    /// Called with the error if closing the menu failed, e.g. because the storage couldn't be
+    /// saved. The C API offers no way to report it, so by default it is only logged.
+    fn close_failed(error: MenuError) {
+        report_close_error(&error);
+    }
+}
+
+/// Config menu of a plugin instance, for state which doesn't live in storage.
+///
+/// Unlike [ConfigMenu], whose methods are static, the menu is an instance which is handed to
+/// [init][StatefulConfigMenu::init] and kept until the plugin is unloaded. Every time the menu is
+/// opened, [open][StatefulConfigMenu::open] receives a shared reference to it, so mutable state
+/// needs interior mutability (e.g. a [Mutex][crate::sync::Mutex] or atomics). Item callbacks
+/// must be `'static` and can get the instance with [instance][StatefulConfigMenu::instance].
+///
+/// A plugin has a single config menu, so only one instance is stored: initializing a second one
+/// replaces the first.
 ///
 /// # Example
 ///
 /// ```
-/// root.add(config::Label::new("Label"))?;
+/// use wups::sync::atomic::{AtomicU32, Ordering};
+///
+/// struct MyMenu {
+///     opened: AtomicU32,
+/// }
+///
+/// impl StatefulConfigMenu for MyMenu {
+///     fn open(&self, root: config::MenuRoot) -> Result<(), config::MenuError> {
+///         let opened = self.opened.fetch_add(1, Ordering::Relaxed) + 1;
+///         root.add(config::Label::new(&format!("Opened {opened} times")))?;
+///         Ok(())
+///     }
+/// }
+///
+/// #[on_initialize]
+/// fn init() {
+///     let _ = MyMenu { opened: AtomicU32::new(0) }.init("My Plugin");
+/// }
 /// ```
-pub struct Label {
-    text: String,
-}
+pub trait StatefulConfigMenu: Send + Sync + Sized + 'static {
+    /// Store the instance and initialize the config menu.
+    ///
+    /// Should be called inside the [on_initialize][crate::on_initialize] function.
+    ///
+    /// **Should not be overwritten unless special control is required.**
+    fn init(self, name: &str) -> Result<(), MenuError> {
+        let name = CString::new(name)?;
+        let opt = raw::init_options_v1(&name);
 
-impl Label {
-    pub fn new(text: &str) -> Self {
-        Self {
-            text: text.to_string(),
+        *STATEFUL_MENU.lock().unwrap() = Some(Arc::new(self));
+        let status = unsafe {
+            sys::WUPSConfigAPI_Init(opt, Some(Self::_open_callback), Some(Self::_close_callback))
+        };
+        if let Err(e) = check_options(status, 1) {
+            *STATEFUL_MENU.lock().unwrap() = None;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Instance passed to [init][StatefulConfigMenu::init], or `None` if it wasn't initialized.
+    fn instance() -> Option<Arc<Self>> {
+        // cloned, so the lock isn't held while the instance is used
+        let instance = STATEFUL_MENU.lock().unwrap().clone()?;
+        instance.downcast().ok()
+    }
+
+    /// C callback function for config menu
+    ///
+    /// **Should not be overwritten unless special control is required.**
+    extern "C" fn _open_callback(
+        root: sys::WUPSConfigCategoryHandle,
+    ) -> sys::WUPSConfigAPICallbackStatus::Type {
+        use sys::WUPSConfigAPICallbackStatus as S;
+
+        open::opened();
+        let Some(menu) = Self::instance() else {
+            return S::WUPSCONFIG_API_CALLBACK_RESULT_ERROR;
+        };
+
+        let root = MenuRoot::from(root);
+        let result = match menu.header() {
+            Some(header) => root.add(header),
+            None => Ok(()),
+        }
+        .and_then(|_| menu.open(root));
+
+        match result {
+            Ok(_) => S::WUPSCONFIG_API_CALLBACK_RESULT_SUCCESS,
+            Err(_) => S::WUPSCONFIG_API_CALLBACK_RESULT_ERROR,
         }
     }
-}
 
-impl MenuItem for Label {
-    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
-        let text = CString::new(self.text.as_str()).unwrap();
+    /// C callback function for config menu
+    ///
+    /// **Should not be overwritten unless special control is required.**
+    extern "C" fn _close_callback() {
+        registry::clear();
+        open::closed();
+
+        let result = staging::finish()
+            .map_err(MenuError::from)
+            .and_then(|_| Self::instance().map_or(Ok(()), |menu| menu.close()));
+        if let Err(e) = result {
+            Self::close_failed(e);
+        }
+    }
 
-        let status = unsafe { sys::WUPSConfigItemStub_AddToCategory(handle, text.as_ptr()) };
-        MenuError::try_from(status)?;
+    /// Line shown above the items of [open][StatefulConfigMenu::open], see [ConfigMenu::header].
+    fn header(&self) -> Option<Header> {
+        None
+    }
+
+    /// Open callback.
+    ///
+    /// Called when the plugin menu is opened.
+    ///
+    /// All items must be rooted in the `root` node to be added to the UI.
+    fn open(&self, root: MenuRoot) -> Result<(), MenuError>;
+
+    /// Close callback.
+    ///
+    /// Called when the plugin menu is closed.
+    fn close(&self) -> Result<(), MenuError> {
+        storage::save(false)?;
         Ok(())
     }
-}
 
-// endregion
+    /// Called with the error if closing the menu failed, e.g. because the storage couldn't be
+    /// saved. The C API offers no way to report it, so by default it is only logged.
+    fn close_failed(error: MenuError) {
+        report_close_error(&error);
+    }
+}
 
-// region: Toggle
+/// Instance of the [StatefulConfigMenu], if one was initialized.
+static STATEFUL_MENU: Mutex<Option<Arc<dyn Any + Send + Sync>>> = Mutex::new(None);
 
-/// Binary toggle
+/// Whether values were changed since the storage was last saved to disk.
+///
+/// Items write into storage immediately when the user changes them, but the storage is only
+/// saved to disk when the menu is [closed][ConfigMenu::close]. So while the menu is open, this
+/// reports whether anything was changed in this session; after closing it is `false` again
+/// (unless saving failed). Writes through [storage][crate::storage] from anywhere else in the
+/// plugin count as well.
+///
+/// For [staged][ConfigMenu::staged_transactional] menus and coalesced writes, changes which weren't
+/// committed yet count as unsaved too, but only if they differ from the stored value, so changing
+/// an item and then changing it back isn't reported. Items which write immediately can't tell:
+/// once one wrote a value, this is `true` until the storage is saved, even if the value is the
+/// same as before.
+///
+/// Items are rendered once when the menu is opened, so a label can't reflect this live.
 ///
 /// # Example
 ///
 /// ```
-/// root.add(config::Toggle::new(
-///     "Toggle",
-///     "my_toggle_id",
-///     true,
-///     "On",
-///     "Off",
-/// ))?;
-///
-/// assert_eq!(storage::load::<bool>("my_toggle_id").unwrap(), true);
-/// // toggle is changed...
-/// assert_eq!(storage::load::<bool>("my_toggle_id").unwrap(), false);
+/// fn close() -> Result<(), config::MenuError> {
+///     if config::has_unsaved_changes() {
+///         storage::save(false)?;
+///     }
+///     Ok(())
+/// }
 /// ```
+pub fn has_unsaved_changes() -> bool {
+    storage::is_dirty() || staging::has_changes()
+}
+
+/// Reset the global state of this module, see [reset_statics][crate::reset_statics].
+pub(crate) fn reset_statics() {
+    registry::clear();
+    staging::reset();
+    language::reset();
+    layout::reset();
+    open::reset();
+    apply::reset();
+    *STATEFUL_MENU.lock().unwrap() = None;
+}
+
+/// Item of a config menu.
+///
+/// # Disabled items
+///
+/// The C API has no disabled (greyed-out) state for items. [Toggle], [Range], [Select] and
+/// [Stepper] can instead be marked as `disabled`, which shows them as a [value label][Label::value]
+/// with their current value, which the user can't change:
+///
+/// ```
+/// let recording = storage::load_or_default::<bool>("recording");
+/// root.add(config::Toggle::new("Recording", "recording", false, "On", "Off"))?;
+/// root.add(config::Range::new("Bitrate", "bitrate", 10, 1, 50).disabled(!recording))?;
+/// ```
+///
+/// Items are created when the menu is opened, so changing the setting they depend on only takes
+/// effect the next time the menu is opened. The same applies to items hidden with
+/// [visible_if][MenuItem::visible_if].
+///
+/// # Change callbacks
+///
+/// [Toggle], [Range] and [Select] take an `on_change` callback, e.g. to re-install a hook as soon
+/// as a setting flips instead of when the menu is closed. It receives the new value after it was
+/// written (or [staged][ConfigMenu::staged_transactional]), a [Range] on every step while the user
+/// scrubs through it.
+///
+/// The callback runs on the thread of the config menu, which waits for it to return: it **must
+/// not block**, e.g. on I/O or a lock held by a game thread, and should hand longer work off
+/// instead. Callbacks are dropped when the menu is closed and only one item per id can have one,
+/// see [DuplicateCallback][MenuError::DuplicateCallback].
+///
+/// ```
+/// root.add(
+///     config::Toggle::new("Overlay", "overlay", false, "On", "Off")
+///         .on_change(|enabled| println!("Overlay {enabled}")),
+/// )?;
+/// ```
+pub trait MenuItem {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError>;
+
+    /// Only attach the item if `predicate` returns `true` when it is added, see [VisibleIf].
+    fn visible_if<F: FnOnce() -> bool>(self, predicate: F) -> VisibleIf<Self, F>
+    where
+        Self: Sized,
+    {
+        VisibleIf {
+            item: self,
+            predicate,
+        }
+    }
+
+    /// [storage][crate::storage] id the item writes to, if any.
+    fn id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Check the item's invariants without touching the UI.
+    fn validate(&self) -> Result<(), MenuError> {
+        Ok(())
+    }
+
+    /// Collect all problems of this item (and its children) into `errors`.
+    ///
+    /// `ids` contains the storage ids seen so far and is used to detect duplicates.
+    #[doc(hidden)]
+    fn validate_into(&self, ids: &mut BTreeSet<String>, errors: &mut Vec<MenuError>) {
+        if let Some(id) = self.id()
+            && !ids.insert(id.to_string())
+        {
+            errors.push(MenuError::DuplicateIdentifier(id.to_string()));
+        }
+        if let Err(e) = self.validate() {
+            errors.push(e);
+        }
+    }
+}
+
+pub trait Attachable {
+    fn add(&self, item: impl MenuItem) -> Result<(), MenuError>;
+
+    /// Add a new sub menu with `text` and return a handle to add its items to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let advanced = root.add_menu("Advanced")?;
+    /// advanced.add(config::Toggle::new("Debug", "debug", false, "On", "Off"))?;
+    /// advanced.add_menu("Experimental")?.add(config::Label::new("Nothing here yet"))?;
+    /// ```
+    fn add_menu(&self, text: &str) -> Result<SubMenu, MenuError> {
+        let menu = Menu::new(text)?;
+        let handle = menu.handle;
+        self.add(menu)?;
+        Ok(SubMenu(handle))
+    }
+}
+
+// region: VisibleIf
+
+/// Item which is only shown if a predicate holds, created by [MenuItem::visible_if].
+///
+/// The predicate is evaluated when the item is attached, i.e. while the menu is opened. Hidden
+/// items are skipped entirely, so they don't leave an empty line behind. Since the menu is rebuilt
+/// every time it is opened, items can depend on the current settings, e.g. to only show the
+/// options of a feature while it is enabled.
+///
+/// Hidden items are still [validated][MenuItem::validate], so their ids can't collide with visible
+/// ones. [Menu] creates its category when it is constructed, so hide a [MenuBuilder] instead.
+///
+/// # Example
+///
+/// ```
+/// let network = || storage::load_or_default::<bool>("network");
+///
+/// root.add(config::Toggle::new("Networking", "network", false, "On", "Off"))?;
+/// root.add(
+///     config::MenuBuilder::new("Advanced network")
+///         .item(config::NumberInput::new("Port", "port", 8080).min(1).max(65535))
+///         .visible_if(network),
+/// )?;
+///
+/// // the menu only appears after "Networking" was turned on and the menu is opened again
+/// ```
+pub struct VisibleIf<T, F> {
+    item: T,
+    predicate: F,
+}
+
+impl<T: MenuItem, F: FnOnce() -> bool> MenuItem for VisibleIf<T, F> {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        if (self.predicate)() {
+            self.item.attach(handle)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.item.id()
+    }
+
+    fn validate(&self) -> Result<(), MenuError> {
+        self.item.validate()
+    }
+
+    fn validate_into(&self, ids: &mut BTreeSet<String>, errors: &mut Vec<MenuError>) {
+        self.item.validate_into(ids, errors)
+    }
+}
+
+// endregion
+
+// region: MenuRoot
+
+pub struct MenuRoot(sys::WUPSConfigCategoryHandle);
+
+impl From<sys::WUPSConfigCategoryHandle> for MenuRoot {
+    fn from(value: sys::WUPSConfigCategoryHandle) -> Self {
+        Self(value)
+    }
+}
+
+impl Attachable for MenuRoot {
+    fn add(&self, item: impl MenuItem) -> Result<(), MenuError> {
+        item.attach(self.0)
+    }
+}
+
+// endregion
+
+// region: SubMenu
+
+/// Handle to a sub menu which is already attached, returned by [Attachable::add_menu].
+///
+/// The sub menu is owned by its parent. The handle stays valid while the menu is open, i.e. within
+/// [ConfigMenu::open] (or [SettingsMenu::open]) where it was created, and must not be kept for
+/// later menus.
+#[derive(Debug, Clone, Copy)]
+pub struct SubMenu(sys::WUPSConfigCategoryHandle);
+
+impl Attachable for SubMenu {
+    fn add(&self, item: impl MenuItem) -> Result<(), MenuError> {
+        item.attach(self.0)
+    }
+}
+
+// endregion
+
+// region: Menu
+
+/// Sub menu containing items.
+///
+/// # Example
+///
+/// ```
+/// let sub = config::Menu::new("Menu 1")?;
+/// sub.add(config::Label::new("Label 1"))?;
+/// sub.add(config::Label::new("Label 2"))?;
+/// root.add(sub)?;
+/// ```
+pub struct Menu {
+    text: String,
+    handle: sys::WUPSConfigCategoryHandle,
+}
+
+impl Menu {
+    pub fn new(text: &str) -> Result<Self, MenuError> {
+        let mut handle = sys::WUPSConfigCategoryHandle::default();
+        let c_text = CString::new(language::label(text))?;
+
+        let opt = raw::category_options_v1(&c_text);
+
+        let status = unsafe { sys::WUPSConfigAPI_Category_CreateEx(opt, &mut handle) };
+        check_options(status, sys::WUPS_API_CATEGORY_OPTION_VERSION_V1)?;
+
+        Ok(Self {
+            text: text.to_string(),
+            handle,
+        })
+    }
+
+    /// Add a "← Back" label as the first item.
+    ///
+    /// This is purely cosmetic: the label can't be activated, going back is still done with the
+    /// B button like in every other menu. It should be called right after [new][Menu::new], before
+    /// any other item is added. "Back" is translated by the
+    /// [label provider][set_label_provider], if one is set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let sub = config::Menu::new("Advanced")?.with_back_label()?;
+    /// sub.add(config::Label::new("Label 1"))?;
+    /// root.add(sub)?;
+    /// ```
+    pub fn with_back_label(self) -> Result<Self, MenuError> {
+        let text = alloc::format!("{} {}", glyphs::ARROW_LEFT, language::label("Back"));
+        self.add(Label::new(&text))?;
+        Ok(self)
+    }
+
+    /// Text displayed for the menu.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+
+    #[deprecated(note = "use `name` instead, which doesn't clone")]
+    pub fn text(&self) -> String {
+        self.text.clone()
+    }
+}
+
+impl Attachable for Menu {
+    fn add(&self, item: impl MenuItem) -> Result<(), MenuError> {
+        item.attach(self.handle)
+    }
+}
+
+impl MenuItem for Menu {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let status = unsafe { sys::WUPSConfigAPI_Category_AddCategory(handle, self.handle) };
+        MenuError::try_from(status)?;
+        Ok(())
+    }
+}
+
+// endregion
+
+// region: MenuBuilder
+
+/// Object-safe version of [MenuItem], so items of different types can be collected.
+trait DynMenuItem {
+    fn attach_boxed(
+        self: Box<Self>,
+        handle: sys::WUPSConfigCategoryHandle,
+    ) -> Result<(), MenuError>;
+    fn validate_into(&self, ids: &mut BTreeSet<String>, errors: &mut Vec<MenuError>);
+}
+
+impl<T: MenuItem> DynMenuItem for T {
+    fn attach_boxed(
+        self: Box<Self>,
+        handle: sys::WUPSConfigCategoryHandle,
+    ) -> Result<(), MenuError> {
+        (*self).attach(handle)
+    }
+
+    fn validate_into(&self, ids: &mut BTreeSet<String>, errors: &mut Vec<MenuError>) {
+        MenuItem::validate_into(self, ids, errors)
+    }
+}
+
+/// Sub menu which is defined up front and only created in the UI when attached.
+///
+/// In contrast to [Menu], the definition can be checked with [validate][MenuBuilder::validate]
+/// before anything is created.
+///
+/// # Example
+///
+/// ```
+/// let menu = config::MenuBuilder::new("Video")
+///     .item(config::Toggle::new("Enabled", "video_enabled", true, "On", "Off"))
+///     .item(config::Range::new("Scale", "video_scale", 100, 50, 200));
+///
+/// if let Err(errors) = menu.validate() {
+///     for e in errors {
+///         println!("{e}");
+///     }
+/// }
+///
+/// root.add(menu)?;
+/// ```
+///
+/// # Ordering
+///
+/// Items are displayed by ascending weight. [item][MenuBuilder::item] uses the default weight `0`;
+/// [weighted_item][MenuBuilder::weighted_item] sets it explicitly. Items with the same weight keep
+/// the order they were added in, so modules can contribute items without depending on the order
+/// they are called in:
+///
+/// ```
+/// let menu = config::MenuBuilder::new("Settings")
+///     .weighted_item(100, config::Label::new("Version 1.0")) // always last
+///     .item(config::Toggle::new("Enabled", "enabled", true, "On", "Off"))
+///     .weighted_item(-100, config::Label::new("General")); // always first
+/// ```
+pub struct MenuBuilder {
+    text: String,
+    items: Vec<(i32, Box<dyn DynMenuItem>)>,
+}
+
+impl MenuBuilder {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Append an item with the default weight `0` to the menu.
+    pub fn item(self, item: impl MenuItem + 'static) -> Self {
+        self.weighted_item(0, item)
+    }
+
+    /// Append an item to the menu, which is displayed before all items with a higher `weight`.
+    pub fn weighted_item(mut self, weight: i32, item: impl MenuItem + 'static) -> Self {
+        self.items.push((weight, Box::new(item)));
+        self
+    }
+
+    /// Text displayed for the menu.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+
+    /// Check all items (including nested menus) without creating anything in the UI.
+    ///
+    /// Returns every problem found, e.g. storage ids used by more than one item, selects without
+    /// options or ranges with invalid bounds.
+    pub fn validate(&self) -> Result<(), Vec<MenuError>> {
+        let mut ids = BTreeSet::new();
+        let mut errors = Vec::new();
+        MenuItem::validate_into(self, &mut ids, &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl MenuItem for MenuBuilder {
+    fn attach(mut self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        // stable, so items of equal weight keep their insertion order
+        self.items.sort_by_key(|(weight, _)| *weight);
+
+        let menu = Menu::new(&self.text)?;
+        for (_, item) in self.items {
+            item.attach_boxed(menu.handle)?;
+        }
+        menu.attach(handle)
+    }
+
+    fn validate_into(&self, ids: &mut BTreeSet<String>, errors: &mut Vec<MenuError>) {
+        for (_, item) in &self.items {
+            item.validate_into(ids, errors);
+        }
+    }
+}
+
+// endregion
+
+// region: Group
+
+/// Items under a header line, within the current menu.
+///
+/// Unlike a sub menu ([Menu], [MenuBuilder]), a group is purely visual: it adds a header
+/// `"── <text> ──"` followed by its items to the menu it is added to, so related settings stay on
+/// one screen without navigating. The group can't be collapsed or entered and the items keep
+/// their own text, as the C API has no indentation.
+///
+/// # Example
+///
+/// ```
+/// root.add(
+///     config::Group::new("Display")
+///         .item(config::Toggle::new("Show FPS", "show_fps", false, "On", "Off"))
+///         .item(config::Range::new("Scale", "scale", 100, 50, 200)),
+/// )?;
+///
+/// let audio = config::Group::new("Audio").item(config::Range::new("Volume", "volume", 50, 0, 100));
+/// root.add(audio)?;
+/// ```
+pub struct Group {
+    text: String,
+    items: Vec<Box<dyn DynMenuItem>>,
+}
+
+impl Group {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Append an item to the group.
+    pub fn item(mut self, item: impl MenuItem + 'static) -> Self {
+        self.items.push(Box::new(item));
+        self
+    }
+
+    /// Text displayed in the header.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+}
+
+impl MenuItem for Group {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let header = alloc::format!(
+            "{} {} {}",
+            glyphs::LINE.repeat(2),
+            language::label(&self.text),
+            glyphs::LINE.repeat(2)
+        );
+        let header = CString::new(header)?;
+        let status = unsafe { sys::WUPSConfigItemStub_AddToCategory(handle, header.as_ptr()) };
+        MenuError::try_from(status)?;
+
+        for item in self.items {
+            item.attach_boxed(handle)?;
+        }
+        Ok(())
+    }
+
+    fn validate_into(&self, ids: &mut BTreeSet<String>, errors: &mut Vec<MenuError>) {
+        for item in &self.items {
+            item.validate_into(ids, errors);
+        }
+    }
+}
+
+// endregion
+
+// region: Label
+
+/// Text display
+///
+/// # Example
+///
+/// ```
+/// root.add(config::Label::new("Label"))?;
+/// ```
+///
+/// # Values
+///
+/// [Label::value] displays a value in the value column on the right, like the current value of
+/// other items, e.g. for an about screen. The label can be selected, which highlights the value.
+///
+/// ```
+/// root.add(config::Label::value("IP", "192.168.1.5"))?;
+/// root.add(config::Label::value("Version", env!("CARGO_PKG_VERSION")))?;
+/// ```
+#[derive(Debug)]
+pub struct Label {
+    text: String,
+    value: Option<String>,
+}
+
+impl Label {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            value: None,
+        }
+    }
+
+    /// Label with `text` on the left and `value` on the right.
+    pub fn value(text: &str, value: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            value: Some(value.to_string()),
+        }
+    }
+
+    /// Text displayed for the label.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+}
+
+impl MenuItem for Label {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let text = CString::new(language::label(&self.text))?;
+
+        if let Some(value) = self.value {
+            return custom::attach(handle, &text, Box::new(ValueItem(value)));
+        }
+
+        let status = unsafe { sys::WUPSConfigItemStub_AddToCategory(handle, text.as_ptr()) };
+        MenuError::try_from(status)?;
+        Ok(())
+    }
+}
+
+/// Value of a [Label], which can't be changed.
+struct ValueItem(String);
+
+impl custom::CustomItem for ValueItem {
+    fn display(&self) -> String {
+        self.0.clone()
+    }
+
+    fn selected_display(&self) -> String {
+        alloc::format!("[ {} ]", self.0)
+    }
+
+    fn on_input(&mut self, _pressed: sys::WUPSConfigButtons::Type) {}
+
+    fn restore_default(&mut self) {}
+}
+
+// endregion
+
+// region: Button
+
+/// Item which runs an action when A is pressed, e.g. "Reset to defaults" or "Clear cache".
+///
+/// The button doesn't store anything. It shows "Press A" and "Done" once the action ran. The
+/// callback is owned by the item, so it lives as long as the menu is open and is dropped when the
+/// menu is closed; a new one is created every time the menu is opened.
+///
+/// # Example
+///
+/// ```
+/// root.add(config::Button::new("Reset to defaults", || {
+///     let _ = storage::delete("volume");
+/// }))?;
+/// ```
+pub struct Button {
+    text: String,
+    action: Box<dyn Fn() -> Result<(), storage::StorageError>>,
+}
+
+impl Button {
+    pub fn new(text: &str, callback: impl Fn() + 'static) -> Self {
+        Self::fallible(text, move || {
+            callback();
+            Ok(())
+        })
+    }
+
+    /// Button which shows "Failed" instead of "Done" if the action fails.
+    pub(crate) fn fallible(
+        text: &str,
+        action: impl Fn() -> Result<(), storage::StorageError> + 'static,
+    ) -> Self {
+        Self {
+            text: text.to_string(),
+            action: Box::new(action),
+        }
+    }
+
+    /// Text displayed for the button.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+}
+
+impl MenuItem for Button {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let text = CString::new(language::label(&self.text))?;
+        custom::attach(
+            handle,
+            &text,
+            Box::new(ButtonItem {
+                action: self.action,
+                status: language::label("Press A").to_string(),
+            }),
+        )
+    }
+}
+
+struct ButtonItem {
+    action: Box<dyn Fn() -> Result<(), storage::StorageError>>,
+    /// Result of the last press.
+    status: String,
+}
+
+impl custom::CustomItem for ButtonItem {
+    fn display(&self) -> String {
+        self.status.clone()
+    }
+
+    fn on_input(&mut self, pressed: sys::WUPSConfigButtons::Type) {
+        if pressed & sys::WUPSConfigButtons::WUPS_CONFIG_BUTTON_A == 0 {
+            return;
+        }
+        self.status = match (self.action)() {
+            Ok(()) => language::label("Done").to_string(),
+            Err(e) => {
+                wut::println!("Config menu action failed: {e:?}");
+                language::label("Failed").to_string()
+            }
+        };
+    }
+
+    fn restore_default(&mut self) {}
+}
+
+// endregion
+
+// region: Header
+
+/// Status line at the top of the menu, returned by [ConfigMenu::header].
+///
+/// Shows `"<name> v<version>"` on the left and the [status][Header::status] (if any) on the right,
+/// like a [Label::value]. `PLUGIN_NAME` and `PLUGIN_VERSION`, defined by `WUPS_PLUGIN_NAME!`, are
+/// meant to be passed in, so support requests can be matched to a version at a glance.
+///
+/// # Example
+///
+/// ```
+/// let header = config::Header::new(PLUGIN_NAME, PLUGIN_VERSION)
+///     .status(|| alloc::format!("{} patches", patch_count()));
+/// ```
+pub struct Header {
+    name: String,
+    version: String,
+    status: Option<Box<dyn FnOnce() -> String>>,
+}
+
+impl Header {
+    pub fn new(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            status: None,
+        }
+    }
+
+    /// Text shown on the right, computed when the header is added, i.e. every time the menu is
+    /// opened.
+    pub fn status(mut self, status: impl FnOnce() -> String + 'static) -> Self {
+        self.status = Some(Box::new(status));
+        self
+    }
+
+    /// `"<name> v<version>"`, the text on the left.
+    pub fn title(&self) -> String {
+        alloc::format!("{} v{}", self.name, self.version)
+    }
+}
+
+impl core::fmt::Debug for Header {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Header")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MenuItem for Header {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let title = self.title();
+        let label = match self.status {
+            Some(status) => Label::value(&title, &status()),
+            None => Label::new(&title),
+        };
+        label.attach(handle)
+    }
+}
+
+// endregion
+
+// region: Gauge
+
+/// Number of glyphs of the [bar][glyphs::bar] of a [Gauge] or [ProgressBar].
+const GAUGE_BAR_WIDTH: usize = 10;
+
+/// Read-only numeric readout, e.g. for status displays.
+///
+/// The value is read from storage or a provider every time the menu is opened and rendered into
+/// the text, optionally with a unit and a [bar][glyphs::bar]. It is **not** updated while the
+/// menu stays open.
+///
+/// # Example
+///
+/// ```
+/// root.add(config::Gauge::key::<u32>("Battery saves", "saves"))?;
+/// // "Cache: 3.2 MB ██████░░░░"
+/// root.add(
+///     config::Gauge::provider("Cache", || cache_size() as f64 / 1_000_000.0)
+///         .unit("MB")
+///         .decimals(1)
+///         .bar(0.0, 5.0),
+/// )?;
+/// ```
+pub struct Gauge {
+    text: String,
+    source: GaugeSource,
+    unit: Option<String>,
+    decimals: usize,
+    bar: Option<(f64, f64)>,
+}
+
+enum GaugeSource {
+    Key(String, fn(&str) -> Option<f64>),
+    Provider(Box<dyn Fn() -> f64>),
+}
+
+impl GaugeSource {
+    fn key<T>(id: &str) -> Self
+    where
+        T: storage::StorageCompatible,
+        T::T: Into<f64>,
+    {
+        fn load<T>(id: &str) -> Option<f64>
+        where
+            T: storage::StorageCompatible,
+            T::T: Into<f64>,
+        {
+            storage::load::<T>(id).ok().map(Into::into)
+        }
+
+        Self::Key(id.to_string(), load::<T>)
+    }
+
+    fn read(&self) -> Option<f64> {
+        match self {
+            Self::Key(id, load) => load(id),
+            Self::Provider(provider) => Some(provider()),
+        }
+    }
+}
+
+impl Gauge {
+    /// Display the value stored under `id` as `T`. Missing values are displayed as `-`.
+    pub fn key<T>(text: &str, id: &str) -> Self
+    where
+        T: storage::StorageCompatible,
+        T::T: Into<f64>,
+    {
+        Self::new(text, GaugeSource::key::<T>(id))
+    }
+
+    /// Display the value returned by `provider`.
+    pub fn provider(text: &str, provider: impl Fn() -> f64 + 'static) -> Self {
+        Self::new(text, GaugeSource::Provider(Box::new(provider)))
+    }
+
+    fn new(text: &str, source: GaugeSource) -> Self {
+        Self {
+            text: text.to_string(),
+            source,
+            unit: None,
+            decimals: 0,
+            bar: None,
+        }
+    }
+
+    /// Unit displayed after the value.
+    pub fn unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    /// Number of decimal places of the value. Defaults to `0`.
+    pub fn decimals(mut self, decimals: usize) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Display a bar which is empty at `min` and full at `max`.
+    pub fn bar(mut self, min: f64, max: f64) -> Self {
+        debug_assert!(min < max);
+        self.bar = Some((min, max));
+        self
+    }
+
+    /// Text displayed for the gauge.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+
+    fn render(&self) -> String {
+        let Some(value) = self.source.read() else {
+            return alloc::format!("{}: -", language::label(&self.text));
+        };
+
+        let label = language::label(&self.text);
+        let mut text = alloc::format!("{}: {:.*}", label, self.decimals, value);
+        if let Some(unit) = &self.unit {
+            text.push(' ');
+            text.push_str(unit);
+        }
+        if let Some((min, max)) = self.bar {
+            text.push(' ');
+            text.push_str(&glyphs::bar((value - min) / (max - min), GAUGE_BAR_WIDTH));
+        }
+        text
+    }
+}
+
+impl core::fmt::Debug for Gauge {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Gauge")
+            .field("text", &self.text)
+            .field("unit", &self.unit)
+            .field("decimals", &self.decimals)
+            .field("bar", &self.bar)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MenuItem for Gauge {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let text = CString::new(self.render())?;
+
+        let status = unsafe { sys::WUPSConfigItemStub_AddToCategory(handle, text.as_ptr()) };
+        MenuError::try_from(status)?;
+        Ok(())
+    }
+}
+
+// endregion
+
+// region: ProgressBar
+
+/// Read-only bar of a percentage, e.g. download progress or playtime toward a goal.
+///
+/// Renders `"<text>: <bar> <value>%"` with a [glyph bar][glyphs::bar]. Values are clamped to
+/// `0..=100`. Like [Gauge], the value is read from
+/// storage or a provider every time the menu is opened, and not updated while it stays open.
+///
+/// # Example
+///
+/// ```
+/// root.add(config::ProgressBar::key::<u32>("Download", "download_progress"))?;
+/// root.add(config::ProgressBar::provider("Goal", || playtime_hours() as f64 / 50.0 * 100.0))?;
+///
+/// let bar = |value: f64| config::ProgressBar::provider("Done", move || value).text();
+/// assert_eq!(bar(0.0), "Done: ░░░░░░░░░░ 0%");
+/// assert_eq!(bar(50.0), "Done: █████░░░░░ 50%");
+/// assert_eq!(bar(100.0), "Done: ██████████ 100%");
+/// assert_eq!(bar(250.0), "Done: ██████████ 100%");
+/// ```
+pub struct ProgressBar {
+    text: String,
+    source: GaugeSource,
+}
+
+impl ProgressBar {
+    /// Display the value stored under `id` as `T`. Missing values are displayed as `-`.
+    pub fn key<T>(text: &str, id: &str) -> Self
+    where
+        T: storage::StorageCompatible,
+        T::T: Into<f64>,
+    {
+        Self {
+            text: text.to_string(),
+            source: GaugeSource::key::<T>(id),
+        }
+    }
+
+    /// Display the value returned by `provider`.
+    pub fn provider(text: &str, provider: impl Fn() -> f64 + 'static) -> Self {
+        Self {
+            text: text.to_string(),
+            source: GaugeSource::Provider(Box::new(provider)),
+        }
+    }
+
+    /// Text displayed for the bar.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+
+    /// Rendered text with the current value, as it would be displayed if the menu opened now.
+    pub fn text(&self) -> String {
+        let label = language::label(&self.text);
+        let Some(value) = self.source.read() else {
+            return alloc::format!("{label}: -");
+        };
+
+        let value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 100.0)
+        };
+        let bar = glyphs::bar(value / 100.0, GAUGE_BAR_WIDTH);
+        alloc::format!("{label}: {bar} {value:.0}%")
+    }
+}
+
+impl core::fmt::Debug for ProgressBar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ProgressBar")
+            .field("text", &self.text)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MenuItem for ProgressBar {
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let text = CString::new(self.text())?;
+
+        let status = unsafe { sys::WUPSConfigItemStub_AddToCategory(handle, text.as_ptr()) };
+        MenuError::try_from(status)?;
+        Ok(())
+    }
+}
+
+// endregion
+
+// region: Toggle
+
+/// Binary toggle
+///
+/// # Example
+///
+/// ```
+/// root.add(config::Toggle::new(
+///     "Toggle",
+///     "my_toggle_id",
+///     true,
+///     "On",
+///     "Off",
+/// ))?;
+///
+/// assert_eq!(storage::load::<bool>("my_toggle_id").unwrap(), true);
+/// // toggle is changed...
+/// assert_eq!(storage::load::<bool>("my_toggle_id").unwrap(), false);
+/// ```
+#[derive(Debug)]
 pub struct Toggle {
     text: String,
     id: String,
-    default: bool,
-    true_value: String,
-    false_value: String,
+    default: bool,
+    true_value: String,
+    false_value: String,
+    disabled: bool,
+    on_change: Option<registry::OnChange<bool>>,
+}
+
+impl Toggle {
+    /// # Arguments
+    ///
+    /// - `text`: Text to display on the toggle.
+    /// - `id`: [storage][crate::storage] id to access value.
+    /// - `default`: Default value if id doesn't exist in storage.
+    /// - `trueValue`: Text to display when value is `true`.
+    /// - `falseValue`: Text to display when value is `false`.
+    pub fn new(text: &str, id: &str, default: bool, true_value: &str, false_value: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            id: id.to_string(),
+            default,
+            true_value: true_value.to_string(),
+            false_value: false_value.to_string(),
+            disabled: false,
+            on_change: None,
+        }
+    }
+
+    /// Show the item as a read-only label with its current value, e.g. when it depends on another
+    /// setting which is off. See [disabled items][MenuItem#disabled-items].
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Run `callback` with the new value whenever the user changes the toggle, see
+    /// [change callbacks][MenuItem#change-callbacks].
+    pub fn on_change(mut self, callback: impl FnMut(bool) + Send + 'static) -> Self {
+        self.on_change = Some(registry::OnChange::new(callback));
+        self
+    }
+
+    /// Text displayed for the toggle.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+}
+
+impl MenuItem for Toggle {
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let text = CString::new(language::label(&self.text))?;
+        let id = CString::new(self.id.as_str())?;
+        let true_value = CString::new(language::label(&self.true_value))?;
+        let false_value = CString::new(language::label(&self.false_value))?;
+
+        let current = match migrate::load::<bool>(&self.id) {
+            Ok(v) => v,
+            Err(storage::StorageError::NotFound) => {
+                storage::store::<bool>(&self.id, self.default)?;
+                self.default
+            }
+            Err(e) => return Err(MenuError::STORAGE(e)),
+        };
+
+        if self.disabled {
+            let value = if current {
+                &self.true_value
+            } else {
+                &self.false_value
+            };
+            return Label::value(&self.text, language::label(value)).attach(handle);
+        }
+
+        if let Some(on_change) = self.on_change {
+            on_change.register(&self.id, |value| match value {
+                registry::Value::Bool(value) => Some(value),
+                _ => None,
+            })?;
+        }
+
+        let status = unsafe {
+            sys::WUPSConfigItemBoolean_AddToCategoryEx(
+                handle,
+                id.as_ptr(),
+                text.as_ptr(),
+                self.default,
+                current,
+                Some(_callback_toggle_changed),
+                true_value.as_ptr(),
+                false_value.as_ptr(),
+            )
+        };
+        MenuError::try_from(status)?;
+
+        Ok(())
+    }
+}
+
+extern "C" fn _callback_toggle_changed(item: *mut sys::ConfigItemBoolean, value: bool) {
+    let id = unsafe { CStr::from_ptr((*item).identifier) };
+    let _ = staging::write(&id.to_string_lossy(), registry::Value::Bool(value));
+    registry::dispatch(id, registry::Value::Bool(value));
+}
+
+// endregion
+
+// region: Range
+
+/// Select a number from a range.
+///
+/// # Example
+///
+/// ```
+/// root.add(config::Range::new("Range", "my_range_id", 0, -5, 5))?;
+///
+/// assert_eq!(storage::load::<i32>("my_range_id").unwrap(), 0);
+/// // range is increased and the menu closed...
+/// assert_eq!(storage::load::<i32>("my_range_id").unwrap(), 1);
+/// ```
+///
+/// # Writes
+///
+/// The range reports every intermediate value while the user scrubs through it. These aren't
+/// written one by one: only the latest value is kept and written once when the menu is closed,
+/// before [ConfigMenu::close] or [SettingsMenu::close] runs. The menu itself shows the new value
+/// right away. Moving from 0 to 40 and back to 30 thus results in a single write of 30 (visible
+/// e.g. in the [audit log][storage]), and storage keeps the old value while the menu is still
+/// open.
+///
+/// # Formatting
+///
+/// With a [formatter][Range::format], the formatted value is shown instead of the raw integer and
+/// updated while the user scrubs (e.g. `50.00%` for `5000`). The C item renders the raw integer
+/// and has no value-label callback, so a formatted range is a Rust item instead, which behaves
+/// the same: Left/Right change the value by 1, L/R by 50. The stored value is always the raw
+/// integer.
+///
+/// ```
+/// root.add(
+///     config::Range::new("Volume", "volume", 5000, 0, 10000)
+///         .format(|v| format!("{}.{:02}%", v / 100, v % 100)),
+/// )?;
+/// ```
+///
+/// # Floats
+///
+/// A `Range<f32>` is quantized to [steps][Range::steps] evenly spaced values from `min` to `max`
+/// (100 by default), which Left/Right move between. The value is stored as `f32` and displayed
+/// with as many decimals as the step size needs, or with the [formatter][Range::format]. Since
+/// there is no C item for floats, each step is written like a [Stepper] instead of coalesced.
+///
+/// ```
+/// root.add(config::Range::new("Volume", "volume", 0.5, 0.0, 1.0).steps(20))?;
+///
+/// assert_eq!(storage::load::<f32>("volume").unwrap(), 0.5);
+/// ```
+#[derive(Debug)]
+pub struct Range<T: RangeCompatible = i32> {
+    text: String,
+    id: String,
+    default: T,
+    min: T,
+    max: T,
+    format: Option<fn(T) -> String>,
+    disabled: bool,
+    /// Number of steps from `min` to `max`, only used by floats.
+    steps: u32,
+    on_change: Option<registry::OnChange<T>>,
+}
+
+impl<T: RangeCompatible> Range<T> {
+    /// Invalid bounds (including NaN) only panic in debug builds, otherwise adding the range fails
+    /// with [InvalidRange][MenuError::InvalidRange] or
+    /// [InvalidFloatRange][MenuError::InvalidFloatRange]. Use [try_new][Range::try_new] to check
+    /// them right away, e.g. for bounds which aren't constant.
+    pub fn new(text: &str, id: &str, default: T, min: T, max: T) -> Self {
+        // all of them fail for NaN bounds as well
+        debug_assert!(min < max);
+        debug_assert!(min <= default);
+        debug_assert!(default <= max);
+
+        Self::unchecked(text, id, default, min, max)
+    }
+
+    /// Like [new][Range::new], but returns an error instead of panicking for invalid bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let max = f32::NAN;
+    /// assert!(matches!(
+    ///     config::Range::try_new("Volume", "volume", 0.5, 0.0, max),
+    ///     Err(config::MenuError::InvalidFloatRange(_))
+    /// ));
+    /// ```
+    pub fn try_new(text: &str, id: &str, default: T, min: T, max: T) -> Result<Self, MenuError> {
+        let range = Self::unchecked(text, id, default, min, max);
+        T::validate(&range)?;
+        Ok(range)
+    }
+
+    fn unchecked(text: &str, id: &str, default: T, min: T, max: T) -> Self {
+        Self {
+            text: text.to_string(),
+            id: id.to_string(),
+            default,
+            min,
+            max,
+            format: None,
+            disabled: false,
+            steps: 100,
+            on_change: None,
+        }
+    }
+
+    /// Show the item as a read-only label with its current value, e.g. when it depends on another
+    /// setting which is off. See [disabled items][MenuItem#disabled-items].
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Formatter used to display the value, see [formatting](Range#formatting).
+    pub fn format(mut self, format: fn(T) -> String) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Run `callback` with the new value whenever the user changes the range, see
+    /// [change callbacks][MenuItem#change-callbacks].
+    pub fn on_change(mut self, callback: impl FnMut(T) + Send + 'static) -> Self {
+        self.on_change = Some(registry::OnChange::new(callback));
+        self
+    }
+
+    /// Text displayed for the range.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+
+    fn contains(&self, value: T) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    fn display(&self, value: T) -> String {
+        match self.format {
+            Some(format) => format(value),
+            None => T::display(value, self),
+        }
+    }
+}
+
+impl Range<i32> {
+    /// Range from 0 to 100, shown as `50%`, which can be loaded as [Percent][storage::Percent].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// root.add(config::Range::percent("Volume", "volume", 50))?;
+    ///
+    /// let volume = storage::load::<storage::Percent>("volume")?.as_f32();
+    /// ```
+    pub fn percent(text: &str, id: &str, default: u8) -> Self {
+        let default = storage::Percent::new(default).get() as i32;
+        Self::new(text, id, default, 0, 100).format(|v| alloc::format!("{v}%"))
+    }
+}
+
+impl Range<f32> {
+    /// Number of evenly spaced steps from `min` to `max`, e.g. `20` for steps of `0.05` from `0.0`
+    /// to `1.0`. Defaults to 100.
+    pub fn steps(mut self, steps: u32) -> Self {
+        debug_assert!(steps > 0);
+        self.steps = steps;
+        self
+    }
+}
+
+impl<T: RangeCompatible> MenuItem for Range<T> {
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn validate(&self) -> Result<(), MenuError> {
+        T::validate(self)
+    }
+
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        T::attach(self, handle)
+    }
+}
+
+/// Number type of a [Range]: `i32` or `f32`.
+pub trait RangeCompatible: Copy + PartialOrd + core::fmt::Debug + 'static {
+    #[doc(hidden)]
+    fn validate(range: &Range<Self>) -> Result<(), MenuError>;
+
+    #[doc(hidden)]
+    fn attach(range: Range<Self>, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError>;
+
+    #[doc(hidden)]
+    fn display(value: Self, range: &Range<Self>) -> String;
+}
+
+impl RangeCompatible for i32 {
+    fn validate(range: &Range<Self>) -> Result<(), MenuError> {
+        if range.min < range.max && range.contains(range.default) {
+            Ok(())
+        } else {
+            Err(MenuError::InvalidRange {
+                id: range.id.clone(),
+                min: range.min,
+                default: range.default,
+                max: range.max,
+            })
+        }
+    }
+
+    fn attach(range: Range<Self>, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let id = CString::new(range.id.as_str())?;
+        let text = CString::new(language::label(&range.text))?;
+
+        let current = match migrate::load::<i32>(&range.id) {
+            Ok(v) => {
+                if range.contains(v) {
+                    v
+                } else {
+                    range.default
+                }
+            }
+            Err(storage::StorageError::NotFound) => {
+                storage::store::<i32>(&range.id, range.default)?;
+                range.default
+            }
+            Err(e) => return Err(MenuError::STORAGE(e)),
+        };
+
+        if range.disabled {
+            return Label::value(&range.text, &range.display(current)).attach(handle);
+        }
+
+        if let Some(on_change) = range.on_change {
+            on_change.register(&range.id, |value| match value {
+                registry::Value::I32(value) => Some(value),
+                _ => None,
+            })?;
+        }
+
+        if let Some(format) = range.format {
+            return custom::attach(
+                handle,
+                &text,
+                Box::new(IntRangeItem {
+                    id: range.id,
+                    format,
+                    min: range.min,
+                    max: range.max,
+                    default: range.default,
+                    current,
+                }),
+            );
+        }
+
+        let status = unsafe {
+            sys::WUPSConfigItemIntegerRange_AddToCategory(
+                handle,
+                id.as_ptr(),
+                text.as_ptr(),
+                range.default,
+                current,
+                range.min,
+                range.max,
+                Some(_callback_range_changed),
+            )
+        };
+        MenuError::try_from(status)?;
+
+        Ok(())
+    }
+
+    fn display(value: Self, _range: &Range<Self>) -> String {
+        value.to_string()
+    }
+}
+
+/// Called for every step while the user scrubs, so the writes are coalesced.
+extern "C" fn _callback_range_changed(item: *mut sys::ConfigItemIntegerRange, value: i32) {
+    let id = unsafe { CStr::from_ptr((*item).identifier) };
+    staging::write_coalesced(&id.to_string_lossy(), registry::Value::I32(value));
+    registry::dispatch(id, registry::Value::I32(value));
+}
+
+/// `Range<i32>` with a formatter, which the C item can't display.
+struct IntRangeItem {
+    id: String,
+    format: fn(i32) -> String,
+    min: i32,
+    max: i32,
+    default: i32,
+    current: i32,
+}
+
+impl IntRangeItem {
+    fn set(&mut self, value: i32) {
+        let value = value.clamp(self.min, self.max);
+        if value != self.current {
+            self.current = value;
+            // coalesced like the C item
+            staging::write_coalesced(&self.id, registry::Value::I32(value));
+            if let Ok(id) = CString::new(self.id.as_str()) {
+                registry::dispatch(&id, registry::Value::I32(value));
+            }
+        }
+    }
+}
+
+impl custom::CustomItem for IntRangeItem {
+    fn display(&self) -> String {
+        (self.format)(self.current)
+    }
+
+    fn selected_display(&self) -> String {
+        let left = if self.current > self.min { "<" } else { " " };
+        let right = if self.current < self.max { ">" } else { " " };
+        alloc::format!("{left} {} {right}", self.display())
+    }
+
+    fn on_input(&mut self, pressed: sys::WUPSConfigButtons::Type) {
+        use sys::WUPSConfigButtons as B;
+
+        let delta = if pressed & B::WUPS_CONFIG_BUTTON_LEFT != 0 {
+            -1
+        } else if pressed & B::WUPS_CONFIG_BUTTON_RIGHT != 0 {
+            1
+        } else if pressed & B::WUPS_CONFIG_BUTTON_L != 0 {
+            -50
+        } else if pressed & B::WUPS_CONFIG_BUTTON_R != 0 {
+            50
+        } else {
+            return;
+        };
+        self.set(self.current.saturating_add(delta));
+    }
+
+    fn restore_default(&mut self) {
+        self.set(self.default);
+    }
+}
+
+impl RangeCompatible for f32 {
+    fn validate(range: &Range<Self>) -> Result<(), MenuError> {
+        // false for NaN as well
+        if range.min < range.max && range.contains(range.default) && range.steps > 0 {
+            Ok(())
+        } else {
+            Err(MenuError::InvalidFloatRange(range.id.clone()))
+        }
+    }
+
+    fn attach(range: Range<Self>, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        // quantizing needs valid bounds, which can't be checked in `new` in release builds
+        Self::validate(&range)?;
+        let text = CString::new(language::label(&range.text))?;
+        let steps = FloatSteps::of(&range);
+
+        let default = steps.step_of(range.default);
+        let current = match migrate::load::<f32>(&range.id) {
+            Ok(v) if range.contains(v) => steps.step_of(v),
+            Ok(_) => default,
+            Err(storage::StorageError::NotFound) => {
+                storage::store::<f32>(&range.id, range.default)?;
+                default
+            }
+            Err(e) => return Err(MenuError::STORAGE(e)),
+        };
+
+        if range.disabled {
+            let value = range.display(steps.value(current));
+            return Label::value(&range.text, &value).attach(handle);
+        }
+
+        if let Some(on_change) = range.on_change {
+            on_change.register(&range.id, |value| match value {
+                registry::Value::F32(value) => Some(value),
+                _ => None,
+            })?;
+        }
+
+        custom::attach(
+            handle,
+            &text,
+            Box::new(FloatRangeItem {
+                steps,
+                format: range.format,
+                id: range.id,
+                default,
+                current,
+            }),
+        )
+    }
+
+    fn display(value: Self, range: &Range<Self>) -> String {
+        FloatSteps::of(range).format(value)
+    }
+}
+
+/// Values of a `Range<f32>`, `min + (max - min) * i / steps` for `i` in `0..=steps`.
+#[derive(Debug, Clone, Copy)]
+struct FloatSteps {
+    min: f32,
+    max: f32,
+    steps: u32,
+}
+
+impl FloatSteps {
+    fn of(range: &Range<f32>) -> Self {
+        Self {
+            min: range.min,
+            max: range.max,
+            steps: range.steps,
+        }
+    }
+
+    fn value(&self, step: u32) -> f32 {
+        if step >= self.steps {
+            // exact, even if the division is not
+            return self.max;
+        }
+        self.min + (self.max - self.min) * step as f32 / self.steps as f32
+    }
+
+    /// Closest step to `value`, which is within the bounds.
+    fn step_of(&self, value: f32) -> u32 {
+        let step = (value - self.min) / (self.max - self.min) * self.steps as f32;
+        // rounds to nearest, since `step` isn't negative
+        ((step + 0.5) as u32).min(self.steps)
+    }
+
+    /// `value` with enough decimals to tell neighboring steps apart.
+    fn format(&self, value: f32) -> String {
+        let mut step = (self.max - self.min) / self.steps as f32;
+        let mut decimals = 0;
+        while step < 1.0 && decimals < 6 {
+            step *= 10.0;
+            decimals += 1;
+        }
+        alloc::format!("{value:.decimals$}")
+    }
+}
+
+struct FloatRangeItem {
+    id: String,
+    steps: FloatSteps,
+    format: Option<fn(f32) -> String>,
+    default: u32,
+    current: u32,
+}
+
+impl FloatRangeItem {
+    fn set(&mut self, step: u32) {
+        if step != self.current {
+            self.current = step;
+            let value = self.steps.value(step);
+            let _ = staging::write(&self.id, registry::Value::F32(value));
+            if let Ok(id) = CString::new(self.id.as_str()) {
+                registry::dispatch(&id, registry::Value::F32(value));
+            }
+        }
+    }
+}
+
+impl custom::CustomItem for FloatRangeItem {
+    fn display(&self) -> String {
+        let value = self.steps.value(self.current);
+        match self.format {
+            Some(format) => format(value),
+            None => self.steps.format(value),
+        }
+    }
+
+    fn selected_display(&self) -> String {
+        alloc::format!("< {} >", self.display())
+    }
+
+    fn on_input(&mut self, pressed: sys::WUPSConfigButtons::Type) {
+        use sys::WUPSConfigButtons as B;
+
+        if pressed & B::WUPS_CONFIG_BUTTON_LEFT != 0 {
+            self.set(self.current.saturating_sub(1));
+        } else if pressed & B::WUPS_CONFIG_BUTTON_RIGHT != 0 {
+            self.set((self.current + 1).min(self.steps.steps));
+        }
+    }
+
+    fn restore_default(&mut self) {
+        self.set(self.default);
+    }
+}
+
+// endregion
+
+// region: NumberInput
+
+/// Exact integer, entered digit by digit, e.g. for a port where a [Range] is impractical.
+///
+/// Press A to start editing: Left/Right select a digit (or the sign, if `min` is negative), Up/Down
+/// change it, A confirms and B cancels. Values outside of [min][NumberInput::min] and
+/// [max][NumberInput::max] are rejected; cancelled and rejected input keeps the previous value.
+///
+/// The value is stored as `i32`, like [Range].
+///
+/// # Example
+///
+/// ```
+/// root.add(config::NumberInput::new("Port", "port", 8080).min(1).max(65535))?;
+///
+/// assert_eq!(storage::load::<i32>("port").unwrap(), 8080);
+/// ```
+///
+/// # System keyboard
+///
+/// The numeric keyboard of the system (swkbd) can't be shown from the config menu: it has to be
+/// drawn and updated every frame by its caller, while the config menu owns the render loop.
+#[derive(Debug)]
+pub struct NumberInput {
+    text: String,
+    id: String,
+    default: i32,
+    min: i32,
+    max: i32,
+}
+
+impl NumberInput {
+    /// Input accepting every `i32` until [min][NumberInput::min] or [max][NumberInput::max] are
+    /// set.
+    pub fn new(text: &str, id: &str, default: i32) -> Self {
+        Self {
+            text: text.to_string(),
+            id: id.to_string(),
+            default,
+            min: i32::MIN,
+            max: i32::MAX,
+        }
+    }
+
+    /// Smallest accepted value.
+    pub fn min(mut self, min: i32) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Largest accepted value.
+    pub fn max(mut self, max: i32) -> Self {
+        self.max = max;
+        self
+    }
+
+    /// Text displayed for the input.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+
+    fn contains(&self, value: i32) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+impl MenuItem for NumberInput {
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn validate(&self) -> Result<(), MenuError> {
+        if self.contains(self.default) {
+            Ok(())
+        } else {
+            Err(MenuError::InvalidRange {
+                id: self.id.clone(),
+                min: self.min,
+                default: self.default,
+                max: self.max,
+            })
+        }
+    }
+
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let text = CString::new(language::label(&self.text))?;
+
+        let current = match migrate::load::<i32>(&self.id) {
+            Ok(v) if self.contains(v) => v,
+            Ok(_) => self.default,
+            Err(storage::StorageError::NotFound) => {
+                storage::store::<i32>(&self.id, self.default)?;
+                self.default
+            }
+            Err(e) => return Err(MenuError::STORAGE(e)),
+        };
+
+        custom::attach(
+            handle,
+            &text,
+            Box::new(NumberItem {
+                id: self.id,
+                current,
+                default: self.default,
+                min: self.min,
+                max: self.max,
+                edit: None,
+                rejected: false,
+            }),
+        )
+    }
+}
+
+struct NumberItem {
+    id: String,
+    current: i32,
+    default: i32,
+    min: i32,
+    max: i32,
+    edit: Option<NumberEdit>,
+    /// The last input was out of range.
+    rejected: bool,
+}
+
+impl NumberItem {
+    fn set(&mut self, value: i32) {
+        if value != self.current {
+            self.current = value;
+            let _ = staging::write(&self.id, registry::Value::I32(value));
+            if let Ok(id) = CString::new(self.id.as_str()) {
+                registry::dispatch(&id, registry::Value::I32(value));
+            }
+        }
+    }
+
+    fn confirm(&mut self, value: i64) {
+        match i32::try_from(value) {
+            Ok(value) if self.min <= value && value <= self.max => self.set(value),
+            _ => self.rejected = true,
+        }
+    }
+}
+
+impl custom::CustomItem for NumberItem {
+    fn display(&self) -> String {
+        match &self.edit {
+            Some(edit) => edit.to_string(),
+            None if self.rejected => alloc::format!("{} (out of range)", self.current),
+            None => self.current.to_string(),
+        }
+    }
+
+    fn selected_display(&self) -> String {
+        match &self.edit {
+            Some(edit) => edit.to_string(),
+            None => alloc::format!("[ {} ]", self.display()),
+        }
+    }
+
+    fn on_input(&mut self, pressed: sys::WUPSConfigButtons::Type) {
+        use sys::WUPSConfigButtons as B;
+
+        let Some(edit) = &mut self.edit else {
+            if pressed & B::WUPS_CONFIG_BUTTON_A != 0 {
+                self.edit = Some(NumberEdit::new(self.current, self.min, self.max));
+                self.rejected = false;
+            }
+            return;
+        };
+
+        if pressed & B::WUPS_CONFIG_BUTTON_A != 0 {
+            let value = edit.value();
+            self.edit = None;
+            self.confirm(value);
+        } else if pressed & B::WUPS_CONFIG_BUTTON_B != 0 {
+            self.edit = None;
+        } else if pressed & B::WUPS_CONFIG_BUTTON_LEFT != 0 {
+            edit.cursor = edit.cursor.saturating_sub(1);
+        } else if pressed & B::WUPS_CONFIG_BUTTON_RIGHT != 0 {
+            edit.cursor = (edit.cursor + 1).min(edit.positions() - 1);
+        } else if pressed & B::WUPS_CONFIG_BUTTON_UP != 0 {
+            edit.step(1);
+        } else if pressed & B::WUPS_CONFIG_BUTTON_DOWN != 0 {
+            edit.step(9);
+        }
+    }
+
+    fn restore_default(&mut self) {
+        self.edit = None;
+        self.rejected = false;
+        self.set(self.default);
+    }
+
+    fn on_close(&mut self) {
+        self.edit = None;
+    }
+
+    fn is_movement_allowed(&self) -> bool {
+        self.edit.is_none()
+    }
+}
+
+/// Digits of a [NumberItem] while it is edited.
+struct NumberEdit {
+    /// Whether there is a sign in front of the digits, which is at cursor position 0.
+    signed: bool,
+    negative: bool,
+    digits: Vec<u8>,
+    cursor: usize,
+}
+
+impl NumberEdit {
+    /// Enough digits for all values from `min` to `max`, filled with `value`.
+    fn new(value: i32, min: i32, max: i32) -> Self {
+        let width = min
+            .unsigned_abs()
+            .max(max.unsigned_abs())
+            .checked_ilog10()
+            .unwrap_or(0) as usize
+            + 1;
+
+        let mut rest = value.unsigned_abs();
+        let mut digits = alloc::vec![0; width];
+        for digit in digits.iter_mut().rev() {
+            *digit = (rest % 10) as u8;
+            rest /= 10;
+        }
+
+        let signed = min < 0;
+        Self {
+            signed,
+            negative: value < 0,
+            cursor: signed as usize + width - 1,
+            digits,
+        }
+    }
+
+    fn positions(&self) -> usize {
+        self.signed as usize + self.digits.len()
+    }
+
+    /// Add `delta` (modulo 10) to the digit at the cursor, or flip the sign.
+    fn step(&mut self, delta: u8) {
+        match self.cursor.checked_sub(self.signed as usize) {
+            None => self.negative = !self.negative,
+            Some(i) => self.digits[i] = (self.digits[i] + delta) % 10,
+        }
+    }
+
+    fn value(&self) -> i64 {
+        let value = self
+            .digits
+            .iter()
+            .fold(0i64, |value, digit| value * 10 + *digit as i64);
+        if self.negative { -value } else { value }
+    }
+}
+
+impl core::fmt::Display for NumberEdit {
+    /// Digits with the one at the cursor in brackets, e.g. `-0[8]0`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let sign = if self.negative { '-' } else { '+' };
+        let chars = self
+            .signed
+            .then_some(sign)
+            .into_iter()
+            .chain(self.digits.iter().map(|d| (b'0' + d) as char));
+
+        for (i, c) in chars.enumerate() {
+            if i == self.cursor {
+                write!(f, "[{c}]")?;
+            } else {
+                write!(f, "{c}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// endregion
+
+// region: TextInput
+
+/// Free-form text, e.g. a server name or an IP address, entered character by character.
+///
+/// The config menu has no text entry and the system keyboard can't be shown from it (see
+/// [NumberInput#system-keyboard]), so the text is edited in place: press A to start editing,
+/// Left/Right move the cursor, Up/Down change the character at the cursor, X deletes it, A
+/// confirms and B cancels. Behind the last character, Up/Down append a new one. Characters are
+/// picked from the printable ASCII characters; other characters of the current value are kept
+/// until they are changed.
+///
+/// The value is stored as `String` and has at most `max_length` bytes, which must be below
+/// [STORAGE_MAX_LENGTH][storage::STORAGE_MAX_LENGTH] (the terminator is stored as well). A stored
+/// value which is too long is replaced by the default.
+///
+/// # Example
+///
+/// ```
+/// root.add(config::TextInput::new("Server", "server", "192.168.0.10", 15))?;
+///
+/// assert_eq!(storage::load::<String>("server").unwrap(), "192.168.0.10");
+/// ```
+#[derive(Debug)]
+pub struct TextInput {
+    text: String,
+    id: String,
+    default: String,
+    max_length: usize,
 }
 
-impl Toggle {
-    /// # Arguments
-    ///
-    /// - `text`: Text to display on the toggle.
-    /// - `id`: [storage][crate::storage] id to access value.
-    /// - `default`: Default value if id doesn't exist in storage.
-    /// - `trueValue`: Text to display when value is `true`.
-    /// - `falseValue`: Text to display when value is `false`.
-    pub fn new(text: &str, id: &str, default: bool, true_value: &str, false_value: &str) -> Self {
+impl TextInput {
+    pub fn new(text: &str, id: &str, default: &str, max_length: usize) -> Self {
+        Self {
+            text: text.to_string(),
+            id: id.to_string(),
+            default: default.to_string(),
+            max_length,
+        }
+    }
+
+    /// Text displayed for the input.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+}
+
+impl MenuItem for TextInput {
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn validate(&self) -> Result<(), MenuError> {
+        CString::new(self.default.as_str())?;
+        if self.max_length >= storage::STORAGE_MAX_LENGTH || self.default.len() > self.max_length {
+            return Err(MenuError::InvalidTextLength {
+                id: self.id.clone(),
+                default: self.default.len(),
+                max_length: self.max_length,
+            });
+        }
+        Ok(())
+    }
+
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        self.validate()?;
+        let text = CString::new(language::label(&self.text))?;
+
+        let current = match migrate::load::<String>(&self.id) {
+            Ok(v) if v.len() <= self.max_length => v,
+            Ok(_) => self.default.clone(),
+            Err(storage::StorageError::NotFound) => {
+                storage::store::<String>(&self.id, self.default.clone())?;
+                self.default.clone()
+            }
+            Err(e) => return Err(MenuError::STORAGE(e)),
+        };
+
+        custom::attach(
+            handle,
+            &text,
+            Box::new(TextItem {
+                id: self.id,
+                current,
+                default: self.default,
+                max_length: self.max_length,
+                edit: None,
+            }),
+        )
+    }
+}
+
+/// Characters Up/Down cycle through, starting with the ones most text consists of.
+const TEXT_CHARSET: &str = concat!(
+    " abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789",
+    ".-_:/@!\"#$%&'()*+,;<=>?[\\]^`{|}~"
+);
+
+struct TextItem {
+    id: String,
+    current: String,
+    default: String,
+    max_length: usize,
+    edit: Option<TextEdit>,
+}
+
+impl TextItem {
+    fn set(&mut self, value: String) {
+        if value != self.current {
+            let _ = staging::write(&self.id, registry::Value::String(value.clone()));
+            if let Ok(id) = CString::new(self.id.as_str()) {
+                registry::dispatch(&id, registry::Value::String(value.clone()));
+            }
+            self.current = value;
+        }
+    }
+}
+
+impl custom::CustomItem for TextItem {
+    fn display(&self) -> String {
+        match &self.edit {
+            Some(edit) => edit.to_string(),
+            None => self.current.clone(),
+        }
+    }
+
+    fn selected_display(&self) -> String {
+        match &self.edit {
+            Some(edit) => edit.to_string(),
+            None => alloc::format!("[ {} ]", self.current),
+        }
+    }
+
+    fn on_input(&mut self, pressed: sys::WUPSConfigButtons::Type) {
+        use sys::WUPSConfigButtons as B;
+
+        let Some(edit) = &mut self.edit else {
+            if pressed & B::WUPS_CONFIG_BUTTON_A != 0 {
+                self.edit = Some(TextEdit::new(&self.current, self.max_length));
+            }
+            return;
+        };
+
+        if pressed & B::WUPS_CONFIG_BUTTON_A != 0 {
+            let value = edit.chars.iter().collect();
+            self.edit = None;
+            self.set(value);
+        } else if pressed & B::WUPS_CONFIG_BUTTON_B != 0 {
+            self.edit = None;
+        } else if pressed & B::WUPS_CONFIG_BUTTON_LEFT != 0 {
+            edit.cursor = edit.cursor.saturating_sub(1);
+        } else if pressed & B::WUPS_CONFIG_BUTTON_RIGHT != 0 {
+            edit.cursor = (edit.cursor + 1).min(edit.chars.len());
+        } else if pressed & B::WUPS_CONFIG_BUTTON_UP != 0 {
+            edit.step(1);
+        } else if pressed & B::WUPS_CONFIG_BUTTON_DOWN != 0 {
+            edit.step(TEXT_CHARSET.len() - 1);
+        } else if pressed & B::WUPS_CONFIG_BUTTON_X != 0 {
+            edit.delete();
+        }
+    }
+
+    fn restore_default(&mut self) {
+        self.edit = None;
+        self.set(self.default.clone());
+    }
+
+    fn on_close(&mut self) {
+        self.edit = None;
+    }
+
+    fn is_movement_allowed(&self) -> bool {
+        self.edit.is_none()
+    }
+}
+
+/// Characters of a [TextItem] while it is edited.
+struct TextEdit {
+    chars: Vec<char>,
+    /// Position of the edited character, `chars.len()` for a new one at the end.
+    cursor: usize,
+    max_length: usize,
+}
+
+impl TextEdit {
+    fn new(value: &str, max_length: usize) -> Self {
+        let chars: Vec<char> = value.chars().collect();
+        Self {
+            cursor: chars.len(),
+            chars,
+            max_length,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.chars.iter().map(|c| c.len_utf8()).sum()
+    }
+
+    /// Move the character at the cursor `delta` places forward in [TEXT_CHARSET] (wrapping
+    /// around), or append the first one if the cursor is at the end.
+    fn step(&mut self, delta: usize) {
+        let charset = TEXT_CHARSET.as_bytes();
+        let len = self.len();
+        match self.chars.get_mut(self.cursor) {
+            Some(c) => {
+                // characters outside of the charset start over at its beginning
+                let index = charset.iter().position(|b| *b as char == *c);
+                *c = charset[index.map_or(0, |i| (i + delta) % charset.len())] as char;
+            }
+            None if len < self.max_length => self.chars.push(charset[0] as char),
+            _ => {}
+        }
+    }
+
+    /// Delete the character at the cursor, or the last one if the cursor is at the end.
+    fn delete(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        } else if self.chars.pop().is_some() {
+            self.cursor -= 1;
+        }
+    }
+}
+
+impl core::fmt::Display for TextEdit {
+    /// Characters with the one at the cursor in brackets, e.g. `192.1[6]8`, or `192.168[_]` at
+    /// the end.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, c) in self.chars.iter().enumerate() {
+            if i == self.cursor {
+                write!(f, "[{c}]")?;
+            } else {
+                write!(f, "{c}")?;
+            }
+        }
+        if self.cursor == self.chars.len() {
+            write!(f, "[_]")?;
+        }
+        Ok(())
+    }
+}
+
+// endregion
+
+// region: RangePair
+
+/// Two linked ranges for a lower and upper bound, e.g. a minimal and maximal delay.
+///
+/// The bounds are stored under `"<id>.min"` and `"<id>.max"` and always satisfy `min <= max`:
+/// moving one bound past the other pushes the other one along.
+///
+/// # Example
+///
+/// ```
+/// root.add(config::RangePair::new("Spawn delay", "delay", (2, 5), 0, 10))?;
+///
+/// assert_eq!(storage::load::<i32>("delay.min").unwrap(), 2);
+/// assert_eq!(storage::load::<i32>("delay.max").unwrap(), 5);
+/// // min is increased to 6...
+/// assert_eq!(storage::load::<i32>("delay.min").unwrap(), 6);
+/// assert_eq!(storage::load::<i32>("delay.max").unwrap(), 6);
+/// ```
+#[derive(Debug)]
+pub struct RangePair {
+    text: String,
+    id: String,
+    default: (i32, i32),
+    min: i32,
+    max: i32,
+}
+
+impl RangePair {
+    pub fn new(text: &str, id: &str, default: (i32, i32), min: i32, max: i32) -> Self {
+        debug_assert!(min < max);
+        debug_assert!(min <= default.0);
+        debug_assert!(default.0 <= default.1);
+        debug_assert!(default.1 <= max);
+
         Self {
             text: text.to_string(),
             id: id.to_string(),
             default,
-            true_value: true_value.to_string(),
-            false_value: false_value.to_string(),
+            min,
+            max,
+        }
+    }
+
+    /// Text displayed for the pair.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+
+    fn contains(&self, value: i32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    /// Current bounds, falling back to the defaults if they are missing or invalid.
+    fn load(&self, lower_id: &str, upper_id: &str) -> Result<(i32, i32), MenuError> {
+        let load = |id: &str| match migrate::load::<i32>(id) {
+            Ok(v) => Ok(Some(v).filter(|v| self.contains(*v))),
+            Err(storage::StorageError::NotFound) => Ok(None),
+            Err(e) => Err(MenuError::STORAGE(e)),
+        };
+
+        match (load(lower_id)?, load(upper_id)?) {
+            (Some(lower), Some(upper)) if lower <= upper => Ok((lower, upper)),
+            _ => {
+                storage::store::<i32>(lower_id, self.default.0)?;
+                storage::store::<i32>(upper_id, self.default.1)?;
+                Ok(self.default)
+            }
+        }
+    }
+}
+
+impl MenuItem for RangePair {
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn validate(&self) -> Result<(), MenuError> {
+        let (lower, upper) = self.default;
+        if self.min < self.max && self.contains(lower) && self.contains(upper) && lower <= upper {
+            Ok(())
+        } else {
+            Err(MenuError::InvalidRange {
+                id: self.id.clone(),
+                min: self.min,
+                default: if self.contains(lower) { upper } else { lower },
+                max: self.max,
+            })
+        }
+    }
+
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let lower_id = alloc::format!("{}.min", self.id);
+        let upper_id = alloc::format!("{}.max", self.id);
+        let text = language::label(&self.text);
+        let lower_text = CString::new(alloc::format!("{text} (min)"))?;
+        let upper_text = CString::new(alloc::format!("{text} (max)"))?;
+
+        let (lower, upper) = self.load(&lower_id, &upper_id)?;
+        let lower = Bound::new(lower_id, lower, self.default.0, self.min, self.max);
+        let upper = Bound::new(upper_id, upper, self.default.1, self.min, self.max);
+
+        // every bound pushes the other one along when it is moved past it
+        lower.push_on_change(&upper, i32::max)?;
+        upper.push_on_change(&lower, i32::min)?;
+
+        custom::attach(handle, &lower_text, Box::new(lower))?;
+        custom::attach(handle, &upper_text, Box::new(upper))
+    }
+}
+
+/// One bound of a [RangePair].
+///
+/// The value is shared with the callback of the other bound, which pushes it along.
+struct Bound {
+    id: String,
+    value: Arc<AtomicI32>,
+    default: i32,
+    min: i32,
+    max: i32,
+}
+
+impl Bound {
+    fn new(id: String, value: i32, default: i32, min: i32, max: i32) -> Self {
+        Self {
+            id,
+            value: Arc::new(AtomicI32::new(value)),
+            default,
+            min,
+            max,
+        }
+    }
+
+    /// When this bound changes, replace the value of `other` by `push(other, new value)`.
+    fn push_on_change(&self, other: &Bound, push: fn(i32, i32) -> i32) -> Result<(), MenuError> {
+        let other_id = other.id.clone();
+        let other_value = other.value.clone();
+
+        registry::register(
+            CString::new(self.id.as_str())?,
+            Box::new(move |value| {
+                if let registry::Value::I32(value) = value {
+                    let current = other_value.load(Ordering::Relaxed);
+                    let pushed = push(current, value);
+                    if pushed != current {
+                        other_value.store(pushed, Ordering::Relaxed);
+                        let _ = staging::write(&other_id, registry::Value::I32(pushed));
+                    }
+                }
+            }),
+        )
+    }
+
+    fn set(&mut self, value: i32) {
+        let value = value.clamp(self.min, self.max);
+        if value != self.value.swap(value, Ordering::Relaxed) {
+            let _ = staging::write(&self.id, registry::Value::I32(value));
+            if let Ok(id) = CString::new(self.id.as_str()) {
+                registry::dispatch(&id, registry::Value::I32(value));
+            }
+        }
+    }
+}
+
+impl custom::CustomItem for Bound {
+    fn display(&self) -> String {
+        self.value.load(Ordering::Relaxed).to_string()
+    }
+
+    fn selected_display(&self) -> String {
+        alloc::format!("< {} >", self.value.load(Ordering::Relaxed))
+    }
+
+    fn on_input(&mut self, pressed: sys::WUPSConfigButtons::Type) {
+        use sys::WUPSConfigButtons as B;
+
+        let value = self.value.load(Ordering::Relaxed);
+        if pressed & B::WUPS_CONFIG_BUTTON_LEFT != 0 {
+            self.set(value.saturating_sub(1));
+        } else if pressed & B::WUPS_CONFIG_BUTTON_RIGHT != 0 {
+            self.set(value.saturating_add(1));
+        }
+    }
+
+    fn restore_default(&mut self) {
+        self.set(self.default);
+    }
+}
+
+// endregion
+
+// region: Options
+
+/// Options of [Select] and [Stepper] as `(stored value, displayed text)` pairs.
+#[derive(Debug)]
+struct Options(Vec<(u32, String)>);
+
+impl Options {
+    /// Options are stored as their index.
+    fn indexed(options: Vec<impl ToString>) -> Self {
+        Self(
+            options
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (i as u32, s.to_string()))
+                .collect(),
+        )
+    }
+
+    fn with_values(options: Vec<(u32, impl ToString)>) -> Self {
+        Self(options.iter().map(|(v, s)| (*v, s.to_string())).collect())
+    }
+
+    fn for_enum<E: storage::StorageEnum>() -> Self {
+        Self(
+            E::VARIANTS
+                .iter()
+                .map(|v| (v.to_discriminant(), v.name().to_string()))
+                .collect(),
+        )
+    }
+
+    /// Position of the option with the stored `value`.
+    fn index_of(&self, value: u32) -> Option<usize> {
+        self.0.iter().position(|(v, _)| *v == value)
+    }
+
+    fn validate(&self, id: &str, default: u32) -> Result<(), MenuError> {
+        if self.0.is_empty() {
+            Err(MenuError::EmptySelect(id.to_string()))
+        } else if self.index_of(default).is_none() {
+            Err(MenuError::InvalidSelectDefault(id.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Index of the default and of the current value in storage, which falls back to the default
+    /// if the stored value isn't an option. The default is stored if `id` doesn't exist yet.
+    fn load(&self, id: &str, default: u32) -> Result<(usize, usize), MenuError> {
+        let default_index = self.index_of(default).unwrap_or(0);
+        let current = match migrate::load::<u32>(id) {
+            Ok(v) => self.index_of(v).unwrap_or(default_index),
+            Err(storage::StorageError::NotFound) => {
+                storage::store::<u32>(id, default)?;
+                default_index
+            }
+            Err(e) => return Err(MenuError::STORAGE(e)),
+        };
+        Ok((default_index, current))
+    }
+}
+
+// endregion
+
+// region: Select
+
+/// Select a value from a predefined list.
+///
+/// # Example
+///
+/// ```
+/// root.add(config::Select::new(
+///     "Select",
+///     "my_select_id",
+///     0,
+///     vec!["A", "B", "C"],
+/// ))?;
+///
+/// assert_eq!(storage::load::<u32>("my_select_id").unwrap(), 0);
+/// // select is switched to "C"...
+/// assert_eq!(storage::load::<u32>("my_select_id").unwrap(), 2);
+/// ```
+///
+/// # Enums
+///
+/// [for_enum][Select::for_enum] uses the variants of a [StorageEnum][storage::StorageEnum] as
+/// options and stores their discriminant, so the value can be loaded as the enum directly instead
+/// of mapping an index by hand.
+///
+/// ```
+/// #[derive(Debug, Default, PartialEq, StorageEnum)]
+/// enum Quality {
+///     Low,
+///     #[default]
+///     Medium,
+///     High = 10,
+/// }
+///
+/// root.add(config::Select::for_enum("Quality", "quality", Quality::Medium))?;
+///
+/// assert_eq!(storage::load::<Quality>("quality").unwrap(), Quality::Medium);
+/// // select is switched to "High"...
+/// assert_eq!(storage::load::<Quality>("quality").unwrap(), Quality::High);
+/// // the raw value is the discriminant, not the index
+/// assert_eq!(storage::load::<u32>("quality").unwrap(), 10);
+/// ```
+///
+/// The discriminants are what is persisted, so give the variants explicit discriminants before
+/// reordering them. A stored value which isn't a variant (anymore) is replaced with the default
+/// when the menu is opened; until then, loading it as the enum fails with
+/// [UnexpectedDataType][storage::StorageError::UnexpectedDataType]. With
+/// [staged_transactional][ConfigMenu::staged_transactional], the new value can only be loaded
+/// once the menu is closed.
+#[derive(Debug)]
+pub struct Select {
+    text: String,
+    id: String,
+    default: u32,
+    options: Options,
+    disabled: bool,
+    on_change: Option<registry::OnChange<u32>>,
+}
+
+impl Select {
+    /// Options are stored as their index.
+    pub fn new(text: &str, id: &str, default: u32, options: Vec<impl ToString>) -> Self {
+        debug_assert!(default < options.len() as u32);
+        Select {
+            text: text.to_string(),
+            id: id.to_string(),
+            default,
+            options: Options::indexed(options),
+            disabled: false,
+            on_change: None,
+        }
+    }
+
+    /// Options are stored as the given value instead of their index.
+    ///
+    /// ```
+    /// root.add(config::Select::with_values(
+    ///     "Refresh rate",
+    ///     "refresh_rate",
+    ///     60,
+    ///     vec![(30, "30 Hz"), (60, "60 Hz")],
+    /// ))?;
+    /// ```
+    pub fn with_values(
+        text: &str,
+        id: &str,
+        default: u32,
+        options: Vec<(u32, impl ToString)>,
+    ) -> Self {
+        Select {
+            text: text.to_string(),
+            id: id.to_string(),
+            default,
+            options: Options::with_values(options),
+            disabled: false,
+            on_change: None,
+        }
+    }
+
+    /// Options are the variants of `E`, stored as their discriminant.
+    pub fn for_enum<E: storage::StorageEnum>(text: &str, id: &str, default: E) -> Self {
+        Select {
+            text: text.to_string(),
+            id: id.to_string(),
+            default: default.to_discriminant(),
+            options: Options::for_enum::<E>(),
+            disabled: false,
+            on_change: None,
         }
     }
+
+    /// Show the item as a read-only label with its current value, e.g. when it depends on another
+    /// setting which is off. See [disabled items][MenuItem#disabled-items].
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Run `callback` with the new stored value (not index) whenever the user changes the select,
+    /// see [change callbacks][MenuItem#change-callbacks].
+    pub fn on_change(mut self, callback: impl FnMut(u32) + Send + 'static) -> Self {
+        self.on_change = Some(registry::OnChange::new(callback));
+        self
+    }
+
+    /// Text displayed for the select.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether `value` is one of the stored values (not indices) of the options.
+    ///
+    /// Stored values for which this is `false` are replaced with the default when the menu is
+    /// opened.
+    ///
+    /// ```
+    /// let select = config::Select::new("Select", "select", 0, vec!["A", "B", "C"]);
+    /// assert!(select.contains(0));
+    /// assert!(select.contains(2));
+    /// assert!(!select.contains(3));
+    ///
+    /// let select = config::Select::with_values("Select", "select", 10, vec![(10, "A"), (20, "B")]);
+    /// assert!(select.contains(20));
+    /// assert!(!select.contains(0));
+    /// assert!(!select.contains(1));
+    /// ```
+    pub fn contains(&self, value: u32) -> bool {
+        self.options.index_of(value).is_some()
+    }
 }
 
-impl MenuItem for Toggle {
+impl MenuItem for Select {
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn validate(&self) -> Result<(), MenuError> {
+        self.options.validate(&self.id, self.default)
+    }
+
     fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
-        let text = CString::new(self.text.as_str()).unwrap();
-        let id = CString::new(self.id.as_str()).unwrap();
-        let true_value = CString::new(self.true_value.as_str()).unwrap();
-        let false_value = CString::new(self.false_value.as_str()).unwrap();
+        let text = CString::new(language::label(&self.text))?;
+        let id = CString::new(self.id.as_str())?;
+
+        let mut pairs = CPairs::new(&self.options)?;
+        let (default, current) = self.options.load(&self.id, self.default)?;
+
+        if self.disabled {
+            let value = self
+                .options
+                .0
+                .get(current)
+                .map_or("", |(_, text)| text.as_str());
+            return Label::value(&self.text, language::label(value)).attach(handle);
+        }
 
-        let current = match storage::load::<bool>(&self.id) {
-            Ok(v) => v,
-            Err(storage::StorageError::NotFound) => {
-                storage::store::<bool>(&self.id, self.default)?;
-                self.default
-            }
-            Err(e) => return Err(MenuError::STORAGE(e)),
-        };
+        if let Some(on_change) = self.on_change {
+            on_change.register(&self.id, |value| match value {
+                registry::Value::U32(value) => Some(value),
+                _ => None,
+            })?;
+        }
 
         let status = unsafe {
-            sys::WUPSConfigItemBoolean_AddToCategoryEx(
+            sys::WUPSConfigItemMultipleValues_AddToCategory(
                 handle,
                 id.as_ptr(),
                 text.as_ptr(),
-                self.default,
-                current,
-                Some(_callback_toggle_changed),
-                true_value.as_ptr(),
-                false_value.as_ptr(),
+                default as i32,
+                current as i32,
+                pairs.as_mut_ptr(),
+                pairs.len() as i32,
+                Some(_callback_select_changed),
             )
         };
+        // only needs to live until here, see `CPairs`
+        drop(pairs);
         MenuError::try_from(status)?;
 
         Ok(())
     }
 }
 
-extern "C" fn _callback_toggle_changed(item: *mut sys::ConfigItemBoolean, value: bool) {
-    let _ = storage::store::<bool>(
-        &unsafe { CStr::from_ptr((*item).identifier) }.to_string_lossy(),
-        value,
-    );
+/// Option pairs in the layout of the C API, together with the strings they point to.
+///
+/// `WUPSConfigItemMultipleValues_AddToCategory` copies the pairs and `strdup`s their names, so they
+/// only have to stay valid for the duration of the call. The pairs hold raw pointers into
+/// `_strings`, which is why both are kept in one value: the pointers can't outlive the strings as
+/// long as the pairs are only accessed through this struct.
+struct CPairs {
+    _strings: Vec<CString>,
+    pairs: Vec<sys::ConfigItemMultipleValuesPair>,
+}
+
+impl CPairs {
+    fn new(options: &Options) -> Result<Self, NulError> {
+        let strings = options
+            .0
+            .iter()
+            .map(|(_, s)| CString::new(language::label(s)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // moving a `CString` doesn't move its heap buffer, so these pointers stay valid
+        let pairs = options
+            .0
+            .iter()
+            .zip(&strings)
+            .map(|((value, _), s)| sys::ConfigItemMultipleValuesPair {
+                value: *value,
+                valueName: s.as_ptr(),
+            })
+            .collect();
+
+        Ok(Self {
+            _strings: strings,
+            pairs,
+        })
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut sys::ConfigItemMultipleValuesPair {
+        self.pairs.as_mut_ptr()
+    }
+
+    fn len(&self) -> usize {
+        self.pairs.len()
+    }
+}
+
+/// `value` is the value of the selected pair, not its index.
+extern "C" fn _callback_select_changed(item: *mut sys::ConfigItemMultipleValues, value: u32) {
+    let id = unsafe { CStr::from_ptr((*item).identifier) };
+    let _ = staging::write(&id.to_string_lossy(), registry::Value::U32(value));
+    registry::dispatch(id, registry::Value::U32(value));
 }
 
 // endregion
 
-// region: Range
+// region: MultiSelect
 
-/// Select a number from a range.
+/// Select any number of options from a predefined list, e.g. which log channels are enabled.
+///
+/// The options are shown as toggles in a sub menu named `text`. The selection is stored as a `u32`
+/// bitmask under `id`, option `i` being bit `i`, so there can be at most 32 options.
 ///
 /// # Example
 ///
 /// ```
-/// root.add(config::Range::new("Range", "my_range_id", 0, -5, 5))?;
+/// root.add(config::MultiSelect::new(
+///     "Log channels",
+///     "log_channels",
+///     0b001,
+///     vec!["Errors", "Network", "Input"],
+/// ))?;
 ///
-/// assert_eq!(storage::load::<i32>("my_range_id").unwrap(), 0);
-/// // range is increased...
-/// assert_eq!(storage::load::<i32>("my_range_id").unwrap(), 1);
+/// assert_eq!(storage::load::<u32>("log_channels").unwrap(), 0b001);
+/// // "Network" and "Input" are switched on...
+/// assert_eq!(storage::load::<u32>("log_channels").unwrap(), 0b111);
 /// ```
-pub struct Range {
+#[derive(Debug)]
+pub struct MultiSelect {
     text: String,
     id: String,
-    default: i32,
-    min: i32,
-    max: i32,
+    default: u32,
+    options: Vec<String>,
 }
 
-impl Range {
-    pub fn new(text: &str, id: &str, default: i32, min: i32, max: i32) -> Self {
-        debug_assert!(min < max);
-        debug_assert!(min < default);
-        debug_assert!(default < max);
-
+impl MultiSelect {
+    /// # Arguments
+    ///
+    /// - `text`: Text of the sub menu.
+    /// - `id`: [storage][crate::storage] id to access the bitmask.
+    /// - `default`: Bitmask of the options which are selected if id doesn't exist in storage.
+    /// - `options`: Text of the toggle of each option.
+    pub fn new(text: &str, id: &str, default: u32, options: Vec<impl ToString>) -> Self {
+        debug_assert!(options.len() <= 32);
         Self {
             text: text.to_string(),
             id: id.to_string(),
             default,
-            min,
-            max,
+            options: options.iter().map(|s| s.to_string()).collect(),
         }
     }
-}
 
-impl MenuItem for Range {
-    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
-        let text = CString::new(self.text.as_str()).unwrap();
-        let id = CString::new(self.id.as_str()).unwrap();
+    /// Text displayed for the sub menu.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
 
-        let current = match storage::load::<i32>(&self.id) {
-            Ok(v) => {
-                if v > self.min && v < self.max {
-                    v
-                } else {
-                    self.default
-                }
-            }
+    /// Bits of all options.
+    fn mask(&self) -> u32 {
+        ((1u64 << self.options.len().min(32)) - 1) as u32
+    }
+
+    /// Current bitmask in storage. The default is stored if `id` doesn't exist yet.
+    fn load(&self) -> Result<u32, MenuError> {
+        // bits of options which don't exist (anymore) are dropped
+        match migrate::load::<u32>(&self.id) {
+            Ok(v) => Ok(v & self.mask()),
             Err(storage::StorageError::NotFound) => {
-                storage::store::<i32>(&self.id, self.default)?;
-                self.default
+                storage::store::<u32>(&self.id, self.default)?;
+                Ok(self.default)
             }
-            Err(e) => return Err(MenuError::STORAGE(e)),
-        };
-
-        let status = unsafe {
-            sys::WUPSConfigItemIntegerRange_AddToCategory(
-                handle,
-                id.as_ptr(),
-                text.as_ptr(),
-                self.default,
-                current,
-                self.min,
-                self.max,
-                Some(_callback_range_changed),
-            )
-        };
-        MenuError::try_from(status)?;
-
-        Ok(())
+            Err(e) => Err(MenuError::STORAGE(e)),
+        }
     }
-}
 
-extern "C" fn _callback_range_changed(item: *mut sys::ConfigItemIntegerRange, value: i32) {
-    let _ = storage::store::<i32>(
-        &unsafe { CStr::from_ptr((*item).identifier) }.to_string_lossy(),
-        value,
-    );
-}
-
-// this is overkill but should outline on how to extend API in future
-/*
-pub trait RangeCompatible {
-    type T: storage::StorageCompatible<T: From<i32> + Into<i32>>;
-    extern "C" fn callback(item: *mut sys::ConfigItemIntegerRange, value: i32) {
-        let _ = storage::store::<Self::T>(
-            &unsafe { CStr::from_ptr((*item).identifier) }.to_string_lossy(),
-            From::from(value),
-        );
+    /// Callback of the toggle of `flag`, which writes the updated bitmask.
+    fn on_toggle(&self, flag: u32, selected: Arc<AtomicU32>) -> registry::Callback {
+        let id = self.id.clone();
+        Box::new(move |value| {
+            if let registry::Value::Bool(value) = value {
+                let mask = if value {
+                    selected.fetch_or(flag, Ordering::Relaxed) | flag
+                } else {
+                    selected.fetch_and(!flag, Ordering::Relaxed) & !flag
+                };
+                let _ = staging::write(&id, registry::Value::U32(mask));
+            }
+        })
     }
 }
 
-impl RangeCompatible for i32 {
-    type T = i32;
-}
+impl MenuItem for MultiSelect {
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
 
-pub struct Range<T: RangeCompatible> {
-    text: String,
-    id: String,
-    default: T,
-    min: T,
-    max: T,
-}
+    fn validate(&self) -> Result<(), MenuError> {
+        if self.options.is_empty() {
+            Err(MenuError::EmptySelect(self.id.clone()))
+        } else if self.options.len() > 32 {
+            Err(MenuError::TooManyOptions(self.id.clone()))
+        } else if self.default & !self.mask() != 0 {
+            Err(MenuError::InvalidSelectDefault(self.id.clone()))
+        } else {
+            Ok(())
+        }
+    }
 
-impl<T: RangeCompatible> Range<T> {
-    pub fn new(text: &str, id: &str, default: T, min: T, max: T) -> Self {
-        Self {
-            text: text.to_string(),
-            id: id.to_string(),
-            default,
-            min,
-            max,
+    fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
+        let on = CString::new(language::label("On"))?;
+        let off = CString::new(language::label("Off"))?;
+
+        let current = self.load()?;
+        let selected = Arc::new(AtomicU32::new(current));
+
+        let menu = Menu::new(&self.text)?;
+        for (bit, option) in self.options.iter().enumerate().take(32) {
+            let flag = 1u32 << bit;
+            let toggle_id = CString::new(alloc::format!("{}.{bit}", self.id))?;
+            let text = CString::new(language::label(option))?;
+
+            registry::register(toggle_id.clone(), self.on_toggle(flag, selected.clone()))?;
+
+            let status = unsafe {
+                sys::WUPSConfigItemBoolean_AddToCategoryEx(
+                    menu.handle,
+                    toggle_id.as_ptr(),
+                    text.as_ptr(),
+                    self.default & flag != 0,
+                    current & flag != 0,
+                    Some(_callback_multi_select_changed),
+                    on.as_ptr(),
+                    off.as_ptr(),
+                )
+            };
+            MenuError::try_from(status)?;
         }
+        menu.attach(handle)
     }
 }
 
-impl<T: RangeCompatible> MenuItem for Range<T> {
-    fn attach(&self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
-        todo!()
-    }
+/// The toggles are identified as `"<id>.<bit>"`, their callbacks update the bitmask.
+extern "C" fn _callback_multi_select_changed(item: *mut sys::ConfigItemBoolean, value: bool) {
+    let id = unsafe { CStr::from_ptr((*item).identifier) };
+    registry::dispatch(id, registry::Value::Bool(value));
 }
-    */
 
 // endregion
 
-// region: Select
+// region: Stepper
 
-/// Select a value from a predefined list.
+/// Cycle through a few values in place.
+///
+/// Like [Select], but pressing right (or A) moves to the next and left to the previous option,
+/// wrapping around at both ends (Low → Medium → High → Low → ...). The selected value is written to
+/// storage under `id` immediately, using the same values as [Select] (index, explicit value or enum
+/// discriminant).
 ///
 /// # Example
 ///
 /// ```
-/// root.add(config::Select::new(
-///     "Select",
-///     "my_select_id",
-///     0,
-///     vec!["A", "B", "C"],
-/// ))?;
+/// #[derive(Default, StorageEnum)]
+/// enum Quality {
+///     Low,
+///     #[default]
+///     Medium,
+///     High,
+/// }
 ///
-/// assert_eq!(storage::load::<u32>("my_select_id").unwrap(), 0);
-/// // select is switched to "C"...
-/// assert_eq!(storage::load::<u32>("my_select_id").unwrap(), 2);
+/// root.add(config::Stepper::for_enum("Quality", "quality", Quality::Medium))?;
+/// root.add(config::Stepper::new("Speed", "speed", 0, vec!["1x", "2x", "4x"]))?;
 /// ```
-pub struct Select {
+#[derive(Debug)]
+pub struct Stepper {
     text: String,
     id: String,
     default: u32,
-    options: Vec<String>,
+    options: Options,
+    disabled: bool,
 }
 
-impl Select {
+impl Stepper {
+    /// Options are stored as their index.
     pub fn new(text: &str, id: &str, default: u32, options: Vec<impl ToString>) -> Self {
         debug_assert!(default < options.len() as u32);
-        Select {
+        Self {
             text: text.to_string(),
             id: id.to_string(),
             default,
-            options: options.iter().map(|s| s.to_string()).collect(),
+            options: Options::indexed(options),
+            disabled: false,
+        }
+    }
+
+    /// Options are stored as the given value instead of their index.
+    pub fn with_values(
+        text: &str,
+        id: &str,
+        default: u32,
+        options: Vec<(u32, impl ToString)>,
+    ) -> Self {
+        Self {
+            text: text.to_string(),
+            id: id.to_string(),
+            default,
+            options: Options::with_values(options),
+            disabled: false,
         }
     }
+
+    /// Options are the variants of `E`, stored as their discriminant.
+    pub fn for_enum<E: storage::StorageEnum>(text: &str, id: &str, default: E) -> Self {
+        Self {
+            text: text.to_string(),
+            id: id.to_string(),
+            default: default.to_discriminant(),
+            options: Options::for_enum::<E>(),
+            disabled: false,
+        }
+    }
+
+    /// Show the item as a read-only label with its current value, e.g. when it depends on another
+    /// setting which is off. See [disabled items][MenuItem#disabled-items].
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Text displayed for the stepper.
+    pub fn name(&self) -> &str {
+        &self.text
+    }
 }
 
-impl MenuItem for Select {
+impl MenuItem for Stepper {
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn validate(&self) -> Result<(), MenuError> {
+        self.options.validate(&self.id, self.default)
+    }
+
     fn attach(self, handle: sys::WUPSConfigCategoryHandle) -> Result<(), MenuError> {
-        let text = CString::new(self.text.as_str()).unwrap();
-        let id = CString::new(self.id.as_str()).unwrap();
+        let text = CString::new(language::label(&self.text))?;
+        if self.options.0.is_empty() {
+            return Err(MenuError::EmptySelect(self.id));
+        }
 
-        let strings: Result<Vec<CString>, NulError> =
-            self.options.into_iter().map(|s| CString::new(s)).collect();
-        let strings = strings?;
+        let (default, current) = self.options.load(&self.id, self.default)?;
 
-        let mut options: Vec<_> = strings
-            .iter()
-            .enumerate()
-            .map(|(i, s)| sys::ConfigItemMultipleValuesPair {
-                value: i as u32,
-                valueName: s.as_ptr(),
-            })
-            .collect();
+        if self.disabled {
+            let value = self
+                .options
+                .0
+                .get(current)
+                .map_or("", |(_, text)| text.as_str());
+            return Label::value(&self.text, language::label(value)).attach(handle);
+        }
 
-        let current = match storage::load::<u32>(&self.id) {
-            Ok(v) => {
-                if v > 0 && v < options.len() as u32 {
-                    v
-                } else {
-                    self.default
-                }
-            }
-            Err(storage::StorageError::NotFound) => {
-                storage::store::<u32>(&self.id, self.default)?;
-                self.default
-            }
-            Err(e) => return Err(MenuError::STORAGE(e)),
-        };
+        custom::attach(
+            handle,
+            &text,
+            Box::new(StepperItem {
+                id: self.id,
+                options: self.options,
+                default,
+                current,
+            }),
+        )
+    }
+}
 
-        let status = unsafe {
-            sys::WUPSConfigItemMultipleValues_AddToCategory(
-                handle,
-                id.as_ptr(),
-                text.as_ptr(),
-                self.default as i32,
-                current as i32,
-                options.as_mut_ptr(),
-                options.len() as i32,
-                Some(_callback_select_changed),
-            )
-        };
-        MenuError::try_from(status)?;
+struct StepperItem {
+    id: String,
+    options: Options,
+    default: usize,
+    current: usize,
+}
 
-        Ok(())
+impl StepperItem {
+    fn set(&mut self, index: usize) {
+        if index != self.current {
+            self.current = index;
+            let value = self.options.0[index].0;
+            let _ = staging::write(&self.id, registry::Value::U32(value));
+            if let Ok(id) = CString::new(self.id.as_str()) {
+                registry::dispatch(&id, registry::Value::U32(value));
+            }
+        }
     }
 }
 
-extern "C" fn _callback_select_changed(item: *mut sys::ConfigItemMultipleValues, index: u32) {
-    let _ = storage::store::<u32>(
-        &unsafe { CStr::from_ptr((*item).identifier) }.to_string_lossy(),
-        index,
-    );
+impl custom::CustomItem for StepperItem {
+    fn display(&self) -> String {
+        language::label(&self.options.0[self.current].1).to_string()
+    }
+
+    fn selected_display(&self) -> String {
+        alloc::format!("< {} >", language::label(&self.options.0[self.current].1))
+    }
+
+    fn on_input(&mut self, pressed: sys::WUPSConfigButtons::Type) {
+        use sys::WUPSConfigButtons as B;
+
+        let len = self.options.0.len();
+        if pressed & B::WUPS_CONFIG_BUTTON_LEFT != 0 {
+            self.set((self.current + len - 1) % len);
+        } else if pressed & (B::WUPS_CONFIG_BUTTON_RIGHT | B::WUPS_CONFIG_BUTTON_A) != 0 {
+            self.set((self.current + 1) % len);
+        }
+    }
+
+    fn restore_default(&mut self) {
+        self.set(self.default);
+    }
 }
 
 // endregion
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    mod range {
+        use super::*;
+
+        #[test]
+        fn try_new() {
+            assert!(Range::try_new("Range", "range", 0, -5, 5).is_ok());
+            assert!(matches!(
+                Range::try_new("Range", "range", 6, -5, 5),
+                Err(MenuError::InvalidRange { default: 6, .. })
+            ));
+
+            assert!(Range::try_new("Range", "range", 0.5, 0.0, 1.0).is_ok());
+            for (default, min, max) in [
+                (0.5, 1.0, 0.0),
+                (f32::NAN, 0.0, 1.0),
+                (0.5, f32::NAN, 1.0),
+                (0.5, 0.0, f32::NAN),
+            ] {
+                assert!(matches!(
+                    Range::try_new("Range", "range", default, min, max),
+                    Err(MenuError::InvalidFloatRange(id)) if id == "range"
+                ));
+            }
+        }
+    }
+
+    mod range_pair {
+        use super::*;
+        use crate::storage::mock;
+
+        fn bounds() -> (Bound, Bound) {
+            let lower = Bound::new("fps.min".into(), 20, 20, 0, 60);
+            let upper = Bound::new("fps.max".into(), 40, 40, 0, 60);
+            lower.push_on_change(&upper, i32::max).unwrap();
+            upper.push_on_change(&lower, i32::min).unwrap();
+            (lower, upper)
+        }
+
+        #[test]
+        fn pushes_other_bound() {
+            let _lock = mock::lock();
+            let (mut lower, mut upper) = bounds();
+
+            lower.set(30);
+            assert_eq!(upper.value.load(Ordering::Relaxed), 40);
+            assert_eq!(
+                storage::load::<i32>("fps.max"),
+                Err(storage::StorageError::NotFound)
+            );
+
+            lower.set(50);
+            assert_eq!(upper.value.load(Ordering::Relaxed), 50);
+            assert_eq!(storage::load::<i32>("fps.min"), Ok(50));
+            assert_eq!(storage::load::<i32>("fps.max"), Ok(50));
+
+            upper.set(10);
+            assert_eq!(lower.value.load(Ordering::Relaxed), 10);
+            assert_eq!(storage::load::<i32>("fps.min"), Ok(10));
+            assert_eq!(storage::load::<i32>("fps.max"), Ok(10));
+        }
+
+        #[test]
+        fn stages_pushed_bound() {
+            let _lock = mock::lock();
+            let (mut lower, _upper) = bounds();
+
+            staging::begin();
+            lower.set(70);
+            assert_eq!(lower.value.load(Ordering::Relaxed), 60);
+            assert_eq!(staging::pending_ids(), ["fps.max", "fps.min"]);
+            assert_eq!(
+                storage::load::<i32>("fps.max"),
+                Err(storage::StorageError::NotFound)
+            );
+
+            staging::finish().unwrap();
+            assert_eq!(storage::load::<i32>("fps.min"), Ok(60));
+            assert_eq!(storage::load::<i32>("fps.max"), Ok(60));
+        }
+    }
+
+    mod multi_select {
+        use super::*;
+        use crate::storage::mock;
+
+        fn channels() -> MultiSelect {
+            MultiSelect::new(
+                "Channels",
+                "channels",
+                0b001,
+                vec!["Cafe", "Network", "Input"],
+            )
+        }
+
+        #[test]
+        fn toggles_update_mask() {
+            let _lock = mock::lock();
+            let select = channels();
+            let selected = Arc::new(AtomicU32::new(select.load().unwrap()));
+            assert_eq!(storage::load::<u32>("channels"), Ok(0b001));
+
+            for bit in 0..3 {
+                let id = CString::new(alloc::format!("channels.{bit}")).unwrap();
+                let callback = select.on_toggle(1 << bit, selected.clone());
+                registry::register(id, callback).unwrap();
+            }
+            let toggle = |bit: u32, value| {
+                let id = CString::new(alloc::format!("channels.{bit}")).unwrap();
+                assert!(registry::dispatch(&id, registry::Value::Bool(value)));
+            };
+
+            toggle(1, true);
+            toggle(2, true);
+            assert_eq!(storage::load::<u32>("channels"), Ok(0b111));
+            toggle(0, false);
+            assert_eq!(storage::load::<u32>("channels"), Ok(0b110));
+            assert_eq!(selected.load(Ordering::Relaxed), 0b110);
+        }
+
+        #[test]
+        fn drops_unknown_bits() {
+            let _lock = mock::lock();
+            storage::store::<u32>("channels", 0b1010).unwrap();
+            assert_eq!(channels().load().unwrap(), 0b010);
+        }
+    }
+
+    mod options {
+        use super::*;
+        use crate::storage::mock;
+
+        #[test]
+        fn indexed() {
+            let _lock = mock::lock();
+            let options = Options::indexed(vec!["Low", "Medium", "High"]);
+
+            // missing, the default is stored
+            assert_eq!(options.load("quality", 1).unwrap(), (1, 1));
+            assert_eq!(storage::load::<u32>("quality"), Ok(1));
+
+            storage::store::<u32>("quality", 2).unwrap();
+            assert_eq!(options.load("quality", 1).unwrap(), (1, 2));
+
+            // not an option (anymore)
+            storage::store::<u32>("quality", 3).unwrap();
+            assert_eq!(options.load("quality", 1).unwrap(), (1, 1));
+        }
+
+        #[test]
+        fn with_values() {
+            let _lock = mock::lock();
+            let options = Options::with_values(vec![(30, "30 FPS"), (60, "60 FPS"), (0, "Off")]);
+
+            assert_eq!(options.load("fps", 60).unwrap(), (1, 1));
+            assert_eq!(storage::load::<u32>("fps"), Ok(60));
+
+            storage::store::<u32>("fps", 0).unwrap();
+            assert_eq!(options.load("fps", 60).unwrap(), (1, 2));
+
+            // an index instead of a value isn't mistaken for the option at that position
+            storage::store::<u32>("fps", 2).unwrap();
+            assert_eq!(options.load("fps", 60).unwrap(), (1, 1));
+        }
+
+        #[test]
+        fn validate() {
+            let options = Options::with_values(vec![(30, "30 FPS"), (60, "60 FPS")]);
+            assert!(options.validate("fps", 60).is_ok());
+            assert!(matches!(
+                options.validate("fps", 1),
+                Err(MenuError::InvalidSelectDefault(id)) if id == "fps"
+            ));
+            assert!(matches!(
+                Options::indexed(Vec::<&str>::new()).validate("fps", 0),
+                Err(MenuError::EmptySelect(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Opening the config menu from the plugin.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use wut::sys::{VPADButtons, VPADStatus};
+
+/// Default combo of the Aroma plugin menu: L + DPAD Down + Minus.
+const MENU_COMBO: u32 =
+    VPADButtons::VPAD_BUTTON_L | VPADButtons::VPAD_BUTTON_DOWN | VPADButtons::VPAD_BUTTON_MINUS;
+
+static OPEN: AtomicBool = AtomicBool::new(false);
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the config menu is currently open.
+///
+/// Only tracked for menus registered with [ConfigMenu][super::ConfigMenu] or
+/// [SettingsMenu][super::SettingsMenu].
+pub fn is_menu_open() -> bool {
+    OPEN.load(Ordering::Acquire)
+}
+
+/// Ask for the config menu to be opened with the next Gamepad sample.
+///
+/// The config API (`WUPSConfigAPI_*`) has no function to open the menu, it is only opened by the
+/// plugin loader when it sees the menu combo on the Gamepad. This therefore only sets a flag which
+/// [inject_open_request] turns into a press of the default combo (L + DPAD Down + Minus) in the
+/// plugin's own `VPADRead` hook.
+///
+/// Returns `false` and does nothing if the menu is already open or an open is already pending, so
+/// calling this every frame while a custom combo is held only opens the menu once.
+///
+/// # Caveats
+///
+/// - The plugin must hook `VPADRead` and call [inject_open_request], otherwise nothing happens.
+/// - The loader must still use the default combo, a user configured combo isn't known to plugins.
+/// - The loader has to see the modified sample. This depends on the order in which the hooks of
+///   the loader and of plugins are patched, so it should be tested with the target setup.
+/// - The menu lists all plugins, it opens at the root and not at this plugin's entry.
+pub fn request_open_menu() -> bool {
+    !is_menu_open()
+        && REQUESTED
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+}
+
+/// Press the menu combo in `status` if the menu was [requested][request_open_menu].
+///
+/// Call this in a `VPADRead` hook after the original function with the newest sample. Returns
+/// whether the combo was injected; the request is consumed either way, also if the menu was opened
+/// in the meantime.
+///
+/// # Example
+///
+/// ```
+/// use wups::config;
+///
+/// #[function_hook(module = VPAD, function = VPADRead)]
+/// fn my_VPADRead(
+///     chan: ::wut::sys::VPADChan::Type,
+///     buffers: *mut ::wut::sys::VPADStatus,
+///     count: u32,
+///     error: *mut ::wut::sys::VPADReadError::Type,
+/// ) -> i32 {
+///     use ::wut::sys::VPADButtons as B;
+///
+///     let read = unsafe { hooked(chan, buffers, count, error) };
+///     if read > 0 && chan == ::wut::sys::VPADChan::VPAD_CHAN_0 {
+///         // the newest sample is at index 0
+///         let status = unsafe { &mut *buffers };
+///         if status.trigger & B::VPAD_BUTTON_STICK_L != 0 {
+///             config::request_open_menu();
+///         }
+///         config::inject_open_request(status);
+///     }
+///     read
+/// }
+/// ```
+pub fn inject_open_request(status: &mut VPADStatus) -> bool {
+    if !REQUESTED.swap(false, Ordering::AcqRel) || is_menu_open() {
+        return false;
+    }
+
+    status.hold |= MENU_COMBO;
+    status.trigger |= MENU_COMBO;
+    status.release &= !MENU_COMBO;
+    true
+}
+
+/// Called by the open callbacks of the menu traits.
+pub(super) fn opened() {
+    OPEN.store(true, Ordering::Release);
+    REQUESTED.store(false, Ordering::Release);
+}
+
+/// Called by the close callbacks of the menu traits.
+pub(super) fn closed() {
+    OPEN.store(false, Ordering::Release);
+}
+
+/// Forget the state, see [reset_statics][crate::reset_statics].
+pub(super) fn reset() {
+    OPEN.store(false, Ordering::Release);
+    REQUESTED.store(false, Ordering::Release);
+}
@@ -0,0 +1,37 @@
+//! Values of a [SettingsMenu][super::SettingsMenu] handed to its items.
+//!
+//! While the items of a settings menu are attached, they take their current value from the
+//! settings instead of reading it from storage. The values are only set for the duration of the
+//! open callback.
+
+use crate::{
+    storage::{StorageCompatible, StorageError},
+    sync::Mutex,
+};
+use alloc::collections::BTreeMap;
+use wups_sys as sys;
+use wut::{string::String, vec::Vec};
+
+type Entries = BTreeMap<String, (sys::WUPSStorageItemTypes::Type, Vec<u8>)>;
+
+static VALUES: Mutex<Option<Entries>> = Mutex::new(None);
+
+/// Hand out `entries` (see [Storable::entries][crate::storage::Storable::entries]) until [clear].
+pub(super) fn set(entries: Vec<(String, sys::WUPSStorageItemTypes::Type, Vec<u8>)>) {
+    let entries = entries
+        .into_iter()
+        .map(|(key, item_type, bytes)| (key, (item_type, bytes)))
+        .collect();
+    *VALUES.lock().unwrap() = Some(entries);
+}
+
+pub(super) fn clear() {
+    *VALUES.lock().unwrap() = None;
+}
+
+/// Value of `id`, or `None` if there is none of type `T`.
+pub(super) fn get<T: StorageCompatible>(id: &str) -> Option<Result<T::T, StorageError>> {
+    let values = VALUES.lock().unwrap();
+    let (item_type, bytes) = values.as_ref()?.get(id)?;
+    (*item_type == T::ITEM_TYPE).then(|| T::decode(bytes))
+}
@@ -0,0 +1,66 @@
+//! Constructors for the option structs of the C config API.
+//!
+//! The options of `WUPSConfigAPI_*` are versioned unions (`...__bindgen_ty_1`) with a version tag
+//! next to them. These functions set the tag and the matching union field together, so no field
+//! is left uninitialized or read with the wrong version. They are the supported way to build the
+//! option structs when calling [sys][crate::config::raw::sys] functions directly, and are used by
+//! the items of this crate as well.
+//!
+//! The structs only borrow their strings as raw pointers: every `&CStr` passed in must outlive the
+//! call the options are used in. The API copies the strings, so they don't have to live longer.
+//!
+//! # Example
+//!
+//! ```
+//! use wups::config::raw;
+//!
+//! let name = c"Advanced";
+//! let mut handle = raw::WUPSConfigCategoryHandle::default();
+//! let status = unsafe {
+//!     raw::sys::WUPSConfigAPI_Category_CreateEx(raw::category_options_v1(name), &mut handle)
+//! };
+//! ```
+
+pub use wups_sys as sys;
+pub use wups_sys::{WUPSConfigCategoryHandle, WUPSConfigItemHandle};
+use wut::ffi::{CStr, c_void};
+
+/// Options of `WUPSConfigAPI_Init`.
+pub fn init_options_v1(name: &CStr) -> sys::WUPSConfigAPIOptionsV1 {
+    sys::WUPSConfigAPIOptionsV1 {
+        name: name.as_ptr(),
+    }
+}
+
+/// Options of `WUPSConfigAPI_Category_CreateEx` (version
+/// `WUPS_API_CATEGORY_OPTION_VERSION_V1`).
+pub fn category_options_v1(name: &CStr) -> sys::WUPSConfigAPICreateCategoryOptions {
+    sys::WUPSConfigAPICreateCategoryOptions {
+        version: sys::WUPS_API_CATEGORY_OPTION_VERSION_V1,
+        data: sys::WUPSConfigAPICreateCategoryOptions__bindgen_ty_1 {
+            v1: sys::WUPSConfigAPICreateCategoryOptionsV1 {
+                name: name.as_ptr(),
+            },
+        },
+    }
+}
+
+/// Options of `WUPSConfigAPI_Item_Create` (version `WUPS_API_ITEM_OPTION_VERSION_V2`).
+///
+/// `context` is passed to all `callbacks` unchanged; it is usually freed in `onDelete`.
+pub fn item_options_v2(
+    display_name: &CStr,
+    context: *mut c_void,
+    callbacks: sys::WUPSConfigAPIItemCallbacksV2,
+) -> sys::WUPSConfigAPICreateItemOptions {
+    sys::WUPSConfigAPICreateItemOptions {
+        version: sys::WUPS_API_ITEM_OPTION_VERSION_V2,
+        data: sys::WUPSConfigAPICreateItemOptions__bindgen_ty_1 {
+            v2: sys::WUPSConfigAPIItemOptionsV2 {
+                displayName: display_name.as_ptr(),
+                context,
+                callbacks,
+            },
+        },
+    }
+}
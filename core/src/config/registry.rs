@@ -0,0 +1,202 @@
+//! Rust callbacks for config items.
+//!
+//! The callbacks of the built-in C items only receive the item (and with it its identifier), but
+//! no user data. Items which need to run Rust code on changes register a [Callback] for their
+//! identifier, which the trampolines then [dispatch] to.
+//!
+//! Items are recreated every time the menu is opened, so the registry is [cleared][clear] when the
+//! menu is closed.
+
+use super::MenuError;
+use crate::sync::{Arc, Mutex, OnceLock};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, btree_map::Entry},
+};
+use wut::{
+    ffi::{CStr, CString},
+    string::String,
+};
+
+/// New value of the item which changed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Bool(bool),
+    I32(i32),
+    U32(u32),
+    F32(f32),
+    String(String),
+}
+
+pub(crate) type Callback = Box<dyn FnMut(Value) + Send>;
+
+/// Callbacks are shared, so they can run while the registry is unlocked.
+static REGISTRY: OnceLock<Mutex<BTreeMap<CString, Arc<Mutex<Callback>>>>> = OnceLock::new();
+
+fn registry() -> &'static Mutex<BTreeMap<CString, Arc<Mutex<Callback>>>> {
+    REGISTRY.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Register `callback` for the item `id`.
+///
+/// Fails with [DuplicateCallback][MenuError::DuplicateCallback] if `id` already has a callback, as
+/// replacing it would silently run the wrong item's code.
+pub(crate) fn register(id: CString, callback: Callback) -> Result<(), MenuError> {
+    match registry().lock().unwrap().entry(id) {
+        Entry::Occupied(entry) => Err(MenuError::DuplicateCallback(
+            entry.key().to_string_lossy().into_owned(),
+        )),
+        Entry::Vacant(entry) => {
+            entry.insert(Arc::new(Mutex::new(callback)));
+            Ok(())
+        }
+    }
+}
+
+/// Invoke the callback of the item `id`. Returns whether one was registered.
+///
+/// The registry isn't locked while the callback runs, so it may register callbacks itself, and
+/// [clear] doesn't wait for it. Concurrent changes of the same item run its callback one after
+/// another, so a callback must not change its own item.
+pub(crate) fn dispatch(id: &CStr, value: Value) -> bool {
+    let Some(callback) = registry().lock().unwrap().get(id).cloned() else {
+        return false;
+    };
+
+    (callback.lock().unwrap())(value);
+    true
+}
+
+/// Callback of an item's `on_change`, which receives the new value as `T`.
+pub(crate) struct OnChange<T>(Box<dyn FnMut(T) + Send>);
+
+impl<T: 'static> OnChange<T> {
+    pub(crate) fn new(callback: impl FnMut(T) + Send + 'static) -> Self {
+        Self(Box::new(callback))
+    }
+
+    /// [register] the callback for `id`, calling it with the values `extract` returns `Some` for.
+    pub(crate) fn register(
+        self,
+        id: &str,
+        extract: fn(Value) -> Option<T>,
+    ) -> Result<(), MenuError> {
+        let mut callback = self.0;
+        register(
+            CString::new(id)?,
+            Box::new(move |value| {
+                if let Some(value) = extract(value) {
+                    callback(value);
+                }
+            }),
+        )
+    }
+}
+
+impl<T> core::fmt::Debug for OnChange<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("OnChange")
+    }
+}
+
+/// Remove all callbacks.
+pub(crate) fn clear() {
+    if let Some(registry) = REGISTRY.get() {
+        registry.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::mock;
+    use alloc::vec::Vec;
+
+    fn id(id: &str) -> CString {
+        CString::new(id).unwrap()
+    }
+
+    #[test]
+    fn dispatches_to_the_item() {
+        let _lock = mock::lock();
+        let values = Arc::new(Mutex::new(Vec::new()));
+
+        let seen = values.clone();
+        let callback = Box::new(move |value| seen.lock().unwrap().push(value));
+        register(id("a"), callback).unwrap();
+
+        assert!(dispatch(&id("a"), Value::I32(1)));
+        assert!(!dispatch(&id("b"), Value::I32(2)));
+        assert!(dispatch(&id("a"), Value::Bool(true)));
+        assert_eq!(*values.lock().unwrap(), [Value::I32(1), Value::Bool(true)]);
+
+        assert!(matches!(
+            register(id("a"), Box::new(|_| {})),
+            Err(MenuError::DuplicateCallback(id)) if id == "a"
+        ));
+    }
+
+    #[test]
+    fn stays_registered_while_running() {
+        let _lock = mock::lock();
+        let nested = Arc::new(Mutex::new(None));
+
+        let result = nested.clone();
+        let callback = Box::new(move |_| {
+            // another item changes while this callback runs
+            *result.lock().unwrap() = Some(dispatch(&id("b"), Value::I32(0)));
+            let _ = register(id("c"), Box::new(|_| {}));
+        });
+        register(id("a"), callback).unwrap();
+        register(id("b"), Box::new(|_| {})).unwrap();
+
+        assert!(dispatch(&id("a"), Value::I32(1)));
+        assert_eq!(*nested.lock().unwrap(), Some(true));
+        assert!(dispatch(&id("a"), Value::I32(2)));
+        assert!(dispatch(&id("c"), Value::I32(3)));
+    }
+
+    #[test]
+    fn cleared_while_running() {
+        let _lock = mock::lock();
+        let calls = Arc::new(Mutex::new(0));
+
+        let count = calls.clone();
+        let callback = Box::new(move |_| {
+            *count.lock().unwrap() += 1;
+            // the menu closes while the callback runs
+            clear();
+        });
+        register(id("a"), callback).unwrap();
+
+        assert!(dispatch(&id("a"), Value::I32(1)));
+        assert!(!dispatch(&id("a"), Value::I32(2)));
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn concurrent_changes() {
+        extern crate std;
+
+        let _lock = mock::lock();
+        let calls = Arc::new(Mutex::new(0));
+
+        let count = calls.clone();
+        let callback = Box::new(move |_| *count.lock().unwrap() += 1);
+        register(id("a"), callback).unwrap();
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    (0..100)
+                        .filter(|&i| dispatch(&id("a"), Value::I32(i)))
+                        .count()
+                })
+            })
+            .collect();
+        let dispatched: usize = threads.into_iter().map(|t| t.join().unwrap()).sum();
+
+        assert_eq!(dispatched, 800);
+        assert_eq!(*calls.lock().unwrap(), 800);
+    }
+}
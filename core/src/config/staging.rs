@@ -0,0 +1,206 @@
+//! Staged writes of config items.
+//!
+//! While staging is active, items don't write into storage when the user changes them. The values
+//! are collected instead and written together when the menu is closed (see
+//! [ConfigMenu::staged_transactional][super::ConfigMenu::staged_transactional]).
+//!
+//! Independently of that, items which report many intermediate values (ranges while the user
+//! scrubs) [coalesce][write_coalesced] their writes, so only the final value is written.
+
+use super::registry::Value;
+use crate::storage::{self, StorageCompatible, StorageError};
+use crate::sync::Mutex;
+use alloc::collections::{BTreeMap, BTreeSet};
+use wut::{string::String, vec::Vec};
+
+/// `None` while staging is inactive.
+static STAGED: Mutex<Option<BTreeMap<String, Value>>> = Mutex::new(None);
+
+/// Latest value of every item which coalesces its writes.
+static COALESCED: Mutex<BTreeMap<String, Value>> = Mutex::new(BTreeMap::new());
+
+/// Start collecting writes, discarding previously staged ones.
+pub(crate) fn begin() {
+    *STAGED.lock().unwrap() = Some(BTreeMap::new());
+}
+
+/// Whether committing would change storage, i.e. a staged or coalesced value differs from the
+/// stored one. Values which were changed back don't count.
+pub(crate) fn has_changes() -> bool {
+    let mut pending = STAGED.lock().unwrap().clone().unwrap_or_default();
+    pending.extend(COALESCED.lock().unwrap().clone());
+    pending.iter().any(|(id, value)| differs(id, value))
+}
+
+/// Stage `value` for `id`, or write it into storage right away if staging is inactive.
+pub(crate) fn write(id: &str, value: Value) -> Result<(), StorageError> {
+    if let Some(staged) = STAGED.lock().unwrap().as_mut() {
+        staged.insert(id.into(), value);
+        return Ok(());
+    }
+    store(id, &value)
+}
+
+/// Remember `value` as the latest value of `id`, replacing a previous one. It is only
+/// [written][write] by [commit], so a burst of changes results in a single write.
+pub(crate) fn write_coalesced(id: &str, value: Value) {
+    COALESCED.lock().unwrap().insert(id.into(), value);
+}
+
+/// Write the coalesced values, then stop staging and write all staged values in one
+/// [transaction][storage::transaction].
+///
+/// While staging, the coalesced values are written in the same transaction as the staged ones. If
+/// it fails, none of them are applied and they stay staged, so staging is still active. Otherwise,
+/// values which couldn't be written stay coalesced.
+pub(crate) fn commit() -> Result<(), StorageError> {
+    let coalesced = core::mem::take(&mut *COALESCED.lock().unwrap());
+    let Some(mut staged) = STAGED.lock().unwrap().take() else {
+        return write_each(coalesced);
+    };
+
+    staged.extend(coalesced);
+    let result = storage::transaction(|| {
+        for (id, value) in &staged {
+            store(id, value)?;
+        }
+        Ok(())
+    });
+    if result.is_err() {
+        *STAGED.lock().unwrap() = Some(staged);
+    }
+    result
+}
+
+/// [commit], dropping the values and stopping staging if it fails. For when the menu closes, as
+/// nothing could commit them later.
+pub(crate) fn finish() -> Result<(), StorageError> {
+    commit().inspect_err(|_| reset())
+}
+
+/// Store `values` one by one, keeping the unwritten ones coalesced if a write fails.
+fn write_each(mut values: BTreeMap<String, Value>) -> Result<(), StorageError> {
+    while let Some((id, value)) = values.pop_first() {
+        if let Err(e) = store(&id, &value) {
+            values.insert(id, value);
+            let mut coalesced = COALESCED.lock().unwrap();
+            for (id, value) in values {
+                // values coalesced in the meantime are newer
+                coalesced.entry(id).or_insert(value);
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Ids of all values which would be written by [commit].
+pub(crate) fn pending_ids() -> Vec<String> {
+    let mut ids: BTreeSet<String> = COALESCED.lock().unwrap().keys().cloned().collect();
+    if let Some(staged) = STAGED.lock().unwrap().as_ref() {
+        ids.extend(staged.keys().cloned());
+    }
+    ids.into_iter().collect()
+}
+
+/// Drop all staged and coalesced values without writing them. Staging stays active.
+pub(crate) fn discard() {
+    if let Some(staged) = STAGED.lock().unwrap().as_mut() {
+        staged.clear();
+    }
+    COALESCED.lock().unwrap().clear();
+}
+
+/// Whether `value` isn't stored for `id`, comparing the [encoded][StorageCompatible::encode]
+/// bytes.
+fn differs(id: &str, value: &Value) -> bool {
+    fn differs<T: StorageCompatible>(id: &str, value: &T::T) -> bool {
+        storage::load::<T>(id).map_or(true, |stored| T::encode(&stored) != T::encode(value))
+    }
+
+    match value {
+        Value::Bool(v) => differs::<bool>(id, v),
+        Value::I32(v) => differs::<i32>(id, v),
+        Value::U32(v) => differs::<u32>(id, v),
+        Value::F32(v) => differs::<f32>(id, v),
+        Value::String(v) => differs::<String>(id, v),
+    }
+}
+
+fn store(id: &str, value: &Value) -> Result<(), StorageError> {
+    match value {
+        Value::Bool(v) => storage::store::<bool>(id, *v),
+        Value::I32(v) => storage::store::<i32>(id, *v),
+        Value::U32(v) => storage::store::<u32>(id, *v),
+        Value::F32(v) => storage::store::<f32>(id, *v),
+        Value::String(v) => storage::store::<String>(id, v.clone()),
+    }
+}
+
+/// Discard staged values, see [reset_statics][crate::reset_statics].
+pub(crate) fn reset() {
+    *STAGED.lock().unwrap() = None;
+    COALESCED.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::mock;
+
+    #[test]
+    fn changes_compare_with_storage() {
+        let _lock = mock::lock();
+        storage::store::<i32>("volume", 50).unwrap();
+        storage::store::<String>("name", "Mario".into()).unwrap();
+        storage::save(false).unwrap();
+
+        begin();
+        write("volume", Value::I32(50)).unwrap();
+        write_coalesced("name", Value::String("Mario".into()));
+        assert!(!has_changes());
+
+        write("volume", Value::I32(80)).unwrap();
+        assert!(has_changes());
+        write("volume", Value::I32(50)).unwrap();
+        assert!(!has_changes());
+
+        write_coalesced("new", Value::Bool(false));
+        assert!(has_changes());
+    }
+
+    #[test]
+    fn coalesced_writes_once() {
+        let _lock = mock::lock();
+
+        for value in [0, 10, 40, 35, 30] {
+            write_coalesced("volume", Value::I32(value));
+        }
+        assert_eq!(mock::writes("volume"), 0);
+        assert_eq!(pending_ids(), ["volume"]);
+
+        finish().unwrap();
+        assert_eq!(mock::writes("volume"), 1);
+        assert_eq!(storage::load::<i32>("volume"), Ok(30));
+        assert!(pending_ids().is_empty());
+    }
+
+    #[test]
+    fn staged_and_coalesced_write_once() {
+        let _lock = mock::lock();
+
+        begin();
+        write("enabled", Value::Bool(true)).unwrap();
+        write("enabled", Value::Bool(false)).unwrap();
+        for value in [1, 2, 3] {
+            write_coalesced("volume", Value::I32(value));
+        }
+        assert_eq!(mock::writes("enabled"), 0);
+
+        finish().unwrap();
+        assert_eq!(mock::writes("enabled"), 1);
+        assert_eq!(mock::writes("volume"), 1);
+        assert_eq!(storage::load::<bool>("enabled"), Ok(false));
+        assert_eq!(storage::load::<i32>("volume"), Ok(3));
+    }
+}
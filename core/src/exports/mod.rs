@@ -0,0 +1,115 @@
+//! Module Exports
+//!
+//! WUPS has no mechanism for plugins to export functions to other plugins: plugins are linked
+//! separately and the loader only processes their hooks (`.wups.hooks`) and function replacements
+//! (`.wups.load`). A `#[plugin_export]` attribute therefore can't exist.
+//!
+//! What does exist are exports of Aroma **modules** (e.g. `homebrew_notifications`), which are
+//! loaded before any plugin. Their functions can be resolved at runtime via `OSDynLoad`, which is
+//! what this module wraps.
+//!
+//! # Resolution
+//!
+//! Exports are looked up when [import] (or [Module::function]) is called, not at link time. Modules
+//! are loaded before plugins are initialized, so this works from
+//! [on_initialize][crate::on_initialize] onward. A missing module or export is reported as an error
+//! instead of failing to load the plugin, so optional integrations can be skipped gracefully.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use wups::exports;
+//!
+//! type AddNotification = unsafe extern "C" fn(text: *const core::ffi::c_char) -> i32;
+//!
+//! let notify: AddNotification =
+//!     unsafe { exports::import("homebrew_notifications", "NotificationModule_AddInfoNotification") }
+//!         .unwrap();
+//! unsafe { notify(c"Hello".as_ptr()) };
+//! ```
+
+use thiserror::Error;
+use wut::{
+    ffi::{CString, NulError, c_void},
+    sys,
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ExportError {
+    #[error("Module is not loaded (status {0})")]
+    ModuleNotFound(sys::OSDynLoad_Error::Type),
+    #[error("Module has no such export (status {0})")]
+    ExportNotFound(sys::OSDynLoad_Error::Type),
+    #[error("Internal 0-bytes")]
+    InternalNullByte(#[from] NulError),
+}
+
+/// Handle to a loaded module. The module is released when this is dropped.
+pub struct Module(sys::OSDynLoad_Module);
+
+impl Module {
+    /// Acquire the module `name`, e.g. `"homebrew_notifications"`.
+    pub fn acquire(name: &str) -> Result<Self, ExportError> {
+        let name = CString::new(name)?;
+        let mut module = core::ptr::null_mut();
+
+        let status = unsafe { sys::OSDynLoad_Acquire(name.as_ptr(), &mut module) };
+        if status != sys::OSDynLoad_Error::OS_DYNLOAD_OK {
+            return Err(ExportError::ModuleNotFound(status));
+        }
+
+        Ok(Self(module))
+    }
+
+    /// Address of the exported function `name`.
+    pub fn function(&self, name: &str) -> Result<*const c_void, ExportError> {
+        self.find(sys::OSDynLoad_ExportType::OS_DYNLOAD_EXPORT_FUNC, name)
+    }
+
+    /// Address of the exported data `name`.
+    pub fn data(&self, name: &str) -> Result<*mut c_void, ExportError> {
+        self.find(sys::OSDynLoad_ExportType::OS_DYNLOAD_EXPORT_DATA, name)
+            .map(|address| address as *mut c_void)
+    }
+
+    fn find(
+        &self,
+        export_type: sys::OSDynLoad_ExportType::Type,
+        name: &str,
+    ) -> Result<*const c_void, ExportError> {
+        let name = CString::new(name)?;
+        let mut address = core::ptr::null_mut();
+
+        let status =
+            unsafe { sys::OSDynLoad_FindExport(self.0, export_type, name.as_ptr(), &mut address) };
+        if status != sys::OSDynLoad_Error::OS_DYNLOAD_OK || address.is_null() {
+            return Err(ExportError::ExportNotFound(status));
+        }
+
+        Ok(address)
+    }
+}
+
+impl Drop for Module {
+    fn drop(&mut self) {
+        unsafe { sys::OSDynLoad_Release(self.0) };
+    }
+}
+
+/// Resolve the exported function `function` of the module `module` as a function pointer `F`.
+///
+/// The module stays loaded after its handle is released, so the returned pointer remains valid.
+///
+/// # Safety
+///
+/// `F` must be an `unsafe extern "C" fn` type matching the signature of the export.
+pub unsafe fn import<F: Copy>(module: &str, function: &str) -> Result<F, ExportError> {
+    assert_eq!(
+        core::mem::size_of::<F>(),
+        core::mem::size_of::<*const c_void>(),
+        "F must be a function pointer"
+    );
+
+    let address = Module::acquire(module)?.function(function)?;
+    Ok(unsafe { core::mem::transmute_copy::<*const c_void, F>(&address) })
+}
@@ -0,0 +1,181 @@
+//! Text formatting without `format!`
+//!
+//! Small helpers for the text of config items (e.g. [Label][crate::config::Label] or
+//! [Gauge][crate::config::Gauge]), which write into a fixed-size [Buffer] on the stack.
+//!
+//! # Tradeoffs
+//!
+//! Compared to `format!`, the helpers don't allocate and integers are written by hand instead of
+//! through `core::fmt`, which keeps the formatting machinery out of size-sensitive plugins that
+//! don't use it elsewhere. In exchange they only cover integers and plain strings, and text which
+//! doesn't fit into the buffer is **truncated** (at a char boundary). Items still copy their text
+//! into a C string when they are attached, so this saves code size and temporary allocations, not
+//! every allocation. For anything else, `format!` remains the simpler choice.
+//!
+//! # Example
+//!
+//! ```
+//! use wups::fmt;
+//!
+//! let fps = 30;
+//! root.add(config::Label::new(&fmt::with_unit(fps, " FPS")))?;
+//!
+//! let mut text = fmt::Buffer::<32>::new();
+//! text.push_str("FPS cap: ");
+//! text.push_int(fps);
+//! root.add(config::Label::new(&text))?;
+//! ```
+
+/// String of at most `N` bytes on the stack.
+///
+/// Derefs to `str`. Also implements [core::fmt::Write], so `write!` can be used where `core::fmt`
+/// is linked anyway.
+#[derive(Clone, Copy)]
+pub struct Buffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+    /// Set once text was cut off, a buffer which is filled exactly isn't truncated.
+    truncated: bool,
+}
+
+impl<const N: usize> Buffer<N> {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only whole chars are ever copied in
+        unsafe { core::str::from_utf8_unchecked(&self.bytes[..self.len]) }
+    }
+
+    /// Whether text was cut off because it didn't fit.
+    pub fn is_full(&self) -> bool {
+        self.truncated
+    }
+
+    /// Append `s`, truncated at a char boundary if it doesn't fit.
+    pub fn push_str(&mut self, s: &str) {
+        let mut len = s.len().min(N - self.len);
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.bytes[self.len..self.len + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.len += len;
+        self.truncated |= len < s.len();
+    }
+
+    /// Append `value` in decimal.
+    pub fn push_int(&mut self, value: impl Into<i64>) {
+        let value = value.into();
+        if value < 0 {
+            self.push_str("-");
+        }
+        self.push_uint(value.unsigned_abs());
+    }
+
+    /// Append `value` in decimal.
+    pub fn push_uint(&mut self, value: impl Into<u64>) {
+        let mut value = value.into();
+        // u64::MAX has 20 digits
+        let mut digits = [0u8; 20];
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        // digits are ASCII
+        self.push_str(unsafe { core::str::from_utf8_unchecked(&digits[start..]) });
+    }
+}
+
+impl<const N: usize> Default for Buffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::ops::Deref for Buffer<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> core::fmt::Write for Buffer<N> {
+    /// Never fails, text which doesn't fit is truncated.
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for Buffer<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// `value` in decimal, e.g. `"-42"`.
+pub fn int(value: impl Into<i64>) -> Buffer<20> {
+    let mut buffer = Buffer::new();
+    buffer.push_int(value);
+    buffer
+}
+
+/// `value` in decimal followed by `unit`, e.g. `with_unit(30, " FPS")` is `"30 FPS"`.
+///
+/// The unit is truncated if the whole text exceeds 32 bytes.
+pub fn with_unit(value: impl Into<i64>, unit: &str) -> Buffer<32> {
+    let mut buffer = Buffer::new();
+    buffer.push_int(value);
+    buffer.push_str(unit);
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_fit_isnt_full() {
+        let mut buffer = Buffer::<4>::new();
+        buffer.push_str("ab");
+        buffer.push_str("cd");
+        assert_eq!(&*buffer, "abcd");
+        assert!(!buffer.is_full());
+
+        buffer.push_str("");
+        assert!(!buffer.is_full());
+        buffer.push_str("e");
+        assert_eq!(&*buffer, "abcd");
+        assert!(buffer.is_full());
+    }
+
+    #[test]
+    fn truncates_at_char_boundary() {
+        let mut buffer = Buffer::<4>::new();
+        buffer.push_str("aä€");
+        assert_eq!(&*buffer, "aä");
+        assert!(buffer.is_full());
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(&*int(i64::MIN), "-9223372036854775808");
+        assert_eq!(&*int(0), "0");
+        assert_eq!(&*with_unit(30, " FPS"), "30 FPS");
+
+        let long = with_unit(1, &"x".repeat(40));
+        assert_eq!(long.len(), 32);
+        assert!(long.is_full());
+    }
+}
@@ -0,0 +1,132 @@
+//! File Redirection
+//!
+//! Rules which replace the paths of files opened by the running application, e.g. to load
+//! modified game files from the SD card. Rules are registered with [redirect] and applied by an
+//! `FSOpenFile` hook, which the plugin emits once with [redirect_hook!](crate::fs::redirect_hook).
+//!
+//! # Path matching
+//!
+//! A rule `from → to` matches a path if the path is `from` itself or continues with a `/` after
+//! it, so `/vol/content/a` matches `/vol/content/a/b.bin`, but not `/vol/content/ab.bin`. The
+//! matched part is replaced by `to` and the rest is kept. Paths are compared byte by byte (case
+//! sensitive), a trailing `/` of `from` is ignored. If several rules match, the one with the
+//! longest `from` wins. Paths which match no rule are opened unchanged.
+//!
+//! # Example
+//!
+//! ```
+//! use wups::fs;
+//!
+//! fs::redirect_hook!();
+//!
+//! #[on_initialize]
+//! fn init() {
+//!     fs::redirect("/vol/content/audio", "/vol/external01/wiiu/mymod/audio");
+//! }
+//! ```
+//!
+//! # Target process
+//!
+//! The hook is installed for games and the Wii U Menu, the default of `#[function_hook]`. Only
+//! `FSOpenFile` is hooked: other ways of opening files (`FSOpenFileEx`, `FSAOpenFile`, directory
+//! functions) and files opened before the rule is registered aren't affected.
+
+use crate::sync::Mutex;
+use alloc::collections::BTreeMap;
+use wut::{
+    ffi::{CStr, CString},
+    vec::Vec,
+};
+
+/// Rules by their `from` path, without trailing `/`.
+static RULES: Mutex<BTreeMap<Vec<u8>, Vec<u8>>> = Mutex::new(BTreeMap::new());
+
+/// Open files below `from` from `to` instead, see [path matching](self#path-matching).
+///
+/// Registering the same `from` again replaces its rule.
+pub fn redirect(from: &str, to: &str) {
+    RULES
+        .lock()
+        .unwrap()
+        .insert(trim(from.as_bytes()).to_vec(), trim(to.as_bytes()).to_vec());
+}
+
+/// Remove the rule for `from`. Returns whether there was one.
+pub fn remove_redirect(from: &str) -> bool {
+    RULES
+        .lock()
+        .unwrap()
+        .remove(trim(from.as_bytes()))
+        .is_some()
+}
+
+/// Remove all rules.
+pub fn clear_redirects() {
+    RULES.lock().unwrap().clear();
+}
+
+/// Path `path` is redirected to, or `None` if no rule matches.
+///
+/// Used by the hook of [redirect_hook!](crate::fs::redirect_hook); plugins hooking other open
+/// functions themselves can use it to apply the same rules.
+pub fn resolve(path: &CStr) -> Option<CString> {
+    let path = path.to_bytes();
+    let rules = RULES.lock().unwrap();
+
+    let (from, to) = rules
+        .iter()
+        .filter(|(from, _)| {
+            path.strip_prefix(from.as_slice())
+                .is_some_and(|rest| rest.is_empty() || rest[0] == b'/')
+        })
+        .max_by_key(|(from, _)| from.len())?;
+
+    let mut redirected = to.clone();
+    redirected.extend_from_slice(&path[from.len()..]);
+    // `to` comes from a `&str`, the rest from a C string
+    CString::new(redirected).ok()
+}
+
+fn trim(path: &[u8]) -> &[u8] {
+    path.strip_suffix(b"/").unwrap_or(path)
+}
+
+/// Forget all rules, see [reset_statics][crate::reset_statics].
+pub(crate) fn reset() {
+    clear_redirects();
+}
+
+/// Emit the `FSOpenFile` hook which applies the rules registered with [redirect].
+///
+/// Call it once at the top level of the plugin. The plugin can't hook `FSOpenFile` itself in
+/// addition to this; use [resolve] in its own hook instead.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fs_redirect_hook {
+    () => {
+        #[::wups::macros::function_hook(module = COREINIT, function = FSOpenFile)]
+        fn wups_fs_redirect_FSOpenFile(
+            client: *mut ::wut::sys::FSClient,
+            block: *mut ::wut::sys::FSCmdBlock,
+            path: *const ::core::ffi::c_char,
+            mode: *const ::core::ffi::c_char,
+            handle: *mut ::wut::sys::FSFileHandle,
+            error_mask: ::wut::sys::FSErrorFlag::Type,
+        ) -> ::wut::sys::FSStatus::Type {
+            let redirected = if path.is_null() {
+                None
+            } else {
+                ::wups::fs::resolve(unsafe { ::core::ffi::CStr::from_ptr(path) })
+            };
+
+            match redirected {
+                Some(path) => unsafe {
+                    hooked(client, block, path.as_ptr(), mode, handle, error_mask)
+                },
+                None => unsafe { hooked(client, block, path, mode, handle, error_mask) },
+            }
+        }
+    };
+}
+
+pub use crate::__fs_redirect_hook as redirect_hook;
@@ -0,0 +1,97 @@
+//! Controller Input
+//!
+//! Cache of the most recent controller reads. Plugins which need the input in several places
+//! (hooks, threads, the render loop, ...) should not call `VPADRead`/`KPADReadEx` themselves, as
+//! every call consumes samples which are then missing for the game. Instead, hook the read once
+//! and [record][record_vpad] the result; all other consumers use [latest_vpad]/[latest_kpad],
+//! which share that single physical read.
+//!
+//! # Example
+//!
+//! ```
+//! use wups::input;
+//!
+//! #[function_hook(module = VPAD, function = VPADRead)]
+//! fn my_VPADRead(
+//!     chan: ::wut::sys::VPADChan::Type,
+//!     buffers: *mut ::wut::sys::VPADStatus,
+//!     count: u32,
+//!     error: *mut ::wut::sys::VPADReadError::Type,
+//! ) -> i32 {
+//!     let read = unsafe { hooked(chan, buffers, count, error) };
+//!     if read > 0 && chan == ::wut::sys::VPADChan::VPAD_CHAN_0 {
+//!         // the newest sample is at index 0
+//!         input::record_vpad(unsafe { &*buffers });
+//!     }
+//!     read
+//! }
+//!
+//! #[function_hook(module = PADSCORE, function = KPADReadEx)]
+//! fn my_KPADReadEx(
+//!     chan: ::wut::sys::KPADChan::Type,
+//!     data: *mut ::wut::sys::KPADStatus,
+//!     size: u32,
+//!     error: *mut ::wut::sys::KPADError::Type,
+//! ) -> i32 {
+//!     let read = unsafe { hooked(chan, data, size, error) };
+//!     if read > 0 {
+//!         input::record_kpad(chan, unsafe { &*data });
+//!     }
+//!     read
+//! }
+//!
+//! // anywhere else in the plugin
+//! if let Some(status) = input::latest_vpad() {
+//!     // ...
+//! }
+//! ```
+//!
+//! The cache only contains data while the game actually reads the controller, and it is not
+//! cleared between applications.
+
+use crate::sync::Mutex;
+use wut::sys::{KPADChan, KPADStatus, VPADStatus};
+
+/// Number of Wii Remote channels which are cached.
+pub const KPAD_CHANNELS: usize = 4;
+
+static VPAD: Mutex<Option<VPADStatus>> = Mutex::new(None);
+static KPAD: Mutex<[Option<KPADStatus>; KPAD_CHANNELS]> = Mutex::new([None; KPAD_CHANNELS]);
+
+// region: VPAD
+
+/// Store the newest Gamepad sample. Call this from a `VPADRead` hook.
+pub fn record_vpad(status: &VPADStatus) {
+    *VPAD.lock().unwrap() = Some(*status);
+}
+
+/// Most recent Gamepad sample recorded via [record_vpad].
+pub fn latest_vpad() -> Option<VPADStatus> {
+    *VPAD.lock().unwrap()
+}
+
+// endregion
+
+// region: KPAD
+
+/// Store the newest Wii Remote sample of `chan`. Call this from a `KPADRead`/`KPADReadEx` hook.
+///
+/// Channels above [KPAD_CHANNELS] are ignored.
+pub fn record_kpad(chan: KPADChan::Type, status: &KPADStatus) {
+    if let Some(slot) = KPAD.lock().unwrap().get_mut(chan as usize) {
+        *slot = Some(*status);
+    }
+}
+
+/// Most recent Wii Remote sample of `chan` recorded via [record_kpad].
+pub fn latest_kpad(chan: KPADChan::Type) -> Option<KPADStatus> {
+    KPAD.lock().unwrap().get(chan as usize).copied().flatten()
+}
+
+// endregion
+
+/// Forget all recorded samples, see [reset_statics][crate::reset_statics].
+pub(crate) fn reset_statics() {
+    *VPAD.lock().unwrap() = None;
+    *KPAD.lock().unwrap() = [None; KPAD_CHANNELS];
+}
@@ -1,6 +1,129 @@
 #![no_std]
 
+extern crate alloc;
 extern crate wut;
 
+#[cfg(feature = "config")]
 pub mod config;
+pub mod exports;
+pub mod fmt;
+pub mod fs;
+pub mod input;
+pub mod net;
+pub mod panic;
 pub mod storage;
+pub mod sync;
+pub mod timing;
+
+/// Reset all global state of this crate to its initial values.
+///
+/// Called by the `FINI_WRAPPER` hook emitted by `WUPS_PLUGIN_NAME!` when the plugin is unloaded
+/// (after a pending [save request][storage::flush_save_request] was saved), so a plugin which is
+/// loaded again doesn't see state of the previous load. Reset are:
+///
+/// - storage: the [initialized][storage::is_initialized] flag, retries,
+///   [modification tracking][storage::set_track_modified], the
+///   [save debounce][storage::set_save_debounce] and pending save requests, the
+///   [maximum item size][storage::set_max_item_size], the
+///   [read cache][storage::enable_cache], [registered defaults][storage::set_default], the
+///   [audit log][storage] path, protected checksum groups and the cached key index
+/// - config: item callbacks, staged values, snapshots of
+///   [Apply/Revert][config::ConfigMenu::with_apply_revert], the
+///   [label provider][config::set_label_provider] and the instance of a
+///   [StatefulConfigMenu][config::StatefulConfigMenu]
+/// - fs: [redirect rules][fs::redirect]
+/// - input: recorded samples
+///
+/// Values which can't change between loads stay cached (the config API version and the system
+/// language). Statics generated in the plugin itself (e.g. by `#[function_hook]`) aren't reset.
+pub fn reset_statics() {
+    storage::reset_statics();
+    #[cfg(feature = "config")]
+    config::reset_statics();
+    fs::reset();
+    input::reset_statics();
+}
+
+/// Id passed to `wut_get_thread_specific` by [verify_init_token].
+pub const INIT_TOKEN_ID: i32 = 0x13371337;
+
+/// Value `wut_get_thread_specific` returns for [INIT_TOKEN_ID] if libwups' version of the function
+/// is used, see [verify_init_token].
+pub const INIT_TOKEN: u32 = 0x42424242;
+
+/// Whether `wut_get_thread_specific(INIT_TOKEN_ID)` returns [INIT_TOKEN].
+///
+/// libwups and libwut both define `wut_get_thread_specific`, but only the libwups version answers
+/// [INIT_TOKEN_ID]. It is used if libwups is linked first, so this is the check behind
+/// [check_linking_order] and the `INIT_WRAPPER` hook emitted by `WUPS_PLUGIN_NAME!`.
+pub fn verify_init_token() -> bool {
+    unsafe { wups_sys::wut_get_thread_specific(INIT_TOKEN_ID) == INIT_TOKEN }
+}
+
+/// Whether the plugin was linked in the order the plugin loader requires (`-lwups -lwut`).
+///
+/// The `INIT_WRAPPER` hook emitted by `WUPS_PLUGIN_NAME!` runs the same check before anything
+/// else and halts the console with `OSFatal` if it fails, so a misconfigured plugin never runs.
+/// This is a non-fatal version of it for diagnostics, e.g. to log the problem from a test build.
+/// See [verify_linking_order] for a descriptive error.
+pub fn check_linking_order() -> bool {
+    verify_init_token()
+}
+
+/// [check_linking_order] with an error describing how to fix the problem.
+///
+/// # Example
+///
+/// ```
+/// #[on_initialize]
+/// fn init() {
+///     if let Err(e) = wups::verify_linking_order() {
+///         wut::println!("{e}");
+///     }
+/// }
+/// ```
+pub fn verify_linking_order() -> Result<(), LinkingOrderError> {
+    if check_linking_order() {
+        Ok(())
+    } else {
+        Err(LinkingOrderError)
+    }
+}
+
+/// The plugin was linked in the wrong order, see [verify_linking_order].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "wut_get_thread_specific returned an unexpected value: link wups before wut (\"-lwups -lwut\")"
+)]
+pub struct LinkingOrderError;
+
+/// `INIT_CONFIG` hook emitted by `WUPS_PLUGIN_NAME!`.
+#[cfg(feature = "config")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __init_config {
+    () => {
+        unsafe extern "C" {
+            fn WUPSConfigAPI_InitLibrary_Internal(
+                args: ::wups::sys::wups_loader_init_config_args_t,
+            ) -> ::wups::sys::WUPSConfigAPIStatus::Type;
+        }
+
+        #[unsafe(no_mangle)]
+        unsafe extern "C" fn wups_init_config_functions(
+            args: ::wups::sys::wups_loader_init_config_args_t,
+        ) {
+            unsafe { WUPSConfigAPI_InitLibrary_Internal(args) };
+        }
+
+        ::wups::macros::wups_hook_ex!("INIT_CONFIG", wups_init_config_functions);
+    };
+}
+
+/// Without the `config` feature, the config API isn't initialized (and not linked).
+#[cfg(not(feature = "config"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __init_config {
+    () => {};
+}
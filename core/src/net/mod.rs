@@ -0,0 +1,285 @@
+//! Network Addresses
+//!
+//! Minimal replacements for `std::net` address types, which are not available in `no_std`. They
+//! implement [StorageCompatible][crate::storage::StorageCompatible] so server addresses can be
+//! persisted directly.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use wups::net::{Ipv4Addr, SocketAddrV4};
+//! use wups::storage::{load, store};
+//!
+//! let addr = SocketAddrV4::new(Ipv4Addr::new(192, 168, 1, 1), 8080);
+//! store::<SocketAddrV4>("server", addr).unwrap();
+//! assert_eq!(load::<SocketAddrV4>("server").unwrap(), addr);
+//! ```
+//!
+//! IPv6 addresses are only stored and displayed, as [Ipv6Bytes], without parsing.
+
+use crate::storage::{StorageCompatible, StorageError};
+use core::fmt;
+use wups_sys as sys;
+use wut::vec::Vec;
+
+// region: Ipv4Addr
+
+/// IPv4 address. Stored as a `u32` in network byte order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ipv4Addr([u8; 4]);
+
+impl Ipv4Addr {
+    pub const UNSPECIFIED: Self = Self([0, 0, 0, 0]);
+    pub const LOCALHOST: Self = Self([127, 0, 0, 1]);
+
+    pub const fn new(a: u8, b: u8, c: u8, d: u8) -> Self {
+        Self([a, b, c, d])
+    }
+
+    pub const fn octets(&self) -> [u8; 4] {
+        self.0
+    }
+
+    pub const fn to_bits(&self) -> u32 {
+        u32::from_be_bytes(self.0)
+    }
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits.to_be_bytes())
+    }
+}
+
+impl From<[u8; 4]> for Ipv4Addr {
+    fn from(value: [u8; 4]) -> Self {
+        Self(value)
+    }
+}
+
+impl fmt::Display for Ipv4Addr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d] = self.0;
+        write!(f, "{a}.{b}.{c}.{d}")
+    }
+}
+
+impl StorageCompatible for Ipv4Addr {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_U32;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        Ok(Self::from_bits(u32::load(name)?))
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        u32::store(name, value.to_bits())
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        u32::encode(&value.to_bits())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        Ok(Self::from_bits(u32::decode(bytes)?))
+    }
+}
+
+// endregion
+
+// region: SocketAddrV4
+
+/// IPv4 address with port. Stored as 6 bytes (address, then port; both big-endian) in a binary
+/// item.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SocketAddrV4 {
+    ip: Ipv4Addr,
+    port: u16,
+}
+
+impl SocketAddrV4 {
+    pub const fn new(ip: Ipv4Addr, port: u16) -> Self {
+        Self { ip, port }
+    }
+
+    pub const fn ip(&self) -> &Ipv4Addr {
+        &self.ip
+    }
+
+    pub const fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn set_ip(&mut self, ip: Ipv4Addr) {
+        self.ip = ip;
+    }
+
+    pub fn set_port(&mut self, port: u16) {
+        self.port = port;
+    }
+}
+
+impl fmt::Display for SocketAddrV4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.ip, self.port)
+    }
+}
+
+impl StorageCompatible for SocketAddrV4 {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        Self::decode(&Vec::<u8>::load(name)?)
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        Vec::<u8>::store(name, Self::encode(&value))
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(6);
+        bytes.extend_from_slice(&value.ip.octets());
+        bytes.extend_from_slice(&value.port.to_be_bytes());
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        let [a, b, c, d, p0, p1]: [u8; 6] = bytes
+            .try_into()
+            .map_err(|_| StorageError::UnexpectedDataType)?;
+        Ok(Self::new(Ipv4Addr::new(a, b, c, d), u16::from_be_bytes([p0, p1])))
+    }
+}
+
+// endregion
+
+// region: Ipv6Bytes
+
+/// 16 bytes of an IPv6 address, in network byte order. Stored as the 16 bytes in a binary item.
+///
+/// Unlike `std::net::Ipv6Addr`, there is no parsing or classification (loopback, multicast, ...);
+/// the type only carries the bytes between the network and storage with a fixed byte order.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::net::Ipv6Bytes;
+/// use wups::storage::{load, store};
+///
+/// // 2001:db8::1
+/// let addr = Ipv6Bytes::from_segments([0x2001, 0xdb8, 0, 0, 0, 0, 0, 1]);
+/// assert_eq!(addr.to_bits(), 0x2001_0db8_0000_0000_0000_0000_0000_0001);
+/// assert_eq!(addr.octets()[..4], [0x20, 0x01, 0x0d, 0xb8]);
+/// assert_eq!(addr.to_string(), "2001:db8:0:0:0:0:0:1");
+///
+/// store::<Ipv6Bytes>("server6", addr).unwrap();
+/// assert_eq!(load::<Ipv6Bytes>("server6").unwrap(), addr);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ipv6Bytes([u8; 16]);
+
+impl Ipv6Bytes {
+    pub const UNSPECIFIED: Self = Self([0; 16]);
+    pub const LOCALHOST: Self = Self::from_bits(1);
+
+    /// Address from its bytes in network (big-endian) order.
+    pub const fn new(octets: [u8; 16]) -> Self {
+        Self(octets)
+    }
+
+    /// Bytes in network (big-endian) order.
+    pub const fn octets(&self) -> [u8; 16] {
+        self.0
+    }
+
+    /// Address from bytes in little-endian order, i.e. the reverse of [octets][Self::octets].
+    pub const fn from_le_bytes(bytes: [u8; 16]) -> Self {
+        Self::from_bits(u128::from_le_bytes(bytes))
+    }
+
+    /// Bytes in little-endian order, i.e. the reverse of [octets][Self::octets].
+    pub const fn to_le_bytes(&self) -> [u8; 16] {
+        self.to_bits().to_le_bytes()
+    }
+
+    /// Address from its eight 16-bit segments, e.g. `[0x2001, 0xdb8, 0, 0, 0, 0, 0, 1]`.
+    pub const fn from_segments(segments: [u16; 8]) -> Self {
+        let mut octets = [0; 16];
+        let mut i = 0;
+        while i < 8 {
+            let [high, low] = segments[i].to_be_bytes();
+            octets[2 * i] = high;
+            octets[2 * i + 1] = low;
+            i += 1;
+        }
+        Self(octets)
+    }
+
+    pub const fn segments(&self) -> [u16; 8] {
+        let mut segments = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            segments[i] = u16::from_be_bytes([self.0[2 * i], self.0[2 * i + 1]]);
+            i += 1;
+        }
+        segments
+    }
+
+    pub const fn to_bits(&self) -> u128 {
+        u128::from_be_bytes(self.0)
+    }
+
+    pub const fn from_bits(bits: u128) -> Self {
+        Self(bits.to_be_bytes())
+    }
+}
+
+impl From<[u8; 16]> for Ipv6Bytes {
+    fn from(value: [u8; 16]) -> Self {
+        Self(value)
+    }
+}
+
+impl From<[u16; 8]> for Ipv6Bytes {
+    fn from(value: [u16; 8]) -> Self {
+        Self::from_segments(value)
+    }
+}
+
+/// All eight segments in hex, without `::` shortening, e.g. `2001:db8:0:0:0:0:0:1`.
+impl fmt::Display for Ipv6Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments().iter().enumerate() {
+            if i > 0 {
+                f.write_str(":")?;
+            }
+            write!(f, "{segment:x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl StorageCompatible for Ipv6Bytes {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        Ok(Self(<[u8; 16]>::load(name)?))
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        <[u8; 16]>::store(name, value.0)
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        <[u8; 16]>::encode(&value.0)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        Ok(Self(<[u8; 16]>::decode(bytes)?))
+    }
+}
+
+// endregion
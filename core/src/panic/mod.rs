@@ -0,0 +1,111 @@
+//! Panic Reporting
+//!
+//! In `no_std`, every plugin has to provide its own `#[panic_handler]`. Plugins which don't need
+//! anything special can use [panic_handler!][crate::panic_handler], which logs the panic and stops
+//! the console with an on-screen message naming the plugin, the location and the panic message.
+//!
+//! # Example
+//!
+//! ```
+//! #![no_std]
+//!
+//! use wups::prelude::*;
+//!
+//! WUPS_PLUGIN_NAME!("My Plugin");
+//! wups::panic_handler!();
+//! ```
+//!
+//! # Custom handlers
+//!
+//! There can only be a single `#[panic_handler]` in the final binary, so the macro can't be
+//! combined with a handler of the plugin itself (or of another dependency). A custom handler can
+//! still use the same output by calling [report] at its end:
+//!
+//! ```
+//! #[panic_handler]
+//! fn panic(info: &core::panic::PanicInfo) -> ! {
+//!     // e.g. save state or release hardware first
+//!     wups::panic::report(info, "My Plugin")
+//! }
+//! ```
+
+use core::fmt::Write;
+use wut::{ffi::c_char, sys};
+
+/// Maximum length of the message passed to `OSFatal`. Longer messages are truncated.
+const MESSAGE_MAX_LENGTH: usize = 512;
+
+/// Define the `#[panic_handler]` of the plugin, which calls [report][crate::panic::report] with
+/// the name of the crate.
+///
+/// Must be used at most once per plugin, see the [module docs][crate::panic].
+#[macro_export]
+macro_rules! panic_handler {
+    () => {
+        #[panic_handler]
+        fn __wups_panic_handler(info: &::core::panic::PanicInfo) -> ! {
+            $crate::panic::report(info, ::core::env!("CARGO_PKG_NAME"))
+        }
+    };
+}
+
+/// Log the panic and stop the console via `OSFatal`, which displays the message on screen.
+///
+/// The message has the form `<plugin> panicked at <file>:<line>:<column>: <message>`. It is
+/// formatted without allocating, so this works even if the panic was caused by running out of
+/// memory.
+#[allow(unreachable_code)]
+pub fn report(info: &core::panic::PanicInfo, plugin: &str) -> ! {
+    let mut message = Message {
+        data: [0; MESSAGE_MAX_LENGTH],
+        len: 0,
+    };
+
+    let _ = write!(message, "{plugin} panicked");
+    if let Some(location) = info.location() {
+        let _ = write!(message, " at {location}");
+    }
+    let _ = write!(message, ": {}", info.message());
+
+    let text = message.as_str();
+    wut::println!("{text}");
+
+    unsafe { sys::OSFatal(message.as_c_str()) };
+    // `OSFatal` doesn't return
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+/// Fixed-size buffer, which silently truncates at a char boundary once it is full.
+struct Message {
+    data: [u8; MESSAGE_MAX_LENGTH],
+    len: usize,
+}
+
+impl Message {
+    fn as_str(&self) -> &str {
+        // only whole chars are written
+        core::str::from_utf8(&self.data[..self.len]).unwrap_or_default()
+    }
+
+    /// Terminate the text and return a pointer to it.
+    fn as_c_str(&mut self) -> *const c_char {
+        self.data[self.len] = 0;
+        self.data.as_ptr() as *const c_char
+    }
+}
+
+impl Write for Message {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        // keep one byte for the terminating `\0`
+        let mut len = s.len().min(MESSAGE_MAX_LENGTH - 1 - self.len);
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        self.data[self.len..self.len + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.len += len;
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! Audit log of storage writes (feature `audit-log`).
+
+use crate::sync::Mutex;
+use wups_sys as sys;
+use wut::ffi::{self, CStr, CString, c_char, c_int, c_void};
+
+unsafe extern "C" {
+    fn fopen(path: *const c_char, mode: *const c_char) -> *mut c_void;
+    fn fputs(s: *const c_char, file: *mut c_void) -> c_int;
+    fn fclose(file: *mut c_void) -> c_int;
+}
+
+static PATH: Mutex<Option<CString>> = Mutex::new(None);
+
+/// Append a line for every [store][super::store] and [delete][super::delete] to the file at `path`.
+///
+/// Each line has the form `store key=<key> type=<type> size=<bytes> status=<status>`. Passing
+/// `None` disables the log again.
+///
+/// The path is opened through newlib, so it must be reachable via devoptab (e.g.
+/// `fs:/vol/external01/wiiu/my_plugin.log`).
+///
+/// # Performance
+///
+/// The file is opened, appended to and closed on **every** write, which is slow on the SD card.
+/// This is intended for debugging "my setting isn't saving" reports, not for release builds.
+pub fn set_audit_log(path: Option<&str>) -> Result<(), ffi::NulError> {
+    let path = path.map(CString::new).transpose()?;
+    *PATH.lock().unwrap() = path;
+    Ok(())
+}
+
+pub(super) fn record(
+    op: &str,
+    name: &CStr,
+    item_type: Option<sys::WUPSStorageItemTypes::Type>,
+    size: u32,
+    status: i32,
+) {
+    let path = PATH.lock().unwrap();
+    let Some(path) = path.as_ref() else {
+        return;
+    };
+
+    let item_type = match item_type {
+        Some(t) => type_name(t),
+        None => "-",
+    };
+    let line = alloc::format!(
+        "{op} key={} type={item_type} size={size} status={status}\n",
+        name.to_string_lossy()
+    );
+    let Ok(line) = CString::new(line) else {
+        return;
+    };
+
+    unsafe {
+        let file = fopen(path.as_ptr(), c"a".as_ptr());
+        if !file.is_null() {
+            fputs(line.as_ptr(), file);
+            fclose(file);
+        }
+    }
+}
+
+fn type_name(item_type: sys::WUPSStorageItemTypes::Type) -> &'static str {
+    use sys::WUPSStorageItemTypes as T;
+    match item_type {
+        T::WUPS_STORAGE_ITEM_S32 => "s32",
+        T::WUPS_STORAGE_ITEM_S64 => "s64",
+        T::WUPS_STORAGE_ITEM_U32 => "u32",
+        T::WUPS_STORAGE_ITEM_U64 => "u64",
+        T::WUPS_STORAGE_ITEM_STRING => "string",
+        T::WUPS_STORAGE_ITEM_BINARY => "binary",
+        T::WUPS_STORAGE_ITEM_BOOL => "bool",
+        T::WUPS_STORAGE_ITEM_FLOAT => "float",
+        T::WUPS_STORAGE_ITEM_DOUBLE => "double",
+        _ => "unknown",
+    }
+}
+
+/// Disable the log, see [reset_statics][crate::reset_statics].
+pub(super) fn reset() {
+    *PATH.lock().unwrap() = None;
+}
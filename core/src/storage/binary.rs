@@ -0,0 +1,286 @@
+//! Cursors for structured records in binary items.
+
+use super::StorageError;
+use wut::vec::Vec;
+
+/// Reads fixed-size fields from the bytes of a binary item, front to back.
+///
+/// Every read advances the cursor. Reading past the end fails with
+/// [UnexpectedDataType][StorageError::UnexpectedDataType] and leaves the cursor where it was.
+///
+/// # Example
+///
+/// A record written by C code as `struct { uint16_t version; uint32_t flags; int16_t offset; }`
+/// (packed, big-endian, as on the console):
+///
+/// ```
+/// use wups::storage::{BinaryReader, StorageError};
+///
+/// let bytes = [0x00, 0x02, 0xDE, 0xAD, 0xBE, 0xEF, 0xFF, 0xF6];
+/// let mut reader = BinaryReader::new(&bytes);
+///
+/// assert_eq!(reader.read_u16_be()?, 2);
+/// assert_eq!(reader.read_u32_be()?, 0xDEAD_BEEF);
+/// assert_eq!(reader.read_i16_be()?, -10);
+/// assert!(reader.is_empty());
+/// assert_eq!(reader.read_u8(), Err(StorageError::UnexpectedDataType));
+/// # Ok::<(), StorageError>(())
+/// ```
+///
+/// Items are read with `load::<Vec<u8>>`:
+///
+/// ```no_run
+/// let bytes = storage::load::<Vec<u8>>("record")?;
+/// let mut reader = storage::BinaryReader::new(&bytes);
+/// let version = reader.read_u16_be()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct BinaryReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Next `len` bytes, e.g. for a fixed-size string field.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], StorageError> {
+        if len > self.remaining() {
+            return Err(StorageError::UnexpectedDataType);
+        }
+        let bytes = &self.data[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Skip `len` bytes, e.g. padding or reserved fields.
+    pub fn skip(&mut self, len: usize) -> Result<(), StorageError> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], StorageError> {
+        // `read_bytes` returns exactly `N` bytes
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, StorageError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_i8(&mut self) -> Result<i8, StorageError> {
+        Ok(self.read_u8()? as i8)
+    }
+}
+
+/// Builds the bytes of a binary item from fixed-size fields, front to back.
+///
+/// Writes only append to memory and can't fail; store the result with `store::<Vec<u8>>`.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{self, BinaryReader, BinaryWriter};
+///
+/// let mut writer = BinaryWriter::new();
+/// writer.write_u16_be(2);
+/// writer.write_u32_be(0xDEAD_BEEF);
+/// writer.write_i16_be(-10);
+/// assert_eq!(writer.as_slice(), [0x00, 0x02, 0xDE, 0xAD, 0xBE, 0xEF, 0xFF, 0xF6]);
+///
+/// storage::store::<Vec<u8>>("record", writer.into_vec())?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BinaryWriter {
+    data: Vec<u8>,
+}
+
+impl BinaryWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writer whose buffer can hold `capacity` bytes without reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Append `len` zero bytes, e.g. padding or reserved fields.
+    pub fn pad(&mut self, len: usize) {
+        self.data.resize(self.data.len() + len, 0);
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn write_i8(&mut self, value: i8) {
+        self.data.push(value as u8);
+    }
+}
+
+impl From<BinaryWriter> for Vec<u8> {
+    fn from(value: BinaryWriter) -> Self {
+        value.data
+    }
+}
+
+/// `read_*_be`/`read_*_le` and `write_*_be`/`write_*_le` for each type.
+macro_rules! fields {
+    ($($type:ident: $read_be:ident, $read_le:ident, $write_be:ident, $write_le:ident;)*) => {
+        impl BinaryReader<'_> {
+            $(
+                #[doc = concat!("Next `", stringify!($type), "`, stored big-endian.")]
+                pub fn $read_be(&mut self) -> Result<$type, StorageError> {
+                    Ok($type::from_be_bytes(self.read_array()?))
+                }
+
+                #[doc = concat!("Next `", stringify!($type), "`, stored little-endian.")]
+                pub fn $read_le(&mut self) -> Result<$type, StorageError> {
+                    Ok($type::from_le_bytes(self.read_array()?))
+                }
+            )*
+        }
+
+        impl BinaryWriter {
+            $(
+                /// Append `value` big-endian.
+                pub fn $write_be(&mut self, value: $type) {
+                    self.data.extend_from_slice(&value.to_be_bytes());
+                }
+
+                /// Append `value` little-endian.
+                pub fn $write_le(&mut self, value: $type) {
+                    self.data.extend_from_slice(&value.to_le_bytes());
+                }
+            )*
+        }
+    };
+}
+
+fields! {
+    u16: read_u16_be, read_u16_le, write_u16_be, write_u16_le;
+    i16: read_i16_be, read_i16_le, write_i16_be, write_i16_le;
+    u32: read_u32_be, read_u32_le, write_u32_be, write_u32_le;
+    i32: read_i32_be, read_i32_le, write_i32_be, write_i32_le;
+    u64: read_u64_be, read_u64_le, write_u64_be, write_u64_le;
+    i64: read_i64_be, read_i64_le, write_i64_be, write_i64_le;
+    f32: read_f32_be, read_f32_le, write_f32_be, write_f32_le;
+    f64: read_f64_be, read_f64_le, write_f64_be, write_f64_le;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut writer = BinaryWriter::new();
+        writer.write_u8(0xAB);
+        writer.write_i8(-1);
+        writer.write_u16_be(0x1234);
+        writer.write_u16_le(0x1234);
+        writer.write_i16_be(i16::MIN);
+        writer.write_i16_le(-2);
+        writer.write_u32_be(0xDEAD_BEEF);
+        writer.write_u32_le(0xDEAD_BEEF);
+        writer.write_i32_be(i32::MIN);
+        writer.write_i32_le(-3);
+        writer.write_u64_be(u64::MAX - 1);
+        writer.write_u64_le(0x0102_0304_0506_0708);
+        writer.write_i64_be(i64::MIN);
+        writer.write_i64_le(-4);
+        writer.write_f32_be(1.5);
+        writer.write_f32_le(-0.25);
+        writer.write_f64_be(f64::MAX);
+        writer.write_f64_le(f64::MIN_POSITIVE);
+        writer.pad(3);
+        writer.write_bytes(b"abc");
+        assert_eq!(writer.len(), 88);
+
+        let bytes = writer.into_vec();
+        let mut reader = BinaryReader::new(&bytes);
+        assert_eq!(reader.read_u8(), Ok(0xAB));
+        assert_eq!(reader.read_i8(), Ok(-1));
+        assert_eq!(reader.read_u16_be(), Ok(0x1234));
+        assert_eq!(reader.read_u16_le(), Ok(0x1234));
+        assert_eq!(reader.read_i16_be(), Ok(i16::MIN));
+        assert_eq!(reader.read_i16_le(), Ok(-2));
+        assert_eq!(reader.read_u32_be(), Ok(0xDEAD_BEEF));
+        assert_eq!(reader.read_u32_le(), Ok(0xDEAD_BEEF));
+        assert_eq!(reader.read_i32_be(), Ok(i32::MIN));
+        assert_eq!(reader.read_i32_le(), Ok(-3));
+        assert_eq!(reader.read_u64_be(), Ok(u64::MAX - 1));
+        assert_eq!(reader.read_u64_le(), Ok(0x0102_0304_0506_0708));
+        assert_eq!(reader.read_i64_be(), Ok(i64::MIN));
+        assert_eq!(reader.read_i64_le(), Ok(-4));
+        assert_eq!(reader.read_f32_be(), Ok(1.5));
+        assert_eq!(reader.read_f32_le(), Ok(-0.25));
+        assert_eq!(reader.read_f64_be(), Ok(f64::MAX));
+        assert_eq!(reader.read_f64_le(), Ok(f64::MIN_POSITIVE));
+        assert_eq!(reader.skip(3), Ok(()));
+        assert_eq!(reader.read_bytes(3), Ok(&b"abc"[..]));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn byte_order() {
+        let mut writer = BinaryWriter::new();
+        writer.write_u32_be(0x0102_0304);
+        writer.write_u32_le(0x0102_0304);
+        assert_eq!(writer.as_slice(), [1, 2, 3, 4, 4, 3, 2, 1]);
+
+        let mut reader = BinaryReader::new(writer.as_slice());
+        assert_eq!(reader.read_u32_le(), Ok(0x0403_0201));
+        assert_eq!(reader.read_u32_be(), Ok(0x0403_0201));
+    }
+
+    #[test]
+    fn read_past_end() {
+        let bytes = [1, 2, 3];
+        let mut reader = BinaryReader::new(&bytes);
+        assert_eq!(reader.read_u16_le(), Ok(0x0201));
+        assert_eq!(reader.read_u16_be(), Err(StorageError::UnexpectedDataType));
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.remaining(), 1);
+        assert_eq!(reader.read_u8(), Ok(3));
+    }
+}
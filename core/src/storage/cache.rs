@@ -0,0 +1,106 @@
+//! Read-through cache of loaded items.
+//!
+//! The bytes of every item read through [get_item][super::get_item] are kept in memory and returned
+//! on later reads of the same name and type, until the item is written or deleted through this
+//! crate or the storage is reset or reloaded.
+
+use crate::sync::Mutex;
+use alloc::collections::BTreeMap;
+use core::sync::atomic::{AtomicBool, Ordering};
+use wups_sys as sys;
+use wut::{
+    ffi::{CStr, CString, c_void},
+    vec::Vec,
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static CACHE: Mutex<BTreeMap<CString, (sys::WUPSStorageItemTypes::Type, Vec<u8>)>> =
+    Mutex::new(BTreeMap::new());
+
+/// Cache loaded values in memory. **Off by default.**
+///
+/// While enabled, [load][super::load] only calls the storage API the first time a key is read.
+/// Later reads return the cached value until the key is [stored][super::store] or
+/// [deleted][super::delete], or the storage is [reset][super::reset] or [reloaded][super::reload].
+/// Disabling the cache flushes it.
+///
+/// # Consistency
+///
+/// The cache is global to the plugin and only sees writes made through this crate. Items written
+/// by other means (e.g. C code of the plugin calling `WUPSStorageAPI_StoreItem` directly) keep
+/// returning their old value until [flush_cache] is called.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage;
+///
+/// storage::enable_cache(true);
+/// // only the first call reads from the storage API
+/// for _ in 0..60 {
+///     let speed = storage::load::<f32>("speed");
+/// }
+/// ```
+pub fn enable_cache(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        flush_cache();
+    }
+}
+
+/// Drop all cached values, so the next reads go to the storage API again.
+pub fn flush_cache() {
+    CACHE.lock().unwrap().clear();
+}
+
+/// Copy the cached bytes of `name` into `data` and return their length, if they are cached as
+/// `item_type` and fit into `size` bytes.
+pub(super) fn get(
+    name: &CStr,
+    item_type: sys::WUPSStorageItemTypes::Type,
+    data: *mut c_void,
+    size: u32,
+) -> Option<u32> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let cache = CACHE.lock().unwrap();
+    let (cached_type, bytes) = cache.get(name)?;
+    if *cached_type != item_type || bytes.len() > size as usize {
+        // let the storage API report the error
+        return None;
+    }
+
+    unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), data as *mut u8, bytes.len()) };
+    Some(bytes.len() as u32)
+}
+
+/// Cache the `len` bytes at `data`, which were just read from `name`.
+pub(super) fn insert(
+    name: &CStr,
+    item_type: sys::WUPSStorageItemTypes::Type,
+    data: *const c_void,
+    len: u32,
+) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let bytes = unsafe { core::slice::from_raw_parts(data as *const u8, len as usize) };
+    CACHE
+        .lock()
+        .unwrap()
+        .insert(name.into(), (item_type, bytes.to_vec()));
+}
+
+/// `name` was written or deleted.
+pub(super) fn invalidate(name: &CStr) {
+    CACHE.lock().unwrap().remove(name);
+}
+
+/// Disable and flush the cache, see [reset_statics][crate::reset_statics].
+pub(super) fn reset() {
+    enable_cache(false);
+}
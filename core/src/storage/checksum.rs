@@ -0,0 +1,199 @@
+//! Checksums over groups of keys (feature `key-index`).
+//!
+//! A protected group consists of all keys starting with its prefix. Whenever one of them is stored
+//! or deleted, a checksum over the names, types and values of all keys of the group is stored in
+//! `"<prefix>.__checksum"`. The checksum is verified the first time a key of the group is loaded
+//! (and again after every [reload][super::reload]), or explicitly via [verify_group].
+
+use super::{MTIME_SUFFIX, StorageError, index};
+use crate::sync::Mutex;
+use wups_sys as sys;
+use wut::{
+    ffi::{CStr, CString},
+    string::String,
+    vec::Vec,
+};
+
+const CHECKSUM_SUFFIX: &[u8] = b".__checksum";
+
+struct Group {
+    prefix: String,
+    verified: bool,
+}
+
+static GROUPS: Mutex<Vec<Group>> = Mutex::new(Vec::new());
+
+/// Protect all keys starting with `prefix` with a checksum. **Opt-in**, no group is protected by
+/// default.
+///
+/// Groups aren't persisted, so call this before the first access to the group, e.g. in
+/// [on_initialize][crate::on_initialize]. A checksum is only written once a key of the group is
+/// stored, so a group which was never stored while protected fails to verify if it has keys.
+///
+/// # Overhead
+///
+/// Every [store][super::store] or [delete][super::delete] of a key in the group reads all keys of
+/// the group and writes the checksum item, so keep groups small (a few critical settings). Loads
+/// only read the whole group once to verify it.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{self, StorageError};
+///
+/// storage::protect_group("save.");
+/// storage::store::<u32>("save.level", 3)?;
+/// storage::store::<u32>("save.coins", 120)?;
+///
+/// // e.g. at startup
+/// match storage::verify_group("save.") {
+///     Ok(()) => {}
+///     Err(StorageError::Corrupted(_)) => {
+///         // fall back to defaults
+///         storage::delete("save.level")?;
+///         storage::delete("save.coins")?;
+///     }
+///     Err(e) => return Err(e),
+/// }
+/// # Ok::<(), StorageError>(())
+/// ```
+pub fn protect_group(prefix: &str) {
+    let mut groups = GROUPS.lock().unwrap();
+    if !groups.iter().any(|group| group.prefix == prefix) {
+        groups.push(Group {
+            prefix: prefix.into(),
+            verified: false,
+        });
+    }
+}
+
+/// Check that the keys starting with `prefix` match their checksum.
+///
+/// Fails with [Corrupted][StorageError::Corrupted] if a value was changed outside of this crate
+/// or only partially written. A group without keys and checksum is valid.
+pub fn verify_group(prefix: &str) -> Result<(), StorageError> {
+    let name = CString::new(checksum_key(prefix))?;
+    let mut stored = 0u32;
+    let stored = match super::get_item_raw(
+        &name,
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_U32,
+        &mut stored as *mut _ as *mut _,
+        core::mem::size_of::<u32>() as u32,
+    ) {
+        Ok(_) => Some(stored),
+        Err(StorageError::NotFound) => None,
+        Err(e) => return Err(e),
+    };
+
+    match (stored, checksum(prefix)?) {
+        (None, None) => Ok(()),
+        (Some(stored), Some(actual)) if stored == actual => Ok(()),
+        _ => Err(StorageError::Corrupted(prefix.into())),
+    }
+}
+
+/// Update the checksums of all protected groups containing `name`.
+pub(super) fn on_write(name: &CStr) -> Result<(), StorageError> {
+    for prefix in groups_of(name, false) {
+        let name = checksum_key(&prefix);
+        match checksum(&prefix)? {
+            Some(checksum) => super::store::<u32>(&name, checksum)?,
+            // the last key of the group was deleted
+            None => match super::delete(&name) {
+                Ok(()) | Err(StorageError::NotFound) => {}
+                Err(e) => return Err(e),
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Verify all protected groups containing `name` which weren't verified yet.
+pub(super) fn on_read(name: &CStr) -> Result<(), StorageError> {
+    for prefix in groups_of(name, true) {
+        verify_group(&prefix)?;
+
+        let mut groups = GROUPS.lock().unwrap();
+        if let Some(group) = groups.iter_mut().find(|group| group.prefix == prefix) {
+            group.verified = true;
+        }
+    }
+    Ok(())
+}
+
+/// The storage was reloaded or wiped, so all groups have to be verified again.
+pub(super) fn invalidate() {
+    for group in GROUPS.lock().unwrap().iter_mut() {
+        group.verified = false;
+    }
+}
+
+/// Prefixes of the protected groups containing `name`, optionally only the unverified ones.
+///
+/// Returned as a copy, so the lock isn't held while the checksums are read and written.
+fn groups_of(name: &CStr, unverified: bool) -> Vec<String> {
+    let name = name.to_bytes();
+    if is_derived(name) {
+        return Vec::new();
+    }
+
+    GROUPS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|group| name.starts_with(group.prefix.as_bytes()))
+        .filter(|group| !(unverified && group.verified))
+        .map(|group| group.prefix.clone())
+        .collect()
+}
+
+/// Checksums and modification times, which are derived from other keys.
+pub(super) fn is_derived(name: &[u8]) -> bool {
+    name.ends_with(CHECKSUM_SUFFIX) || name.ends_with(MTIME_SUFFIX)
+}
+
+fn checksum_key(prefix: &str) -> String {
+    alloc::format!("{prefix}.__checksum")
+}
+
+/// FNV-1a over the name, type and value of every key of the group. `None` if it has no keys.
+fn checksum(prefix: &str) -> Result<Option<u32>, StorageError> {
+    const OFFSET_BASIS: u32 = 0x811C9DC5;
+    const PRIME: u32 = 0x01000193;
+
+    fn hash(mut state: u32, bytes: &[u8]) -> u32 {
+        for b in bytes {
+            state = (state ^ *b as u32).wrapping_mul(PRIME);
+        }
+        state
+    }
+
+    let mut state = OFFSET_BASIS;
+    let mut empty = true;
+
+    for (name, item_type) in index::entries()? {
+        let bytes = name.to_bytes();
+        if !bytes.starts_with(prefix.as_bytes()) || is_derived(bytes) {
+            continue;
+        }
+
+        let value = match index::read_bytes(&name, item_type) {
+            Ok(value) => value,
+            Err(StorageError::NotFound) => continue,
+            Err(e) => return Err(e),
+        };
+
+        state = hash(state, name.to_bytes_with_nul());
+        state = hash(state, &item_type.to_be_bytes());
+        state = hash(state, &(value.len() as u32).to_be_bytes());
+        state = hash(state, &value);
+        empty = false;
+    }
+
+    Ok(if empty { None } else { Some(state) })
+}
+
+/// Unprotect all groups, see [reset_statics][crate::reset_statics].
+pub(super) fn reset() {
+    GROUPS.lock().unwrap().clear();
+}
@@ -0,0 +1,60 @@
+//! Registered defaults of keys, used by [load_or_default][super::load_or_default].
+
+use super::StorageCompatible;
+use crate::sync::Mutex;
+use alloc::collections::BTreeMap;
+use wups_sys as sys;
+use wut::{string::String, vec::Vec};
+
+/// Encoded default and item type of every registered key.
+static DEFAULTS: Mutex<BTreeMap<String, (sys::WUPSStorageItemTypes::Type, Vec<u8>)>> =
+    Mutex::new(BTreeMap::new());
+
+/// Register the default of `name`, which [load_or_default][super::load_or_default] returns if the
+/// key can't be loaded.
+///
+/// The lookup order of `load_or_default::<T>(name)` is:
+///
+/// 1. the value stored as `name`,
+/// 2. the default registered here, if it was registered with the same item type as `T`,
+/// 3. `T::default()`.
+///
+/// Unregistered keys therefore keep using `Default`. Registering a key again replaces its default.
+/// Defaults only live in memory, so register them early, e.g. in
+/// [on_initialize][crate::on_initialize], and use the same values for the config items of the keys.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{self, load_or_default};
+///
+/// storage::set_default::<i32>("volume", 50);
+///
+/// storage::delete("volume").ok();
+/// assert_eq!(load_or_default::<i32>("volume"), 50);
+/// assert_eq!(load_or_default::<i32>("unregistered"), 0);
+///
+/// storage::store::<i32>("volume", 80).unwrap();
+/// assert_eq!(load_or_default::<i32>("volume"), 80);
+/// ```
+pub fn set_default<T: StorageCompatible>(name: &str, value: T::T) {
+    DEFAULTS
+        .lock()
+        .unwrap()
+        .insert(name.into(), (T::ITEM_TYPE, T::encode(&value)));
+}
+
+/// Registered default of `name`, if it was registered as the item type of `T`.
+pub(super) fn get<T: StorageCompatible>(name: &str) -> Option<T::T> {
+    let defaults = DEFAULTS.lock().unwrap();
+    let (item_type, bytes) = defaults.get(name)?;
+    if *item_type != T::ITEM_TYPE {
+        return None;
+    }
+    T::decode(bytes).ok()
+}
+
+/// Forget all defaults, see [reset_statics][crate::reset_statics].
+pub(super) fn reset() {
+    DEFAULTS.lock().unwrap().clear();
+}
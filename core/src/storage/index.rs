@@ -0,0 +1,133 @@
+//! Index of all stored keys (feature `key-index`).
+//!
+//! The storage API can't enumerate its items, so the name and type of every item written through
+//! this crate is recorded in the binary item [INDEX_KEY]. The index is cached in memory and only
+//! written when a key is added, removed or changes its type.
+//!
+//! Entries are separated by `\0` and consist of the item type as ASCII digit followed by the key.
+
+use super::StorageError;
+use crate::sync::Mutex;
+use alloc::collections::BTreeMap;
+use wups_sys as sys;
+use wut::{
+    ffi::{CStr, CString},
+    vec::Vec,
+};
+
+pub(super) const INDEX_KEY: &CStr = c"__wups_keys";
+
+/// `None` until the index is read from storage for the first time.
+static INDEX: Mutex<Option<BTreeMap<CString, sys::WUPSStorageItemTypes::Type>>> = Mutex::new(None);
+
+/// Record that `name` was stored as `item_type`.
+pub(super) fn insert(
+    name: &CStr,
+    item_type: sys::WUPSStorageItemTypes::Type,
+) -> Result<(), StorageError> {
+    update(|index| index.insert(name.into(), item_type) != Some(item_type))
+}
+
+/// Record that `name` was deleted.
+pub(super) fn remove(name: &CStr) -> Result<(), StorageError> {
+    update(|index| index.remove(name).is_some())
+}
+
+/// The storage was wiped, including the index.
+pub(super) fn clear() {
+    *INDEX.lock().unwrap() = Some(BTreeMap::new());
+}
+
+/// The storage was reloaded, so the cached index may be outdated.
+pub(super) fn invalidate() {
+    *INDEX.lock().unwrap() = None;
+}
+
+/// All recorded keys and their types, sorted by key.
+pub(super) fn entries() -> Result<Vec<(CString, sys::WUPSStorageItemTypes::Type)>, StorageError> {
+    let mut index = INDEX.lock().unwrap();
+    if index.is_none() {
+        *index = Some(read()?);
+    }
+    Ok(index
+        .iter()
+        .flatten()
+        .map(|(name, item_type)| (name.clone(), *item_type))
+        .collect())
+}
+
+/// Read the whole item `name`, regardless of its size.
+pub(super) fn read_bytes(
+    name: &CStr,
+    item_type: sys::WUPSStorageItemTypes::Type,
+) -> Result<Vec<u8>, StorageError> {
+    super::read_bytes(name, item_type, super::get_item_raw)
+}
+
+fn update(
+    f: impl FnOnce(&mut BTreeMap<CString, sys::WUPSStorageItemTypes::Type>) -> bool,
+) -> Result<(), StorageError> {
+    let mut index = INDEX.lock().unwrap();
+    if index.is_none() {
+        *index = Some(read()?);
+    }
+    let index = index.as_mut().unwrap();
+
+    if f(index) {
+        write(index)?;
+    }
+    Ok(())
+}
+
+fn read() -> Result<BTreeMap<CString, sys::WUPSStorageItemTypes::Type>, StorageError> {
+    let data = match read_bytes(
+        INDEX_KEY,
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY,
+    ) {
+        Ok(data) => data,
+        Err(StorageError::NotFound) => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(decode(&data))
+}
+
+/// Written directly instead of through `store_item`, so the index doesn't index itself.
+fn write(index: &BTreeMap<CString, sys::WUPSStorageItemTypes::Type>) -> Result<(), StorageError> {
+    let mut data = encode(index);
+
+    let status = super::retry(|| unsafe {
+        sys::WUPSStorageAPI_StoreItem(
+            core::ptr::null_mut(),
+            INDEX_KEY.as_ptr(),
+            sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY,
+            data.as_mut_ptr() as *mut _,
+            data.len() as u32,
+        )
+    });
+    super::cache::invalidate(INDEX_KEY);
+    StorageError::try_from(status)?;
+    Ok(())
+}
+
+/// Index stored as `data`, also used for the indices of [sub stores][super::SubStore].
+pub(super) fn decode(data: &[u8]) -> BTreeMap<CString, sys::WUPSStorageItemTypes::Type> {
+    data.split(|b| *b == 0)
+        .filter_map(|entry| {
+            let (item_type, name) = entry.split_first()?;
+            let name = CString::new(name).ok()?;
+            Some((
+                name,
+                item_type.wrapping_sub(b'0') as sys::WUPSStorageItemTypes::Type,
+            ))
+        })
+        .collect()
+}
+
+pub(super) fn encode(index: &BTreeMap<CString, sys::WUPSStorageItemTypes::Type>) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (name, item_type) in index {
+        data.push(b'0' + *item_type as u8);
+        data.extend_from_slice(name.to_bytes_with_nul());
+    }
+    data
+}
@@ -0,0 +1,463 @@
+//! Export and import of the whole storage as JSON (feature `json`).
+
+use super::{StorageError, index};
+use core::fmt::Write;
+use wups_sys as sys;
+use wut::{ffi::CString, string::String, vec::Vec};
+
+/// Item types and their names in the JSON document.
+const TYPES: [(sys::WUPSStorageItemTypes::Type, &str); 9] = {
+    use sys::WUPSStorageItemTypes as T;
+    [
+        (T::WUPS_STORAGE_ITEM_S32, "s32"),
+        (T::WUPS_STORAGE_ITEM_S64, "s64"),
+        (T::WUPS_STORAGE_ITEM_U32, "u32"),
+        (T::WUPS_STORAGE_ITEM_U64, "u64"),
+        (T::WUPS_STORAGE_ITEM_STRING, "string"),
+        (T::WUPS_STORAGE_ITEM_BINARY, "binary"),
+        (T::WUPS_STORAGE_ITEM_BOOL, "bool"),
+        (T::WUPS_STORAGE_ITEM_FLOAT, "float"),
+        (T::WUPS_STORAGE_ITEM_DOUBLE, "double"),
+    ]
+};
+
+/// Serialize all keys of the storage into a JSON document.
+///
+/// # Schema
+///
+/// The document is an object mapping every key to its type and value:
+///
+/// ```json
+/// {
+///   "enabled": { "type": "bool", "value": true },
+///   "volume": { "type": "s32", "value": 50 },
+///   "speed": { "type": "float", "value": 1.5 },
+///   "name": { "type": "string", "value": "Mario" },
+///   "blob": { "type": "binary", "value": "AQID" }
+/// }
+/// ```
+///
+/// | type                         | value                                                   |
+/// |------------------------------|---------------------------------------------------------|
+/// | `s32`, `s64`, `u32`, `u64`   | integer                                                 |
+/// | `float`, `double`            | number, or one of the strings `"NaN"`, `"inf"`, `"-inf"` |
+/// | `bool`                       | `true` or `false`                                       |
+/// | `string`                     | string                                                  |
+/// | `binary`                     | standard base64 with padding                            |
+///
+/// Types which don't have a native item (e.g. `u128` or [StorageEnum][super::StorageEnum]) appear
+/// as their underlying item.
+///
+/// # Limitations
+///
+/// The storage API can't enumerate its items. Instead, every key written while the feature is
+/// enabled is recorded in an index, so keys which were last written before enabling the feature
+/// (or by other means than this crate) are missing from the export until they are stored again.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage;
+///
+/// storage::store::<i32>("volume", 50)?;
+/// let json = storage::export_json()?;
+/// // write `json` to the SD card, edit it on a PC, read it back...
+/// storage::import_json(&json)?;
+/// # Ok::<(), storage::StorageError>(())
+/// ```
+pub fn export_json() -> Result<String, StorageError> {
+    let mut out = String::from("{");
+    let mut first = true;
+
+    for (name, item_type) in index::entries()? {
+        let value = match index::read_bytes(&name, item_type) {
+            Ok(value) => value,
+            Err(StorageError::NotFound) => continue,
+            Err(e) => return Err(e),
+        };
+
+        out.push_str(if first { "\n  " } else { ",\n  " });
+        first = false;
+
+        write_string(&mut out, &name.to_string_lossy());
+        let _ = write!(
+            out,
+            ": {{ \"type\": \"{}\", \"value\": ",
+            type_name(item_type)
+        );
+        write_value(&mut out, item_type, &value);
+        out.push_str(" }");
+    }
+
+    out.push_str(if first { "}" } else { "\n}" });
+    Ok(out)
+}
+
+/// Store all keys of a JSON document created by [export_json].
+///
+/// The whole document is validated before anything is written, and the writes are applied in one
+/// [transaction][super::transaction], so on error the storage is left unchanged. Keys which are
+/// not part of the document are kept.
+pub fn import_json(json: &str) -> Result<(), StorageError> {
+    let mut parser = Parser {
+        bytes: json.as_bytes(),
+        pos: 0,
+    };
+    let document = parser.value()?;
+    parser.whitespace();
+    if parser.pos != parser.bytes.len() {
+        return Err(StorageError::InvalidJson(parser.pos));
+    }
+
+    let Value::Object(entries) = document else {
+        return Err(StorageError::InvalidJson(0));
+    };
+    let items = entries
+        .into_iter()
+        .map(|(name, item)| {
+            let (item_type, data) = item_bytes(&item).ok_or(StorageError::UnexpectedDataType)?;
+            Ok((CString::new(name)?, item_type, data))
+        })
+        .collect::<Result<Vec<_>, StorageError>>()?;
+
+    super::transaction(|| {
+        for (name, item_type, mut data) in items {
+            super::store_item(
+                &name,
+                item_type,
+                data.as_mut_ptr() as *mut _,
+                data.len() as u32,
+            )?;
+        }
+        Ok(())
+    })
+}
+
+fn type_name(item_type: sys::WUPSStorageItemTypes::Type) -> &'static str {
+    TYPES
+        .iter()
+        .find(|(t, _)| *t == item_type)
+        .map_or("unknown", |(_, name)| name)
+}
+
+// region: Encoding
+
+fn write_value(out: &mut String, item_type: sys::WUPSStorageItemTypes::Type, value: &[u8]) {
+    use sys::WUPSStorageItemTypes as T;
+
+    fn array<const N: usize>(value: &[u8]) -> [u8; N] {
+        let mut bytes = [0; N];
+        let len = value.len().min(N);
+        bytes[..len].copy_from_slice(&value[..len]);
+        bytes
+    }
+
+    let _ = match item_type {
+        T::WUPS_STORAGE_ITEM_S32 => write!(out, "{}", i32::from_ne_bytes(array(value))),
+        T::WUPS_STORAGE_ITEM_S64 => write!(out, "{}", i64::from_ne_bytes(array(value))),
+        T::WUPS_STORAGE_ITEM_U32 => write!(out, "{}", u32::from_ne_bytes(array(value))),
+        T::WUPS_STORAGE_ITEM_U64 => write!(out, "{}", u64::from_ne_bytes(array(value))),
+        T::WUPS_STORAGE_ITEM_BOOL => write!(out, "{}", value.first().is_some_and(|b| *b != 0)),
+        T::WUPS_STORAGE_ITEM_FLOAT => write_float(out, f32::from_ne_bytes(array(value)) as f64),
+        T::WUPS_STORAGE_ITEM_DOUBLE => write_float(out, f64::from_ne_bytes(array(value))),
+        T::WUPS_STORAGE_ITEM_STRING => {
+            let value = value.strip_suffix(b"\0").unwrap_or(value);
+            write_string(out, &String::from_utf8_lossy(value));
+            Ok(())
+        }
+        _ => {
+            out.push('"');
+            base64_encode(out, value);
+            out.push('"');
+            Ok(())
+        }
+    };
+}
+
+fn write_float(out: &mut String, value: f64) -> core::fmt::Result {
+    if value.is_finite() {
+        write!(out, "{value}")
+    } else {
+        write!(out, "\"{value}\"")
+    }
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// endregion
+
+// region: Decoding
+
+/// Item type and native bytes of an item `{ "type": ..., "value": ... }`.
+fn item_bytes(item: &Value) -> Option<(sys::WUPSStorageItemTypes::Type, Vec<u8>)> {
+    use sys::WUPSStorageItemTypes as T;
+
+    let Value::Object(fields) = item else {
+        return None;
+    };
+    let field = |name: &str| fields.iter().find(|(n, _)| n == name).map(|(_, v)| v);
+
+    let Some(Value::String(type_name)) = field("type") else {
+        return None;
+    };
+    let (item_type, _) = TYPES.iter().find(|(_, name)| name == type_name)?;
+    let value = field("value")?;
+
+    let data = match (*item_type, value) {
+        (T::WUPS_STORAGE_ITEM_S32, Value::Number(n)) => {
+            n.parse::<i32>().ok()?.to_ne_bytes().to_vec()
+        }
+        (T::WUPS_STORAGE_ITEM_S64, Value::Number(n)) => {
+            n.parse::<i64>().ok()?.to_ne_bytes().to_vec()
+        }
+        (T::WUPS_STORAGE_ITEM_U32, Value::Number(n)) => {
+            n.parse::<u32>().ok()?.to_ne_bytes().to_vec()
+        }
+        (T::WUPS_STORAGE_ITEM_U64, Value::Number(n)) => {
+            n.parse::<u64>().ok()?.to_ne_bytes().to_vec()
+        }
+        (T::WUPS_STORAGE_ITEM_FLOAT, Value::Number(n) | Value::String(n)) => {
+            n.parse::<f32>().ok()?.to_ne_bytes().to_vec()
+        }
+        (T::WUPS_STORAGE_ITEM_DOUBLE, Value::Number(n) | Value::String(n)) => {
+            n.parse::<f64>().ok()?.to_ne_bytes().to_vec()
+        }
+        (T::WUPS_STORAGE_ITEM_BOOL, Value::Bool(b)) => [*b as u8].to_vec(),
+        (T::WUPS_STORAGE_ITEM_STRING, Value::String(s)) => s.as_bytes().to_vec(),
+        (T::WUPS_STORAGE_ITEM_BINARY, Value::String(s)) => base64_decode(s)?,
+        _ => return None,
+    };
+
+    Some((*item_type, data))
+}
+
+/// Parsed JSON. Numbers are kept as text, as their type is only known from the item type.
+///
+/// Arrays and `null` don't occur in the schema and are rejected.
+enum Value {
+    Bool(bool),
+    Number(String),
+    String(String),
+    Object(Vec<(String, Value)>),
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self) -> Result<T, StorageError> {
+        Err(StorageError::InvalidJson(self.pos))
+    }
+
+    fn whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.whitespace();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, token: &[u8]) -> Result<(), StorageError> {
+        self.whitespace();
+        if self.bytes[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+        } else {
+            self.error()
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, StorageError> {
+        match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'"') => Ok(Value::String(self.string()?)),
+            Some(b't') => self.expect(b"true").map(|_| Value::Bool(true)),
+            Some(b'f') => self.expect(b"false").map(|_| Value::Bool(false)),
+            Some(b'-' | b'0'..=b'9') => Ok(Value::Number(self.number())),
+            _ => self.error(),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, StorageError> {
+        self.expect(b"{")?;
+        let mut fields = Vec::new();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(fields));
+        }
+
+        loop {
+            if self.peek() != Some(b'"') {
+                return self.error();
+            }
+            let name = self.string()?;
+            self.expect(b":")?;
+            fields.push((name, self.value()?));
+
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                _ => return self.error(),
+            }
+        }
+    }
+
+    fn number(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| matches!(b, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+        {
+            self.pos += 1;
+        }
+        String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned()
+    }
+
+    fn string(&mut self) -> Result<String, StorageError> {
+        self.expect(b"\"")?;
+        let mut out = Vec::new();
+
+        loop {
+            let Some(&b) = self.bytes.get(self.pos) else {
+                return self.error();
+            };
+            self.pos += 1;
+
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let Some(&escape) = self.bytes.get(self.pos) else {
+                        return self.error();
+                    };
+                    self.pos += 1;
+
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return self.error(),
+                    };
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                b => out.push(b),
+            }
+        }
+
+        String::from_utf8(out).or_else(|_| self.error())
+    }
+
+    /// `\uXXXX` (after the `u`), including surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, StorageError> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            self.expect(b"\\u")?;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return self.error();
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).map_or_else(|| self.error(), Ok)
+    }
+
+    fn hex4(&mut self) -> Result<u32, StorageError> {
+        let digits = self.bytes.get(self.pos..self.pos + 4);
+        let value = digits
+            .and_then(|d| core::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok());
+        match value {
+            Some(value) => {
+                self.pos += 4;
+                Ok(value)
+            }
+            None => self.error(),
+        }
+    }
+}
+
+// endregion
+
+// region: Base64
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(out: &mut String, data: &[u8]) {
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+}
+
+fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let data = data.trim_end_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+
+    for chunk in data.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let digit = BASE64.iter().position(|b| b == c)? as u32;
+            n |= digit << (18 - 6 * i);
+        }
+
+        let bytes = n.to_be_bytes();
+        out.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+
+    Some(out)
+}
+
+// endregion
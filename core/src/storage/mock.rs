@@ -0,0 +1,251 @@
+//! In-memory storage API for host tests.
+//!
+//! Implements the `WUPSStorageAPI_*` functions of the plugin loader (and `OSGetTime`, which is used
+//! for modification times), so the storage functions of this crate can be tested without a
+//! console. Like the loader, it keeps the items in memory and only writes them to the "disk" when
+//! the storage is saved, which is what [reload][super::reload] restores.
+//!
+//! Every test which touches storage (or other global state) holds the [lock], which serializes the
+//! tests and starts them with an empty storage.
+
+extern crate std;
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::ffi::{CStr, c_char, c_void};
+use std::sync::{Mutex, MutexGuard};
+use wups_sys as sys;
+
+use sys::WUPSStorageError as E;
+
+#[derive(Clone)]
+enum Entry {
+    Item(sys::WUPSStorageItemTypes::Type, Vec<u8>),
+    Sub(usize),
+}
+
+#[derive(Clone, Default)]
+struct Node(BTreeMap<Vec<u8>, Entry>);
+
+struct State {
+    /// Root first, handles are indices into it.
+    nodes: Vec<Node>,
+    disk: Vec<Node>,
+    /// Remaining writes before they fail with an I/O error, `None` for no limit.
+    writes_left: Option<usize>,
+    fail_save: bool,
+    /// Number of writes per top-level key.
+    writes: BTreeMap<Vec<u8>, usize>,
+    saves: usize,
+    time: i64,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    nodes: Vec::new(),
+    disk: Vec::new(),
+    writes_left: None,
+    fail_save: false,
+    writes: BTreeMap::new(),
+    saves: 0,
+    time: 0,
+});
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Serialize the test and reset the storage (memory and disk) and all global state of the crate.
+pub(crate) fn lock() -> MutexGuard<'static, ()> {
+    let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    *state() = State {
+        nodes: vec![Node::default()],
+        disk: vec![Node::default()],
+        writes_left: None,
+        fail_save: false,
+        writes: BTreeMap::new(),
+        saves: 0,
+        time: 0,
+    };
+    crate::reset_statics();
+    super::set_initialized();
+    guard
+}
+
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Fail saving with an I/O error.
+pub(crate) fn fail_save(fail: bool) {
+    state().fail_save = fail;
+}
+
+/// Number of writes of the top-level key `name`, including failed ones.
+pub(crate) fn writes(name: &str) -> usize {
+    state().writes.get(name.as_bytes()).copied().unwrap_or(0)
+}
+
+/// Value of the top-level item `name` on "disk", i.e. as of the last save.
+pub(crate) fn saved(name: &str) -> Option<Vec<u8>> {
+    match state().disk[0].0.get(name.as_bytes()) {
+        Some(Entry::Item(_, bytes)) => Some(bytes.clone()),
+        _ => None,
+    }
+}
+
+fn node(parent: sys::wups_storage_item) -> usize {
+    parent as usize
+}
+
+unsafe fn key<'a>(key: *const c_char) -> &'a [u8] {
+    unsafe { CStr::from_ptr(key) }.to_bytes()
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn WUPSStorageAPI_GetItem(
+    parent: sys::wups_storage_item,
+    key: *const c_char,
+    item_type: sys::WUPSStorageItemType,
+    data: *mut c_void,
+    max_size: u32,
+    out_size: *mut u32,
+) -> E::Type {
+    let state = state();
+    let (stored_type, bytes) = match state.nodes[node(parent)].0.get(unsafe { self::key(key) }) {
+        Some(Entry::Item(stored_type, bytes)) => (*stored_type, bytes),
+        Some(Entry::Sub(_)) => return E::WUPS_STORAGE_ERROR_UNEXPECTED_DATA_TYPE,
+        None => return E::WUPS_STORAGE_ERROR_NOT_FOUND,
+    };
+    if stored_type != item_type {
+        return E::WUPS_STORAGE_ERROR_UNEXPECTED_DATA_TYPE;
+    }
+    if bytes.len() > max_size as usize {
+        return E::WUPS_STORAGE_ERROR_BUFFER_TOO_SMALL;
+    }
+    unsafe {
+        core::ptr::copy_nonoverlapping(bytes.as_ptr(), data as *mut u8, bytes.len());
+        *out_size = bytes.len() as u32;
+    }
+    E::WUPS_STORAGE_ERROR_SUCCESS
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn WUPSStorageAPI_StoreItem(
+    parent: sys::wups_storage_item,
+    key: *const c_char,
+    item_type: sys::WUPSStorageItemType,
+    data: *mut c_void,
+    size: u32,
+) -> E::Type {
+    let mut state = state();
+    let key = unsafe { self::key(key) }.to_vec();
+    if parent.is_null() {
+        *state.writes.entry(key.clone()).or_default() += 1;
+    }
+    match &mut state.writes_left {
+        Some(0) => return E::WUPS_STORAGE_ERROR_IO_ERROR,
+        Some(left) => *left -= 1,
+        None => {}
+    }
+
+    let bytes = unsafe { core::slice::from_raw_parts(data as *const u8, size as usize) }.to_vec();
+    state.nodes[node(parent)]
+        .0
+        .insert(key, Entry::Item(item_type, bytes));
+    E::WUPS_STORAGE_ERROR_SUCCESS
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn WUPSStorageAPI_GetItemSize(
+    parent: sys::wups_storage_item,
+    key: *const c_char,
+    item_type: sys::WUPSStorageItemType,
+    out_size: *mut u32,
+) -> E::Type {
+    match state().nodes[node(parent)].0.get(unsafe { self::key(key) }) {
+        Some(Entry::Item(stored_type, bytes)) if *stored_type == item_type => {
+            unsafe { *out_size = bytes.len() as u32 };
+            E::WUPS_STORAGE_ERROR_SUCCESS
+        }
+        Some(_) => E::WUPS_STORAGE_ERROR_UNEXPECTED_DATA_TYPE,
+        None => E::WUPS_STORAGE_ERROR_NOT_FOUND,
+    }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn WUPSStorageAPI_DeleteItem(
+    parent: sys::wups_storage_item,
+    key: *const c_char,
+) -> E::Type {
+    match state().nodes[node(parent)]
+        .0
+        .remove(unsafe { self::key(key) })
+    {
+        Some(_) => E::WUPS_STORAGE_ERROR_SUCCESS,
+        None => E::WUPS_STORAGE_ERROR_NOT_FOUND,
+    }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn WUPSStorageAPI_CreateSubItem(
+    parent: sys::wups_storage_item,
+    key: *const c_char,
+    out_item: *mut sys::wups_storage_item,
+) -> E::Type {
+    let mut state = state();
+    let key = unsafe { self::key(key) }.to_vec();
+    if state.nodes[node(parent)].0.contains_key(&key) {
+        return E::WUPS_STORAGE_ERROR_ALREADY_EXISTS;
+    }
+
+    let index = state.nodes.len();
+    state.nodes.push(Node::default());
+    state.nodes[node(parent)].0.insert(key, Entry::Sub(index));
+    unsafe { *out_item = index as sys::wups_storage_item };
+    E::WUPS_STORAGE_ERROR_SUCCESS
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn WUPSStorageAPI_GetSubItem(
+    parent: sys::wups_storage_item,
+    key: *const c_char,
+    out_item: *mut sys::wups_storage_item,
+) -> E::Type {
+    match state().nodes[node(parent)].0.get(unsafe { self::key(key) }) {
+        Some(Entry::Sub(index)) => {
+            unsafe { *out_item = *index as sys::wups_storage_item };
+            E::WUPS_STORAGE_ERROR_SUCCESS
+        }
+        Some(Entry::Item(..)) => E::WUPS_STORAGE_ERROR_UNEXPECTED_DATA_TYPE,
+        None => E::WUPS_STORAGE_ERROR_NOT_FOUND,
+    }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn WUPSStorageAPI_WipeStorage() -> E::Type {
+    state().nodes = vec![Node::default()];
+    E::WUPS_STORAGE_ERROR_SUCCESS
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn WUPSStorageAPI_ForceReloadStorage() -> E::Type {
+    let mut state = state();
+    state.nodes = state.disk.clone();
+    E::WUPS_STORAGE_ERROR_SUCCESS
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn WUPSStorageAPI_SaveStorage(_force: bool) -> E::Type {
+    let mut state = state();
+    if state.fail_save {
+        return E::WUPS_STORAGE_ERROR_IO_ERROR;
+    }
+    state.disk = state.nodes.clone();
+    state.saves += 1;
+    E::WUPS_STORAGE_ERROR_SUCCESS
+}
+
+/// Advances by one millisecond on every call.
+#[unsafe(no_mangle)]
+extern "C" fn OSGetTime() -> i64 {
+    let mut state = state();
+    state.time += super::TICKS_PER_8_MS as i64 / 8;
+    state.time
+}
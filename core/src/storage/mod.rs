@@ -2,7 +2,7 @@
 //!
 //! This module provides a persistent key-value-like datastore for various data types. It allows
 //! storing, loading, and deleting data with a simple API. The storage supports basic types such as
-//! integers, floats, booleans, strings, and binary data.
+//! integers, floats, booleans, strings, binary data and small maps (`BTreeMap<String, V>`).
 //!
 //! # Examples
 //!
@@ -14,12 +14,14 @@
 //! store::<u64>("big_integer", 420).unwrap();
 //! store::<f32>("float", 3.14).unwrap();
 //! store::<String>("string", "Hello there!".to_string()).unwrap();
+//! store::<u128>("hash", u128::MAX).unwrap();
 //!
 //! // Load data
 //! assert_eq!(load::<i32>("integer").unwrap(), 42);
 //! assert_eq!(load::<u64>("big_integer").unwrap(), 420);
 //! assert_eq!(load::<f32>("float").unwrap(), 3.14);
 //! assert_eq!(load::<String>("string").unwrap(), "Hello there!".to_string());
+//! assert_eq!(load::<u128>("hash").unwrap(), u128::MAX);
 //!
 //! // Load data or return default value
 //! assert_eq!(load_or_default::<i32>("nonexistent"), 0);
@@ -47,19 +49,110 @@
 //!
 //! # Constants
 //!
-//! - `STORAGE_MAX_LENGTH`: The maximum length for storage items, set to 1024 bytes.
+//! - [STORAGE_MAX_LENGTH][crate::storage::STORAGE_MAX_LENGTH]: Size of the stack buffer strings
+//!   and binary items are read into, 1024 bytes. Larger items are read into a heap buffer of their
+//!   exact size. How large strings and binary items may be stored is set with
+//!   [set_max_item_size][crate::storage::set_max_item_size].
+//!
+//! # Floats
+//!
+//! `f32` and `f64` are stored as native float items, whose on-disk representation is chosen by the
+//! plugin loader. Wrap them in [LittleEndian][crate::storage::LittleEndian] or
+//! [BigEndian][crate::storage::BigEndian] to store their bytes in a fixed order instead, e.g. when
+//! the file is read by PC tools.
+//!
+//! # Narrow integers
+//!
+//! There are no item types narrower than 32 bits, so `i8`/`i16` are stored as `S32` and `u8`/`u16`
+//! as `U32` items, which can also be read as `i32`/`u32`. They are narrowed again on load; a stored
+//! value which doesn't fit is an [UnexpectedDataType][StorageError::UnexpectedDataType].
+//!
+//! ```no_run
+//! use wups::storage::{load, store, StorageError};
+//!
+//! store::<u8>("level", u8::MAX)?;
+//! assert_eq!(load::<u8>("level"), Ok(u8::MAX));
+//! assert_eq!(load::<u32>("level"), Ok(255));
+//!
+//! store::<i16>("offset", i16::MIN)?;
+//! assert_eq!(load::<i16>("offset"), Ok(i16::MIN));
+//!
+//! store::<u32>("level", 256)?;
+//! assert_eq!(load::<u8>("level"), Err(StorageError::UnexpectedDataType));
+//! # Ok::<(), StorageError>(())
+//! ```
 //!
 //! # Functions
 //!
 //! - [load][crate::storage::load]: Loads previously saved data
 //!   from storage.
 //! - [load_or_default][crate::storage::load_or_default]: Loads previously saved data from
-//!   storage or returns the default value for the given type.
+//!   storage or returns the [registered default][crate::storage::set_default] of the key or the
+//!   default value for the given type.
 //! - [store][crate::storage::store]: Saves data into storage.
 //! - [delete][crate::storage::delete]: Deletes previously saved data from storage.
+//! - [exists][crate::storage::exists]: Checks whether data is stored, without reading it.
 //! - [reset][crate::storage::reset]: Wipes the entire storage, deleting all data.
 //! - [reload][crate::storage::reload]: Forces a reload of the storage.
+//! - [save][crate::storage::save] / [request_save][crate::storage::request_save]: Saves the storage
+//!   to disk, right away or [debounced][crate::storage::set_save_debounce].
+//! - [transaction][crate::storage::transaction]: Applies several writes together or not at all.
+//! - [try_get_item][crate::storage::try_get_item] / [try_store_item][crate::storage::try_store_item]:
+//!   Untyped access to raw item bytes, for types not covered by [StorageCompatible].
+//!
+//! # Features
+//!
+//! - `audit-log`: Enables [set_audit_log][crate::storage::set_audit_log], which records every
+//!   write and delete in a text file.
+//! - `key-index`: Keeps an index of all keys in the item `"__wups_keys"`, as the storage API can't
+//!   enumerate them. Enables [keys][crate::storage::keys], [load_all][crate::storage::load_all] and
+//!   checksums over groups of keys ([protect_group][crate::storage::protect_group]).
+//! - `json`: Enables [export_json][crate::storage::export_json] and
+//!   [import_json][crate::storage::import_json], which convert the whole storage to and from a
+//!   JSON document for editing on a PC. Implies `key-index`.
+//!
+//! Modification times can be recorded at runtime via
+//! [set_track_modified][crate::storage::set_track_modified]. Read-heavy plugins can cache loaded
+//! values with [enable_cache][crate::storage::enable_cache].
+//!
+//! [RingBuffer][crate::storage::RingBuffer] keeps the last entries of a binary log in a single
+//! item.
+//! [Snapshot][crate::storage::Snapshot] captures a set of keys to undo changes to them later.
+//! [BinaryReader][crate::storage::BinaryReader] and [BinaryWriter][crate::storage::BinaryWriter]
+//! parse and build structured records in binary items, e.g. ones shared with C code.
+//! [SubStore][crate::storage::SubStore] scopes keys to a sub category.
 
+#[cfg(feature = "audit-log")]
+mod audit;
+#[cfg(feature = "audit-log")]
+pub use audit::set_audit_log;
+mod binary;
+pub use binary::{BinaryReader, BinaryWriter};
+mod cache;
+pub use cache::{enable_cache, flush_cache};
+mod defaults;
+pub use defaults::set_default;
+#[cfg(feature = "key-index")]
+mod checksum;
+#[cfg(feature = "key-index")]
+pub use checksum::{protect_group, verify_group};
+#[cfg(feature = "key-index")]
+mod index;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(test)]
+pub(crate) mod mock;
+#[cfg(feature = "json")]
+pub use json::{export_json, import_json};
+mod ring;
+pub use ring::RingBuffer;
+mod snapshot;
+pub use snapshot::Snapshot;
+mod sub;
+pub use sub::SubStore;
+
+use alloc::collections::BTreeMap;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use thiserror::Error;
 use wups_sys as sys;
 use wut::{
@@ -68,7 +161,11 @@ use wut::{
     vec::Vec,
 };
 
-#[derive(Debug, Error)]
+/// Errors of the storage API.
+///
+/// All variants can be compared, e.g. `assert_eq!(load::<i32>("x"), Err(StorageError::NotFound))`.
+/// [UnknownError][StorageError::UnknownError] compares the raw status code.
+#[derive(Debug, Error, PartialEq, Eq)]
 pub enum StorageError {
     #[error("")]
     InvalidArgs,
@@ -76,6 +173,25 @@ pub enum StorageError {
     MallocFailed,
     #[error("")]
     UnexpectedDataType,
+    /// The item exists, but is stored as another type.
+    ///
+    /// ```no_run
+    /// use wups::storage::{load, store, StorageError, StorageType};
+    ///
+    /// store::<u64>("counter", 1).unwrap();
+    /// assert_eq!(
+    ///     load::<u32>("counter"),
+    ///     Err(StorageError::TypeMismatch {
+    ///         expected: StorageType::U32,
+    ///         actual: StorageType::U64,
+    ///     })
+    /// );
+    /// ```
+    #[error("Expected {expected:?}, but the item is stored as {actual:?}")]
+    TypeMismatch {
+        expected: StorageType,
+        actual: StorageType,
+    },
     #[error("")]
     BufferTooSmall,
     #[error("")]
@@ -84,7 +200,10 @@ pub enum StorageError {
     IoError,
     #[error("")]
     NotFound,
-    #[error("")]
+    /// The storage API was used before the `INIT_STORAGE` hook ran, see [is_initialized].
+    #[error(
+        "Storage is only available after the INIT_STORAGE hook, i.e. from on_initialize on (not in static initializers)"
+    )]
     InternalNotInitialized,
     #[error("")]
     InternalInvalidVersion,
@@ -92,6 +211,12 @@ pub enum StorageError {
     UnknownError(i32),
     #[error("CString cannot contain internal 0-bytes.")]
     ContainsNullBytes(#[from] ffi::NulError),
+    #[cfg(feature = "json")]
+    #[error("Invalid JSON at byte {0}")]
+    InvalidJson(usize),
+    #[cfg(feature = "key-index")]
+    #[error("Checksum of group \"{0}\" doesn't match its keys")]
+    Corrupted(String),
 }
 
 impl TryFrom<i32> for StorageError {
@@ -117,51 +242,543 @@ impl TryFrom<i32> for StorageError {
     }
 }
 
-const STORAGE_MAX_LENGTH: usize = 1024;
+// region: StorageType
+
+/// Type of a storage item, see [TypeMismatch][StorageError::TypeMismatch].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorageType {
+    String,
+    Binary,
+    Bool,
+    S32,
+    S64,
+    U32,
+    U64,
+    Float,
+    Double,
+}
+
+impl StorageType {
+    const ALL: [Self; 9] = [
+        Self::S32,
+        Self::S64,
+        Self::U32,
+        Self::U64,
+        Self::Bool,
+        Self::Float,
+        Self::Double,
+        Self::String,
+        Self::Binary,
+    ];
+
+    pub fn from_raw(item_type: sys::WUPSStorageItemTypes::Type) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.to_raw() == item_type)
+    }
+
+    pub fn to_raw(self) -> sys::WUPSStorageItemTypes::Type {
+        use sys::WUPSStorageItemTypes as T;
+        match self {
+            Self::String => T::WUPS_STORAGE_ITEM_STRING,
+            Self::Binary => T::WUPS_STORAGE_ITEM_BINARY,
+            Self::Bool => T::WUPS_STORAGE_ITEM_BOOL,
+            Self::S32 => T::WUPS_STORAGE_ITEM_S32,
+            Self::S64 => T::WUPS_STORAGE_ITEM_S64,
+            Self::U32 => T::WUPS_STORAGE_ITEM_U32,
+            Self::U64 => T::WUPS_STORAGE_ITEM_U64,
+            Self::Float => T::WUPS_STORAGE_ITEM_FLOAT,
+            Self::Double => T::WUPS_STORAGE_ITEM_DOUBLE,
+        }
+    }
+
+    /// Type `name` is stored as.
+    ///
+    /// The storage API can't report the type of an item, so every type is tried until one can be
+    /// read. Returns `None` if none can, e.g. because the item doesn't exist.
+    fn of(name: &ffi::CStr) -> Option<Self> {
+        let mut buffer = [0u8; STORAGE_MAX_LENGTH];
+        Self::ALL.into_iter().find(|t| {
+            let result = get_item_raw(
+                name,
+                t.to_raw(),
+                buffer.as_mut_ptr() as *mut _,
+                buffer.len() as u32,
+            );
+            // a too small buffer still means that the type matched
+            matches!(result, Ok(_) | Err(StorageError::BufferTooSmall))
+        })
+    }
+}
+
+/// Error of reading an item of type `actual` as `expected`, with both types if they are known.
+fn type_mismatch(
+    expected: sys::WUPSStorageItemTypes::Type,
+    actual: Option<StorageType>,
+) -> StorageError {
+    match (StorageType::from_raw(expected), actual) {
+        (Some(expected), Some(actual)) => StorageError::TypeMismatch { expected, actual },
+        _ => StorageError::UnexpectedDataType,
+    }
+}
+
+// endregion
+
+/// Size of the stack buffer strings and binary items are read into first, and the default of
+/// [max_item_size].
+///
+/// It doesn't limit loading: larger items are read into a heap buffer of their exact size, which
+/// is queried from the loader.
+pub const STORAGE_MAX_LENGTH: usize = 1024;
+
+/// Highest value [set_max_item_size] accepts.
+///
+/// Each save writes the whole storage as one JSON file, so a single item shouldn't grow it by more
+/// than a few dozen kilobytes.
+pub const STORAGE_ITEM_SIZE_LIMIT: usize = 64 * 1024;
+
+// region: Item Size
+
+static MAX_ITEM_SIZE: AtomicU32 = AtomicU32::new(STORAGE_MAX_LENGTH as u32);
+
+/// Allow strings and binary items of up to `bytes` bytes (including the terminator of strings) to
+/// be stored. Defaults to 1024, lower values are raised to it and higher values than
+/// [STORAGE_ITEM_SIZE_LIMIT] are lowered to that.
+///
+/// The plugin loader has no fixed limit, but keeps all items in memory and writes the whole storage
+/// to the SD card on every save (binary items base64 encoded), so large items make saving slower.
+/// Storing an item above the limit fails with [BufferTooSmall][StorageError::BufferTooSmall].
+///
+/// Loading isn't limited: items up to 1024 bytes are read into a buffer on the stack, larger ones
+/// into a heap buffer of their exact size.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage;
+///
+/// let blob = vec![0xab; 2048];
+/// assert_eq!(
+///     storage::store::<Vec<u8>>("blob", blob.clone()),
+///     Err(storage::StorageError::BufferTooSmall)
+/// );
+///
+/// storage::set_max_item_size(4096);
+/// storage::store::<Vec<u8>>("blob", blob.clone()).unwrap();
+/// assert_eq!(storage::load::<Vec<u8>>("blob").unwrap(), blob);
+/// ```
+pub fn set_max_item_size(bytes: u32) {
+    let bytes = bytes.clamp(STORAGE_MAX_LENGTH as u32, STORAGE_ITEM_SIZE_LIMIT as u32);
+    MAX_ITEM_SIZE.store(bytes, Ordering::Relaxed);
+}
+
+/// Current limit of [set_max_item_size], [STORAGE_MAX_LENGTH] unless it was raised.
+pub fn max_item_size() -> u32 {
+    MAX_ITEM_SIZE.load(Ordering::Relaxed)
+}
+
+/// Fail with [BufferTooSmall][StorageError::BufferTooSmall] if `len` bytes can't be stored.
+fn check_item_size(len: usize) -> Result<(), StorageError> {
+    if len >= max_item_size() as usize {
+        Err(StorageError::BufferTooSmall)
+    } else {
+        Ok(())
+    }
+}
+
+/// Read the whole item `name` with `read`, regardless of its size.
+///
+/// Tries the stack buffer first and only asks the loader for the size of items which don't fit.
+fn read_bytes(
+    name: &ffi::CStr,
+    item_type: sys::WUPSStorageItemTypes::Type,
+    read: fn(
+        &ffi::CStr,
+        sys::WUPSStorageItemTypes::Type,
+        *mut ffi::c_void,
+        u32,
+    ) -> Result<u32, StorageError>,
+) -> Result<Vec<u8>, StorageError> {
+    let mut buffer = [0u8; STORAGE_MAX_LENGTH];
+    match read(
+        name,
+        item_type,
+        buffer.as_mut_ptr() as *mut _,
+        buffer.len() as u32,
+    ) {
+        Ok(out) => return Ok(buffer[..out as usize].to_vec()),
+        Err(StorageError::BufferTooSmall) => {}
+        Err(e) => return Err(e),
+    }
+
+    let mut size = 0;
+    let status = unsafe {
+        sys::WUPSStorageAPI_GetItemSize(core::ptr::null_mut(), name.as_ptr(), item_type, &mut size)
+    };
+    StorageError::try_from(status)?;
+
+    let mut data = alloc::vec![0u8; size as usize];
+    let out = read(name, item_type, data.as_mut_ptr() as *mut _, size)?;
+    data.truncate(out as usize);
+    Ok(data)
+}
+
+// endregion
+
+/// Size in bytes of the fixed-size item types, `None` for strings and binary items.
+const fn item_size(item_type: sys::WUPSStorageItemTypes::Type) -> Option<usize> {
+    use sys::WUPSStorageItemTypes as T;
+    match item_type {
+        T::WUPS_STORAGE_ITEM_BOOL => Some(1),
+        T::WUPS_STORAGE_ITEM_S32 | T::WUPS_STORAGE_ITEM_U32 | T::WUPS_STORAGE_ITEM_FLOAT => Some(4),
+        T::WUPS_STORAGE_ITEM_S64 | T::WUPS_STORAGE_ITEM_U64 | T::WUPS_STORAGE_ITEM_DOUBLE => {
+            Some(8)
+        }
+        _ => None,
+    }
+}
 
+/// Type which can be stored as an item of type `ITEM_TYPE`.
+///
+/// The default methods copy the bytes of `T` as they are. For the fixed-size item types, the size
+/// of `T` must therefore match the item (e.g. 4 bytes for `WUPS_STORAGE_ITEM_U32`), which is
+/// checked at compile time when the default methods are used:
+///
+/// ```compile_fail
+/// use wups::storage::StorageCompatible;
+/// use wups::sys::WUPSStorageItemTypes;
+///
+/// struct Wide;
+///
+/// impl StorageCompatible for Wide {
+///     type T = u64;
+///     const ITEM_TYPE: WUPSStorageItemTypes::Type = WUPSStorageItemTypes::WUPS_STORAGE_ITEM_U32;
+/// }
+///
+/// // error: size of `T` doesn't match `ITEM_TYPE`
+/// Wide::load("wide");
+/// ```
+///
+/// Types which aren't plain bytes, like strings or maps, override the methods instead, usually by
+/// converting to and from a type which already implements the trait:
+///
+/// ```
+/// use wups::storage::{StorageCompatible, StorageError};
+/// use wups::sys::WUPSStorageItemTypes;
+///
+/// #[derive(Default)]
+/// struct Celsius(i32);
+///
+/// impl StorageCompatible for Celsius {
+///     type T = Self;
+///     const ITEM_TYPE: WUPSStorageItemTypes::Type = i32::ITEM_TYPE;
+///
+///     fn load(name: &str) -> Result<Self, StorageError> {
+///         i32::load(name).map(Self)
+///     }
+///
+///     fn store(name: &str, value: Self) -> Result<(), StorageError> {
+///         i32::store(name, value.0)
+///     }
+///
+///     fn encode(value: &Self) -> Vec<u8> {
+///         i32::encode(&value.0)
+///     }
+///
+///     fn decode(bytes: &[u8]) -> Result<Self, StorageError> {
+///         i32::decode(bytes).map(Self)
+///     }
+/// }
+/// ```
 pub trait StorageCompatible {
     type T: Default;
     const ITEM_TYPE: sys::WUPSStorageItemTypes::Type;
 
+    #[doc(hidden)]
+    const SIZE_MATCHES: () = assert!(
+        match item_size(Self::ITEM_TYPE) {
+            Some(size) => size == core::mem::size_of::<Self::T>(),
+            None => true,
+        },
+        "size of `StorageCompatible::T` doesn't match `ITEM_TYPE`"
+    );
+
     fn load(name: &str) -> Result<Self::T, StorageError> {
+        let () = Self::SIZE_MATCHES;
         let name = ffi::CString::new(name)?;
         let mut value: Self::T = Default::default();
-        let mut out = 0;
-
-        let status = unsafe {
-            sys::WUPSStorageAPI_GetItem(
-                core::ptr::null_mut(),
-                name.as_ptr(),
-                Self::ITEM_TYPE,
-                &mut value as *mut _ as *mut ffi::c_void,
-                core::mem::size_of::<Self::T>() as u32,
-                &mut out,
-            )
-        };
+        let out = get_item(
+            &name,
+            Self::ITEM_TYPE,
+            &mut value as *mut _ as *mut ffi::c_void,
+            core::mem::size_of::<Self::T>() as u32,
+        )?;
         debug_assert_eq!(out, core::mem::size_of::<Self::T>() as u32);
-        StorageError::try_from(status)?;
 
         Ok(value)
     }
 
     fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        let () = Self::SIZE_MATCHES;
         let name = ffi::CString::new(name)?;
         let mut value = value;
-        let status = unsafe {
-            sys::WUPSStorageAPI_StoreItem(
-                core::ptr::null_mut(),
-                name.as_ptr() as *const _,
-                Self::ITEM_TYPE,
-                &mut value as *mut _ as *mut ffi::c_void,
-                core::mem::size_of::<Self::T>() as u32,
+        store_item(
+            &name,
+            Self::ITEM_TYPE,
+            &mut value as *mut _ as *mut ffi::c_void,
+            core::mem::size_of::<Self::T>() as u32,
+        )
+    }
+
+    /// Bytes of `value` when it is nested in another item, e.g. as a value of a [BTreeMap].
+    ///
+    /// The default copies the bytes of `value` like the default [store][Self::store]. Types which
+    /// override `load` and `store` must override `encode` and [decode][Self::decode] as well.
+    fn encode(value: &Self::T) -> Vec<u8> {
+        let () = Self::SIZE_MATCHES;
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                value as *const _ as *const u8,
+                core::mem::size_of::<Self::T>(),
             )
         };
-        StorageError::try_from(status)?;
+        bytes.to_vec()
+    }
 
-        Ok(())
+    /// Counterpart to [encode][Self::encode]. Fails with
+    /// [UnexpectedDataType][StorageError::UnexpectedDataType] if `bytes` aren't a valid value.
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        let () = Self::SIZE_MATCHES;
+        if bytes.len() != core::mem::size_of::<Self::T>() {
+            return Err(StorageError::UnexpectedDataType);
+        }
+        let mut value: Self::T = Default::default();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                &mut value as *mut _ as *mut u8,
+                bytes.len(),
+            )
+        };
+        Ok(value)
+    }
+}
+
+// region: Initialization
+
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the storage API can be used.
+///
+/// The storage API is initialized by the `INIT_STORAGE` hook emitted by `WUPS_PLUGIN_NAME!`, which
+/// the plugin loader calls right before [on_initialize][crate::on_initialize]. Everything running
+/// earlier, e.g. static initializers or `#[ctor]`-like code, gets
+/// [InternalNotInitialized][StorageError::InternalNotInitialized] from all storage functions.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage;
+///
+/// let speed = if storage::is_initialized() {
+///     storage::load_or_default::<f32>("speed")
+/// } else {
+///     1.0
+/// };
+/// ```
+pub fn is_initialized() -> bool {
+    INITIALIZED.load(Ordering::Acquire)
+}
+
+/// Called by the `INIT_STORAGE` hook once the storage API is initialized.
+#[doc(hidden)]
+pub fn set_initialized() {
+    INITIALIZED.store(true, Ordering::Release);
+}
+
+// endregion
+
+// region: Retry
+
+static RETRIES: AtomicU32 = AtomicU32::new(0);
+
+/// Retry reads and writes up to `attempts` additional times if they fail with
+/// [IoError][StorageError::IoError]. Defaults to `0` (no retries).
+///
+/// Only I/O errors, which may be caused by a flaky SD card, are retried. Logical errors like
+/// [NotFound][StorageError::NotFound] or [UnexpectedDataType][StorageError::UnexpectedDataType]
+/// are returned immediately, as trying again wouldn't change the result.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage;
+///
+/// storage::set_retry(3);
+/// let volume = storage::load::<i32>("volume");
+/// ```
+pub fn set_retry(attempts: u32) {
+    RETRIES.store(attempts, Ordering::Relaxed);
+}
+
+/// Call the storage function `f` until it doesn't fail with an I/O error or the configured
+/// attempts are used up. Returns the last status.
+fn retry(mut f: impl FnMut() -> i32) -> i32 {
+    let mut retries = RETRIES.load(Ordering::Relaxed);
+    loop {
+        let status = f();
+        if status != sys::WUPSStorageError::WUPS_STORAGE_ERROR_IO_ERROR || retries == 0 {
+            return status;
+        }
+        retries -= 1;
+    }
+}
+
+// endregion
+
+/// All typed reads go through here, so protected groups are verified before their first read and
+/// values can be [cached][enable_cache].
+fn get_item(
+    name: &ffi::CStr,
+    item_type: sys::WUPSStorageItemTypes::Type,
+    data: *mut ffi::c_void,
+    size: u32,
+) -> Result<u32, StorageError> {
+    #[cfg(feature = "key-index")]
+    checksum::on_read(name)?;
+    if let Some(out) = cache::get(name, item_type, data, size) {
+        return Ok(out);
+    }
+
+    let out = match get_item_raw(name, item_type, data, size) {
+        Err(StorageError::UnexpectedDataType) => {
+            return Err(type_mismatch(item_type, StorageType::of(name)));
+        }
+        result => result?,
+    };
+    cache::insert(name, item_type, data, out);
+    Ok(out)
+}
+
+fn get_item_raw(
+    name: &ffi::CStr,
+    item_type: sys::WUPSStorageItemTypes::Type,
+    data: *mut ffi::c_void,
+    size: u32,
+) -> Result<u32, StorageError> {
+    let mut out = 0;
+    let status = retry(|| unsafe {
+        sys::WUPSStorageAPI_GetItem(
+            core::ptr::null_mut(),
+            name.as_ptr(),
+            item_type,
+            data,
+            size,
+            &mut out,
+        )
+    });
+    StorageError::try_from(status)?;
+
+    Ok(out)
+}
+
+/// Set by every successful write and cleared when the storage is saved or reloaded.
+static DIRTY: AtomicBool = AtomicBool::new(false);
+
+/// Whether the storage was modified since it was last saved to (or reloaded from) disk.
+pub(crate) fn is_dirty() -> bool {
+    DIRTY.load(Ordering::Relaxed)
+}
+
+/// All writes go through here, so they can be observed in a single place.
+fn store_item(
+    name: &ffi::CStr,
+    item_type: sys::WUPSStorageItemTypes::Type,
+    data: *mut ffi::c_void,
+    size: u32,
+) -> Result<(), StorageError> {
+    let status = retry(|| unsafe {
+        sys::WUPSStorageAPI_StoreItem(core::ptr::null_mut(), name.as_ptr(), item_type, data, size)
+    });
+    cache::invalidate(name);
+    #[cfg(feature = "audit-log")]
+    audit::record("store", name, Some(item_type), size, status);
+    StorageError::try_from(status)?;
+    DIRTY.store(true, Ordering::Relaxed);
+    #[cfg(feature = "key-index")]
+    {
+        index::insert(name, item_type)?;
+        checksum::on_write(name)?;
+    }
+
+    if TRACK_MODIFIED.load(Ordering::Relaxed) && !name.to_bytes().ends_with(MTIME_SUFFIX) {
+        touch(name)?;
     }
+
+    Ok(())
+}
+
+// region: Modification Time
+
+const MTIME_SUFFIX: &[u8] = b".__mtime";
+
+static TRACK_MODIFIED: AtomicBool = AtomicBool::new(false);
+
+/// Record when values are stored. **Off by default.**
+///
+/// While enabled, every [store] additionally writes the current time into the companion key
+/// `"<key>.__mtime"` (a `u64` item), which can be read via [modified_at]. This is intended for
+/// merging saves (e.g. last-write-wins when syncing between consoles).
+///
+/// # Overhead
+///
+/// Every write becomes two writes and every tracked key gets an additional 8 byte item in the
+/// storage file. Companion keys are removed together with their key by [delete].
+pub fn set_track_modified(enabled: bool) {
+    TRACK_MODIFIED.store(enabled, Ordering::Relaxed);
+}
+
+/// Time `name` was last stored while [tracking][set_track_modified] was enabled, in milliseconds
+/// since 2000-01-01 (the console's epoch).
+///
+/// Returns `None` if the key was never stored with tracking enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{modified_at, set_track_modified, store};
+///
+/// set_track_modified(true);
+/// store::<i32>("volume", 50).unwrap();
+/// assert!(modified_at("volume").is_some());
+/// ```
+pub fn modified_at(name: &str) -> Option<u64> {
+    u64::load(&alloc::format!("{name}.__mtime")).ok()
+}
+
+/// Timer ticks per 8 milliseconds (bus clock / 4 / 125).
+const TICKS_PER_8_MS: u64 = 497_250;
+
+/// Milliseconds since 2000-01-01.
+fn now_millis() -> u64 {
+    let ticks = unsafe { wut::sys::OSGetTime() } as u64;
+    ticks * 8 / TICKS_PER_8_MS
+}
+
+fn touch(name: &ffi::CStr) -> Result<(), StorageError> {
+    let mut key = name.to_bytes().to_vec();
+    key.extend_from_slice(MTIME_SUFFIX);
+    let key = ffi::CString::new(key)?;
+
+    let mut millis = now_millis();
+    store_item(
+        &key,
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_U64,
+        &mut millis as *mut _ as *mut ffi::c_void,
+        core::mem::size_of::<u64>() as u32,
+    )
 }
 
+// endregion
+
 // region: Impls
 
 impl StorageCompatible for i32 {
@@ -192,6 +809,15 @@ impl StorageCompatible for bool {
     type T = Self;
     const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
         sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BOOL;
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        // any other byte isn't a valid `bool`
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(StorageError::UnexpectedDataType),
+        }
+    }
 }
 
 impl StorageCompatible for f32 {
@@ -208,6 +834,47 @@ impl StorageCompatible for f64 {
 
 // endregion
 
+// region: Narrow Integers
+
+// Stored as the wider type, see "Narrow integers" in the module docs.
+macro_rules! narrow_integers {
+    ($($t:ty => $wide:ty),* $(,)?) => {
+        $(
+            impl StorageCompatible for $t {
+                type T = Self;
+                const ITEM_TYPE: sys::WUPSStorageItemTypes::Type = <$wide>::ITEM_TYPE;
+
+                fn load(name: &str) -> Result<Self::T, StorageError> {
+                    Self::try_from(<$wide>::load(name)?)
+                        .map_err(|_| StorageError::UnexpectedDataType)
+                }
+
+                fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+                    <$wide>::store(name, value.into())
+                }
+
+                fn encode(value: &Self::T) -> Vec<u8> {
+                    <$wide>::encode(&(*value).into())
+                }
+
+                fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+                    Self::try_from(<$wide>::decode(bytes)?)
+                        .map_err(|_| StorageError::UnexpectedDataType)
+                }
+            }
+        )*
+    };
+}
+
+narrow_integers! {
+    i8 => i32,
+    i16 => i32,
+    u8 => u32,
+    u16 => u32,
+}
+
+// endregion
+
 impl StorageCompatible for String {
     type T = Self;
     const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
@@ -215,46 +882,32 @@ impl StorageCompatible for String {
 
     fn load(name: &str) -> Result<Self::T, StorageError> {
         let name = ffi::CString::new(name)?;
-        let mut value = [0u8; STORAGE_MAX_LENGTH];
-        let mut out = 0;
-
-        let status = unsafe {
-            sys::WUPSStorageAPI_GetItem(
-                core::ptr::null_mut(),
-                name.as_ptr(),
-                Self::ITEM_TYPE,
-                &mut value as *mut _ as *mut ffi::c_void,
-                value.len() as u32,
-                &mut out,
-            )
-        };
-        debug_assert!(out < value.len() as u32);
-        StorageError::try_from(status)?;
+        let value = read_bytes(&name, Self::ITEM_TYPE, get_item)?;
 
-        let s = String::from_utf8_lossy(&value[..(out as usize)]);
+        let s = String::from_utf8_lossy(&value);
         let s = s.strip_suffix('\0').unwrap_or(&s).to_string();
         Ok(s)
     }
 
     fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
         let name = ffi::CString::new(name)?;
-        if value.len() >= STORAGE_MAX_LENGTH {
-            return Err(StorageError::BufferTooSmall);
-        }
+        check_item_size(value.len())?;
         let mut value = value;
 
-        let status = unsafe {
-            sys::WUPSStorageAPI_StoreItem(
-                core::ptr::null_mut(),
-                name.as_ptr() as *const _,
-                Self::ITEM_TYPE,
-                value.as_mut_ptr() as *mut _,
-                value.len() as u32,
-            )
-        };
-        StorageError::try_from(status)?;
+        store_item(
+            &name,
+            Self::ITEM_TYPE,
+            value.as_mut_ptr() as *mut _,
+            value.len() as u32,
+        )
+    }
 
-        Ok(())
+    fn encode(value: &Self::T) -> Vec<u8> {
+        value.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        Ok(String::from_utf8_lossy(bytes).to_string())
     }
 }
 
@@ -265,87 +918,885 @@ impl StorageCompatible for Vec<u8> {
 
     fn load(name: &str) -> Result<Self::T, StorageError> {
         let name = ffi::CString::new(name)?;
-        let mut value = [0u8; STORAGE_MAX_LENGTH];
-        let mut out = 0;
-
-        let status = unsafe {
-            sys::WUPSStorageAPI_GetItem(
-                core::ptr::null_mut(),
-                name.as_ptr(),
-                Self::ITEM_TYPE,
-                &mut value as *mut _ as *mut ffi::c_void,
-                value.len() as u32,
-                &mut out,
-            )
-        };
-        debug_assert!(out < value.len() as u32);
-        StorageError::try_from(status)?;
-
-        Ok(value[..(out as usize)].to_vec())
+        read_bytes(&name, Self::ITEM_TYPE, get_item)
     }
 
     fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
         let name = ffi::CString::new(name)?;
-        if value.len() >= STORAGE_MAX_LENGTH {
-            return Err(StorageError::BufferTooSmall);
-        }
+        check_item_size(value.len())?;
         let mut value = value;
 
-        let status = unsafe {
-            sys::WUPSStorageAPI_StoreItem(
-                core::ptr::null_mut(),
-                name.as_ptr() as *const _,
-                Self::ITEM_TYPE,
-                value.as_mut_ptr() as *mut _,
-                value.len() as u32,
-            )
-        };
-        StorageError::try_from(status)?;
+        store_item(
+            &name,
+            Self::ITEM_TYPE,
+            value.as_mut_ptr() as *mut _,
+            value.len() as u32,
+        )
+    }
 
-        Ok(())
+    fn encode(value: &Self::T) -> Vec<u8> {
+        value.clone()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        Ok(bytes.to_vec())
     }
 }
 
-/// Loads previously saved data from storage.
+/// Raw C strings, stored as string items.
 ///
-/// # Examples
+/// Unlike [String], the bytes aren't required to be UTF-8 and are returned unchanged, so strings
+/// written by C plugins (e.g. in Latin-1) survive the round-trip. Loading stops at the first
+/// `\0`.
+///
+/// # Example
 ///
 /// ```no_run
-/// use wups::storage::{store, load};
+/// use wups::storage::{load, store};
+/// use wut::ffi::CString;
 ///
-/// store::<i32>("exists", 42);
-/// assert_eq!(load::<i32>("exists"), 42);
-/// assert_eq!(load::<i32>("doesnt exist"), Err(StorageError::NOT_FOUND));
+/// // "café" in Latin-1, which isn't valid UTF-8
+/// store::<CString>("name", c"caf\xe9".into()).unwrap();
+/// assert_eq!(load::<CString>("name").unwrap().as_bytes(), b"caf\xe9");
 /// ```
-#[inline]
-pub fn load<T: StorageCompatible>(name: &str) -> Result<T::T, StorageError> {
-    T::load(name)
+impl StorageCompatible for ffi::CString {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_STRING;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        let name = ffi::CString::new(name)?;
+        let value = read_bytes(&name, Self::ITEM_TYPE, get_item)?;
+        let len = value.iter().position(|b| *b == 0).unwrap_or(value.len());
+        Ok(ffi::CString::new(&value[..len])?)
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        let name = ffi::CString::new(name)?;
+        let mut value = value.into_bytes();
+        check_item_size(value.len())?;
+
+        store_item(
+            &name,
+            Self::ITEM_TYPE,
+            value.as_mut_ptr() as *mut _,
+            value.len() as u32,
+        )
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        value.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        Ok(ffi::CString::new(bytes)?)
+    }
 }
 
-/// Loads previously saved data from storage or returns default value for given type.
+// region: Percent
+
+/// Percentage from 0 to 100.
 ///
-/// # Examples
+/// Stored as `i32`, like the value of [Range::percent][crate::config::Range::percent], so both can
+/// use the same key. Values outside of 0–100 are clamped when they are created or loaded.
+///
+/// # Example
 ///
 /// ```no_run
-/// use wups::storage::{store, load_or_default};
+/// use wups::storage::{load, store, Percent};
 ///
-/// store::<i32>("exists", 42);
-/// assert_eq!(load::<i32>("exists"), 42);
-/// assert_eq!(load_or_default::<i32>("doesnt exist"), 0);
+/// store::<Percent>("volume", Percent::new(50)).unwrap();
+/// assert_eq!(load::<Percent>("volume").unwrap().as_f32(), 0.5);
+///
+/// // out of range values are clamped
+/// assert_eq!(Percent::new(150), Percent::MAX);
+/// store::<i32>("volume", -20).unwrap();
+/// assert_eq!(load::<Percent>("volume").unwrap(), Percent::MIN);
 /// ```
-#[inline]
-pub fn load_or_default<T: StorageCompatible>(name: &str) -> T::T {
-    match T::load(name) {
-        Ok(v) => v,
-        Err(_) => Default::default(),
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Percent(u8);
+
+impl Percent {
+    pub const MIN: Self = Self(0);
+    pub const MAX: Self = Self(100);
+
+    /// `value` clamped to 0–100.
+    pub const fn new(value: u8) -> Self {
+        Self(if value > 100 { 100 } else { value })
+    }
+
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+
+    /// Fraction from 0.0 to 1.0.
+    pub fn as_f32(self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+
+    fn clamped(value: i32) -> Self {
+        Self(value.clamp(0, 100) as u8)
     }
 }
 
-/// Save data into storage.
-///
-/// # Examples
-///
-/// ```no_run
+impl StorageCompatible for Percent {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_S32;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        Ok(Self::clamped(i32::load(name)?))
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        i32::store(name, value.0 as i32)
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        i32::encode(&(value.0 as i32))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        Ok(Self::clamped(i32::decode(bytes)?))
+    }
+}
+
+// endregion
+
+// region: Map
+
+/// Map stored in a single binary item.
+///
+/// Every entry is written as the length of the key (`u16`, big-endian), the key, the length of the
+/// value and the value encoded with [StorageCompatible::encode]. The whole map has to fit into one
+/// item (less than [max_item_size] bytes) and every key and encoded value into `u16::MAX` bytes;
+/// other maps fail to store with [BufferTooSmall][StorageError::BufferTooSmall]. Malformed data
+/// fails to load with [UnexpectedDataType][StorageError::UnexpectedDataType].
+///
+/// # Example
+///
+/// ```no_run
+/// use alloc::collections::BTreeMap;
+/// use wups::storage::{load, store};
+///
+/// // per-game overrides
+/// let mut speeds = BTreeMap::new();
+/// speeds.insert("Mario Kart 8".to_string(), 150);
+/// speeds.insert("Splatoon".to_string(), 100);
+/// speeds.insert("Zelda".to_string(), 0);
+/// store::<BTreeMap<String, i32>>("speeds", speeds.clone()).unwrap();
+/// assert_eq!(load::<BTreeMap<String, i32>>("speeds").unwrap(), speeds);
+///
+/// store::<BTreeMap<String, bool>>("empty", BTreeMap::new()).unwrap();
+/// assert!(load::<BTreeMap<String, bool>>("empty").unwrap().is_empty());
+/// ```
+///
+/// The encoding round-trips without touching the storage:
+///
+/// ```
+/// use alloc::collections::BTreeMap;
+/// use wups::storage::StorageCompatible;
+///
+/// type Map = BTreeMap<String, i32>;
+///
+/// let mut map = Map::new();
+/// map.insert("a".to_string(), -1);
+/// map.insert("bb".to_string(), 0);
+/// map.insert("".to_string(), i32::MAX);
+/// assert_eq!(Map::decode(&Map::encode(&map)).unwrap(), map);
+///
+/// let empty = Map::new();
+/// assert!(Map::encode(&empty).is_empty());
+/// assert_eq!(Map::decode(&[]).unwrap(), empty);
+///
+/// // truncated data
+/// let bytes = Map::encode(&map);
+/// assert!(Map::decode(&bytes[..bytes.len() - 1]).is_err());
+/// ```
+impl<V: StorageCompatible> StorageCompatible for BTreeMap<String, V> {
+    type T = BTreeMap<String, V::T>;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        Self::decode(&Vec::<u8>::load(name)?)
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        // their length wouldn't fit into the prefix
+        let too_long = |part: &[u8]| part.len() > u16::MAX as usize;
+        if value
+            .iter()
+            .any(|(key, value)| too_long(key.as_bytes()) || too_long(&V::encode(value)))
+        {
+            return Err(StorageError::BufferTooSmall);
+        }
+        Vec::<u8>::store(name, Self::encode(&value))
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (key, value) in value {
+            for part in [key.as_bytes(), &V::encode(value)] {
+                // `store` rejects maps with longer parts, also of maps nested in them, as their
+                // encoding is longer as well
+                let len = u16::try_from(part.len()).unwrap_or(u16::MAX);
+                bytes.extend_from_slice(&len.to_be_bytes());
+                bytes.extend_from_slice(part);
+            }
+        }
+        bytes
+    }
+
+    fn decode(mut bytes: &[u8]) -> Result<Self::T, StorageError> {
+        fn part<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], StorageError> {
+            let (len, rest) = bytes
+                .split_first_chunk::<2>()
+                .ok_or(StorageError::UnexpectedDataType)?;
+            let len = u16::from_be_bytes(*len) as usize;
+            if rest.len() < len {
+                return Err(StorageError::UnexpectedDataType);
+            }
+            let (part, rest) = rest.split_at(len);
+            *bytes = rest;
+            Ok(part)
+        }
+
+        let mut map = BTreeMap::new();
+        while !bytes.is_empty() {
+            let key = core::str::from_utf8(part(&mut bytes)?)
+                .map_err(|_| StorageError::UnexpectedDataType)?;
+            let value = V::decode(part(&mut bytes)?)?;
+            map.insert(key.to_string(), value);
+        }
+        Ok(map)
+    }
+}
+
+// endregion
+
+// region: 128-bit
+
+// There is no native 128-bit item type, so these are stored as 16 (big-endian) bytes in a binary
+// item.
+
+impl StorageCompatible for i128 {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        Self::decode(&Vec::<u8>::load(name)?)
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        Vec::<u8>::store(name, Self::encode(&value))
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        value.to_be_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        let bytes: [u8; 16] = bytes
+            .try_into()
+            .map_err(|_| StorageError::UnexpectedDataType)?;
+        Ok(Self::from_be_bytes(bytes))
+    }
+}
+
+impl StorageCompatible for u128 {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        Self::decode(&Vec::<u8>::load(name)?)
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        Vec::<u8>::store(name, Self::encode(&value))
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        value.to_be_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        let bytes: [u8; 16] = bytes
+            .try_into()
+            .map_err(|_| StorageError::UnexpectedDataType)?;
+        Ok(Self::from_be_bytes(bytes))
+    }
+}
+
+/// Stored as is, e.g. for UUIDs or hashes. See [net::Ipv6Bytes][crate::net::Ipv6Bytes] for IPv6
+/// addresses.
+///
+/// ```no_run
+/// use wups::storage::{load, store};
+///
+/// let uuid: [u8; 16] = *b"\x12\x34\x56\x78\x9a\xbc\xde\xf0\x12\x34\x56\x78\x9a\xbc\xde\xf0";
+/// store::<[u8; 16]>("device_id", uuid).unwrap();
+/// assert_eq!(load::<[u8; 16]>("device_id").unwrap(), uuid);
+/// ```
+impl StorageCompatible for [u8; 16] {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        Self::decode(&Vec::<u8>::load(name)?)
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        Vec::<u8>::store(name, Self::encode(&value))
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        value.to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        bytes
+            .try_into()
+            .map_err(|_| StorageError::UnexpectedDataType)
+    }
+}
+
+// endregion
+
+// region: Endianness
+
+// `f32`/`f64` are passed to the loader as native FLOAT/DOUBLE items and their on-disk
+// representation is up to the loader. These wrappers store the raw bytes in an explicit byte order
+// instead, so files can be read by tools which expect a fixed layout.
+
+/// Value stored as little-endian bytes in a binary item.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{load, store, LittleEndian};
+///
+/// store::<LittleEndian<f32>>("scale", LittleEndian(1.0)).unwrap();
+/// assert_eq!(load::<LittleEndian<f32>>("scale").unwrap(), LittleEndian(1.0));
+/// // 1.0 = 0x3F800000
+/// assert_eq!(load::<Vec<u8>>("scale").unwrap(), [0x00, 0x00, 0x80, 0x3F]);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct LittleEndian<T>(pub T);
+
+/// Value stored as big-endian bytes in a binary item.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{load, store, BigEndian};
+///
+/// store::<BigEndian<f64>>("scale", BigEndian(1.0)).unwrap();
+/// // 1.0 = 0x3FF0000000000000
+/// assert_eq!(load::<Vec<u8>>("scale").unwrap(), [0x3F, 0xF0, 0, 0, 0, 0, 0, 0]);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct BigEndian<T>(pub T);
+
+macro_rules! impl_endian {
+    ($($t:ty),*) => {
+        $(
+            impl StorageCompatible for LittleEndian<$t> {
+                type T = Self;
+                const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+                    sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+                fn load(name: &str) -> Result<Self::T, StorageError> {
+                    Self::decode(&Vec::<u8>::load(name)?)
+                }
+
+                fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+                    Vec::<u8>::store(name, Self::encode(&value))
+                }
+
+                fn encode(value: &Self::T) -> Vec<u8> {
+                    value.0.to_le_bytes().to_vec()
+                }
+
+                fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+                    let bytes = bytes
+                        .try_into()
+                        .map_err(|_| StorageError::UnexpectedDataType)?;
+                    Ok(Self(<$t>::from_le_bytes(bytes)))
+                }
+            }
+
+            impl StorageCompatible for BigEndian<$t> {
+                type T = Self;
+                const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+                    sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+                fn load(name: &str) -> Result<Self::T, StorageError> {
+                    Self::decode(&Vec::<u8>::load(name)?)
+                }
+
+                fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+                    Vec::<u8>::store(name, Self::encode(&value))
+                }
+
+                fn encode(value: &Self::T) -> Vec<u8> {
+                    value.0.to_be_bytes().to_vec()
+                }
+
+                fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+                    let bytes = bytes
+                        .try_into()
+                        .map_err(|_| StorageError::UnexpectedDataType)?;
+                    Ok(Self(<$t>::from_be_bytes(bytes)))
+                }
+            }
+        )*
+    };
+}
+
+impl_endian!(f32, f64);
+
+// endregion
+
+// region: Raw
+
+/// Read item `name` of type `item_type` into `buffer` and return the number of bytes written.
+///
+/// This is the layer below [StorageCompatible]: the raw status codes are translated into
+/// [StorageError], but interpreting the bytes is up to the caller. Useful for item types or
+/// layouts the typed API doesn't cover yet.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::try_get_item;
+/// use wups::sys::WUPSStorageItemTypes;
+///
+/// let mut buffer = [0u8; 64];
+/// let len = try_get_item("blob", WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY, &mut buffer)?;
+/// let blob = &buffer[..len];
+/// # Ok::<(), wups::storage::StorageError>(())
+/// ```
+pub fn try_get_item(
+    name: &str,
+    item_type: sys::WUPSStorageItemTypes::Type,
+    buffer: &mut [u8],
+) -> Result<usize, StorageError> {
+    let name = ffi::CString::new(name)?;
+    let out = get_item(
+        &name,
+        item_type,
+        buffer.as_mut_ptr() as *mut _,
+        buffer.len() as u32,
+    )?;
+    Ok(out as usize)
+}
+
+/// Store `data` as item `name` of type `item_type`.
+///
+/// Counterpart to [try_get_item]. The size of `data` must match what the loader expects for
+/// `item_type` (e.g. 4 bytes for `WUPS_STORAGE_ITEM_S32`).
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::try_store_item;
+/// use wups::sys::WUPSStorageItemTypes;
+///
+/// try_store_item("blob", WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY, &[1, 2, 3])?;
+/// # Ok::<(), wups::storage::StorageError>(())
+/// ```
+pub fn try_store_item(
+    name: &str,
+    item_type: sys::WUPSStorageItemTypes::Type,
+    data: &[u8],
+) -> Result<(), StorageError> {
+    let name = ffi::CString::new(name)?;
+    // The loader copies the data and never writes through this pointer.
+    store_item(&name, item_type, data.as_ptr() as *mut _, data.len() as u32)
+}
+
+/// Type which is valid for every bit pattern and has no padding, so it can be copied to and from
+/// bytes as it is. Required by [Raw].
+///
+/// Implemented for the integer and float primitives and arrays of `Pod` types.
+///
+/// # Safety
+///
+/// Implementors must guarantee that
+///
+/// - every sequence of `size_of::<Self>()` bytes is a valid value (so no `bool`, `char`,
+///   references, pointers or enums), and
+/// - the type has no padding bytes, which would be read as uninitialized memory. For structs this
+///   requires `#[repr(C)]` (or `#[repr(transparent)]`) and fields which are `Pod` themselves and
+///   follow each other without gaps, e.g. `(u8, u32)` doesn't qualify.
+///
+/// # Example
+///
+/// ```
+/// use wups::storage::Pod;
+///
+/// #[derive(Default, Clone, Copy)]
+/// #[repr(C)]
+/// struct Position {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// // SAFETY: `repr(C)`, two `f32` without padding
+/// unsafe impl Pod for Position {}
+/// ```
+pub unsafe trait Pod: Copy + Default + 'static {}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(
+            unsafe impl Pod for $t {}
+        )*
+    };
+}
+
+impl_pod!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64);
+
+// `[T; N]` is only `Default` for `N <= 32`
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] where [T; N]: Default {}
+
+/// [Pod] struct stored as its bytes in a binary item, without implementing [StorageCompatible] or
+/// deriving [Storable] for it.
+///
+/// The bytes are copied as they are in memory, which is only sound for [Pod] types. Its size must
+/// be smaller than 1024 bytes (the default of [max_item_size]), which is checked at compile time.
+///
+/// # Layout changes
+///
+/// The stored bytes don't describe the layout. A stored item whose size differs from the struct,
+/// e.g. because a field was added in a new version of the plugin, fails to load with
+/// [UnexpectedDataType][StorageError::UnexpectedDataType] instead of being reinterpreted. Changing
+/// the fields without changing the size can't be detected; use a new key (or a
+/// [versioned][Storable] struct) in that case.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{load, store, Pod, Raw};
+///
+/// #[derive(Debug, Default, Clone, Copy, PartialEq)]
+/// #[repr(C)]
+/// struct Position {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// // SAFETY: `repr(C)`, two `f32` without padding
+/// unsafe impl Pod for Position {}
+///
+/// store::<Raw<Position>>("cursor", Raw(Position { x: 1.0, y: 2.0 }))?;
+/// assert_eq!(load::<Raw<Position>>("cursor")?.0, Position { x: 1.0, y: 2.0 });
+/// # Ok::<(), wups::storage::StorageError>(())
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[repr(transparent)]
+pub struct Raw<T>(pub T);
+
+impl<T> Raw<T> {
+    // `check_item_size` rejects items of `max_item_size()` bytes or more
+    const FITS: () = assert!(
+        core::mem::size_of::<T>() < STORAGE_MAX_LENGTH,
+        "`Raw<T>` can store at most 1023 bytes"
+    );
+}
+
+impl<T: Pod> StorageCompatible for Raw<T> {
+    type T = Self;
+    const ITEM_TYPE: sys::WUPSStorageItemTypes::Type =
+        sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY;
+
+    fn load(name: &str) -> Result<Self::T, StorageError> {
+        let () = Self::FITS;
+        let name = ffi::CString::new(name)?;
+        let mut buffer = [0u8; STORAGE_MAX_LENGTH];
+        let out = match get_item(
+            &name,
+            Self::ITEM_TYPE,
+            buffer.as_mut_ptr() as *mut _,
+            buffer.len() as u32,
+        ) {
+            // larger than any `Raw<T>`
+            Err(StorageError::BufferTooSmall) => return Err(StorageError::UnexpectedDataType),
+            result => result?,
+        };
+        // checks that exactly `size_of::<T>()` bytes were read
+        Self::decode(&buffer[..out as usize])
+    }
+
+    fn store(name: &str, value: Self::T) -> Result<(), StorageError> {
+        let () = Self::FITS;
+        Vec::<u8>::store(name, Self::encode(&value))
+    }
+
+    fn encode(value: &Self::T) -> Vec<u8> {
+        // SAFETY: `T: Pod` has no padding, so all of its bytes are initialized
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                &value.0 as *const T as *const u8,
+                core::mem::size_of::<T>(),
+            )
+        };
+        bytes.to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self::T, StorageError> {
+        if bytes.len() != core::mem::size_of::<T>() {
+            return Err(StorageError::UnexpectedDataType);
+        }
+        // SAFETY: the length matches and every bit pattern is a valid `T: Pod`
+        Ok(Self(unsafe {
+            core::ptr::read_unaligned(bytes.as_ptr() as *const T)
+        }))
+    }
+}
+
+// endregion
+
+// region: StorageEnum
+
+/// Fieldless enum which is stored as its discriminant in a `u32` item.
+///
+/// Usually implemented via `#[derive(StorageEnum)]`, which also implements [StorageCompatible].
+/// Loading a discriminant which doesn't belong to any variant fails with
+/// [UnexpectedDataType][StorageError::UnexpectedDataType].
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::macros::StorageEnum;
+/// use wups::storage::{load, store};
+///
+/// #[derive(Debug, Default, PartialEq, StorageEnum)]
+/// enum Quality {
+///     Low,
+///     #[default]
+///     Medium,
+///     High,
+/// }
+///
+/// store::<Quality>("quality", Quality::High).unwrap();
+/// assert_eq!(load::<Quality>("quality").unwrap(), Quality::High);
+/// ```
+pub trait StorageEnum: Sized + 'static {
+    /// All variants in declaration order.
+    const VARIANTS: &'static [Self];
+
+    fn to_discriminant(&self) -> u32;
+
+    fn from_discriminant(value: u32) -> Option<Self>;
+
+    /// Name of the variant, e.g. used as option text by
+    /// [Select::for_enum][crate::config::Select::for_enum].
+    fn name(&self) -> &'static str;
+}
+
+// endregion
+
+// region: Storable
+
+/// A group of values which are loaded and stored together, e.g. a plugin's settings struct.
+///
+/// Usually implemented via `#[derive(Storable)]`, which stores every field under its own name.
+/// Fields missing from storage are taken from the struct's [Default] implementation. The derive
+/// can also version the struct with `#[storable(version = ...)]`, so fields added in later
+/// versions start from their defaults when older data is loaded.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::macros::Storable;
+/// use wups::storage::Storable as _;
+///
+/// #[derive(Default, Storable)]
+/// struct Settings {
+///     enabled: bool,
+///     volume: i32,
+/// }
+///
+/// let mut settings = Settings::load().unwrap();
+/// settings.volume = 50;
+/// settings.store().unwrap();
+/// ```
+pub trait Storable: Sized {
+    /// Load all values from storage.
+    fn load() -> Result<Self, StorageError>;
+
+    /// Store all values into storage.
+    fn store(&self) -> Result<(), StorageError>;
+
+    /// Key, item type and [encoded][StorageCompatible::encode] value of every value, as
+    /// [store][Self::store] would write them.
+    ///
+    /// Used by [SettingsMenu][crate::config::SettingsMenu] to show the values in the menu
+    /// without reading them back from storage. The default returns nothing, so the items read
+    /// storage as usual.
+    fn entries(&self) -> Vec<(String, sys::WUPSStorageItemTypes::Type, Vec<u8>)> {
+        Vec::new()
+    }
+}
+
+// endregion
+
+/// Loads previously saved data from storage.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wups::storage::{store, load};
+///
+/// store::<i32>("exists", 42);
+/// assert_eq!(load::<i32>("exists"), Ok(42));
+/// assert_eq!(load::<i32>("doesnt exist"), Err(StorageError::NotFound));
+/// ```
+#[inline]
+pub fn load<T: StorageCompatible>(name: &str) -> Result<T::T, StorageError> {
+    T::load(name)
+}
+
+/// Loads previously saved data from storage or returns default value for given type.
+///
+/// The default is the one [registered][set_default] for `name`, or `T::default()` if there is none.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wups::storage::{store, load_or_default};
+///
+/// store::<i32>("exists", 42);
+/// assert_eq!(load::<i32>("exists"), 42);
+/// assert_eq!(load_or_default::<i32>("doesnt exist"), 0);
+/// ```
+#[inline]
+pub fn load_or_default<T: StorageCompatible>(name: &str) -> T::T {
+    T::load(name).unwrap_or_else(|_| defaults::get::<T>(name).unwrap_or_default())
+}
+
+/// Loads previously saved data from storage, or stores `default` if the key doesn't exist yet.
+///
+/// Unlike [load_or_default], the default is written back, so the key exists afterwards. Other
+/// errors (e.g. [TypeMismatch][StorageError::TypeMismatch]) are returned and nothing is stored.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wups::storage::{load, load_or_store};
+///
+/// assert_eq!(load_or_store::<i32>("volume", 50), Ok(50));
+/// assert_eq!(load::<i32>("volume"), Ok(50));
+/// assert_eq!(load_or_store::<i32>("volume", 80), Ok(50));
+/// ```
+pub fn load_or_store<T: StorageCompatible>(name: &str, default: T::T) -> Result<T::T, StorageError>
+where
+    T::T: Clone,
+{
+    match T::load(name) {
+        Err(StorageError::NotFound) => {
+            T::store(name, default.clone())?;
+            Ok(default)
+        }
+        result => result,
+    }
+}
+
+/// Load all keys starting with `prefix` which are stored as `T`, sorted by key.
+///
+/// Keys of a different item type are skipped if `skip_mismatched` is set, otherwise they fail with
+/// [TypeMismatch][StorageError::TypeMismatch]. Companion keys of
+/// [set_track_modified] are never included.
+///
+/// Keys are enumerated via the key index (feature `key-index`), so keys which were last written
+/// before the feature was enabled aren't found. Keys are sorted bytewise, so `"slot10"` comes
+/// before `"slot2"`; pad numbers (`"slot02"`) to keep them in numeric order.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wups::storage::{load_all, store};
+///
+/// store::<i32>("slot0", 10).unwrap();
+/// store::<i32>("slot1", 20).unwrap();
+/// store::<i32>("slot2", 30).unwrap();
+/// store::<bool>("slots_enabled", true).unwrap();
+///
+/// let slots = load_all::<i32>("slot", true).unwrap();
+/// assert_eq!(
+///     slots,
+///     [("slot0".into(), 10), ("slot1".into(), 20), ("slot2".into(), 30)]
+/// );
+/// ```
+#[cfg(feature = "key-index")]
+pub fn load_all<T: StorageCompatible>(
+    prefix: &str,
+    skip_mismatched: bool,
+) -> Result<Vec<(String, T::T)>, StorageError> {
+    let mut values = Vec::new();
+
+    for (name, item_type) in index::entries()? {
+        let name = name.to_bytes();
+        if !name.starts_with(prefix.as_bytes()) || name.ends_with(MTIME_SUFFIX) {
+            continue;
+        }
+        if item_type != T::ITEM_TYPE {
+            if skip_mismatched {
+                continue;
+            }
+            return Err(type_mismatch(
+                T::ITEM_TYPE,
+                StorageType::from_raw(item_type),
+            ));
+        }
+
+        let name = String::from_utf8_lossy(name).into_owned();
+        let value = T::load(&name)?;
+        values.push((name, value));
+    }
+
+    Ok(values)
+}
+
+/// Names of all stored keys, sorted bytewise.
+///
+/// The storage API has no way to enumerate its items, so the keys come from the key index
+/// (feature `key-index`): keys which were last written before the feature was enabled, or by
+/// other means than this crate, aren't found. Companion keys of [set_track_modified] and
+/// [protect_group] are never included.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wups::storage;
+///
+/// // delete everything of an old feature
+/// for key in storage::keys().unwrap() {
+///     if key.starts_with("legacy_") {
+///         storage::delete(&key).unwrap();
+///     }
+/// }
+/// ```
+#[cfg(feature = "key-index")]
+pub fn keys() -> Result<Vec<String>, StorageError> {
+    Ok(index::entries()?
+        .into_iter()
+        .map(|(name, _)| name)
+        .filter(|name| !checksum::is_derived(name.to_bytes()))
+        .map(|name| String::from_utf8_lossy(name.to_bytes()).into_owned())
+        .collect())
+}
+
+/// Save data into storage.
+///
+/// # Examples
+///
+/// ```no_run
 /// use wups::storage::store;
 ///
 /// store::<i32>("integer", 42);
@@ -363,15 +1814,345 @@ pub fn store<T: StorageCompatible>(name: &str, value: T::T) -> Result<(), Storag
 pub fn delete(name: &str) -> Result<(), StorageError> {
     let name = ffi::CString::new(name)?;
     let status = unsafe { sys::WUPSStorageAPI_DeleteItem(core::ptr::null_mut(), name.as_ptr()) };
+    cache::invalidate(&name);
+    #[cfg(feature = "audit-log")]
+    audit::record("delete", &name, None, 0, status);
     StorageError::try_from(status)?;
+    DIRTY.store(true, Ordering::Relaxed);
+
+    // companion key of `set_track_modified`, which may not exist
+    let mut key = name.as_bytes().to_vec();
+    key.extend_from_slice(MTIME_SUFFIX);
+    let key = ffi::CString::new(key)?;
+    unsafe { sys::WUPSStorageAPI_DeleteItem(core::ptr::null_mut(), key.as_ptr()) };
+    cache::invalidate(&key);
+
+    #[cfg(feature = "key-index")]
+    {
+        index::remove(&name)?;
+        index::remove(&key)?;
+        checksum::on_write(&name)?;
+    }
     Ok(())
 }
 
+/// Whether an item `name` of any type exists, without reading its value.
+///
+/// Only the size of the item is queried, so nothing is copied or allocated and the type doesn't
+/// need to be known, e.g. to detect the first run before any defaults are written.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{self, StorageError};
+///
+/// if !storage::exists("version")? {
+///     // first run
+///     storage::store::<u32>("version", 2)?;
+/// }
+/// # Ok::<(), StorageError>(())
+/// ```
+pub fn exists(name: &str) -> Result<bool, StorageError> {
+    let name = ffi::CString::new(name)?;
+    let mut size = 0;
+    // the size is only reported for strings, but the type is only checked once the item was found
+    let status = retry(|| unsafe {
+        sys::WUPSStorageAPI_GetItemSize(
+            core::ptr::null_mut(),
+            name.as_ptr(),
+            sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_STRING,
+            &mut size,
+        )
+    });
+    match StorageError::try_from(status) {
+        Ok(_) | Err(StorageError::UnexpectedDataType) => Ok(true),
+        Err(StorageError::NotFound) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// region: Key
+
+/// Typed handle to a key, so its name and type are only written down once.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{Key, StorageError};
+///
+/// const VOLUME: Key<i32> = Key::new("volume");
+///
+/// VOLUME.set(50)?;
+/// assert_eq!(VOLUME.get()?, 50);
+/// assert_eq!(VOLUME.get_or(100), 50);
+///
+/// VOLUME.delete()?;
+/// assert_eq!(VOLUME.get(), Err(StorageError::NotFound));
+/// # Ok::<(), StorageError>(())
+/// ```
+pub struct Key<T: StorageCompatible> {
+    name: &'static str,
+    // `fn() -> T` keeps the handle `Send`, `Sync` and `Copy` regardless of `T`
+    _type: core::marker::PhantomData<fn() -> T>,
+}
+
+impl<T: StorageCompatible> Key<T> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _type: core::marker::PhantomData,
+        }
+    }
+
+    /// Name of the key.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// See [load].
+    pub fn get(&self) -> Result<T::T, StorageError> {
+        T::load(self.name)
+    }
+
+    /// Stored value, or `default` if it can't be loaded.
+    pub fn get_or(&self, default: T::T) -> T::T {
+        T::load(self.name).unwrap_or(default)
+    }
+
+    /// See [store].
+    pub fn set(&self, value: T::T) -> Result<(), StorageError> {
+        T::store(self.name, value)
+    }
+
+    /// See [delete].
+    pub fn delete(&self) -> Result<(), StorageError> {
+        delete(self.name)
+    }
+}
+
+impl<T: StorageCompatible> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: StorageCompatible> Copy for Key<T> {}
+
+impl<T: StorageCompatible> core::fmt::Debug for Key<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Key").field(&self.name).finish()
+    }
+}
+
+// endregion
+
+// region: Array
+
+/// Typed handle to `N` keys `"<name>[0]"` to `"<name>[N-1]"`, e.g. for save slots or per-player
+/// settings.
+///
+/// Each element is its own key, so elements are read and written independently. Indices `>= N`
+/// fail with [InvalidArgs][StorageError::InvalidArgs] without touching storage.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{self, Array, StorageError};
+///
+/// const SLOTS: Array<u32, 3> = Array::new("slot");
+///
+/// SLOTS.set(0, 10)?;
+/// SLOTS.set(2, 30)?;
+/// assert_eq!(SLOTS.get(0)?, 10);
+/// assert_eq!(SLOTS.get(1), Err(StorageError::NotFound));
+/// assert_eq!(SLOTS.get_or(1, 20), 20);
+/// assert_eq!(SLOTS.set(3, 40), Err(StorageError::InvalidArgs));
+///
+/// // elements are regular keys, so they persist like any other
+/// storage::save(true)?;
+/// storage::reload()?;
+/// assert_eq!(storage::load::<u32>("slot[2]")?, 30);
+/// # Ok::<(), StorageError>(())
+/// ```
+///
+/// With the `key-index` feature, [load_all] can list the stored elements with the prefix
+/// `"<name>["`. It sorts keys bytewise, so `"slot[10]"` comes before `"slot[2]"`.
+pub struct Array<T: StorageCompatible, const N: usize> {
+    name: &'static str,
+    // see `Key`
+    _type: core::marker::PhantomData<fn() -> T>,
+}
+
+impl<T: StorageCompatible, const N: usize> Array<T, N> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _type: core::marker::PhantomData,
+        }
+    }
+
+    /// Name of the array, without index.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Number of elements, `N`.
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Key of element `index`, `"<name>[<index>]"`.
+    pub fn key(&self, index: usize) -> Result<String, StorageError> {
+        if index >= N {
+            return Err(StorageError::InvalidArgs);
+        }
+        Ok(alloc::format!("{}[{}]", self.name, index))
+    }
+
+    /// See [load].
+    pub fn get(&self, index: usize) -> Result<T::T, StorageError> {
+        T::load(&self.key(index)?)
+    }
+
+    /// Stored element, or `default` if it can't be loaded or `index` is out of range.
+    pub fn get_or(&self, index: usize, default: T::T) -> T::T {
+        self.get(index).unwrap_or(default)
+    }
+
+    /// See [store].
+    pub fn set(&self, index: usize, value: T::T) -> Result<(), StorageError> {
+        T::store(&self.key(index)?, value)
+    }
+
+    /// See [delete].
+    pub fn delete(&self, index: usize) -> Result<(), StorageError> {
+        delete(&self.key(index)?)
+    }
+}
+
+impl<T: StorageCompatible, const N: usize> Clone for Array<T, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: StorageCompatible, const N: usize> Copy for Array<T, N> {}
+
+impl<T: StorageCompatible, const N: usize> core::fmt::Debug for Array<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Array")
+            .field("name", &self.name)
+            .field("len", &N)
+            .finish()
+    }
+}
+
+// endregion
+
+// region: Settings
+
+/// Generate a `settings` module with a typed accessor module for each listed key.
+///
+/// Each entry `name: Type = default` becomes a module `settings::name`, stored under the key
+/// `"name"` as `Type` (any [StorageCompatible] type whose value is [Clone]):
+///
+/// ```ignore
+/// pub mod settings {
+///     pub mod volume {
+///         /// Handle of the key `"volume"`.
+///         pub const KEY: Key<i32> = Key::new("volume");
+///         /// The declared default, `50`.
+///         pub fn default() -> i32;
+///         /// Stored value. The default is stored first if the key doesn't exist yet.
+///         pub fn try_get() -> Result<i32, StorageError>;
+///         /// Like `try_get`, but returns the default if the key can't be loaded or stored.
+///         pub fn get() -> i32;
+///         /// Store `value`.
+///         pub fn set(value: i32) -> Result<(), StorageError>;
+///     }
+/// }
+/// ```
+///
+/// `try_get` goes through [load_or_store], so the first read writes the default to storage. `get`
+/// hides errors (e.g. [TypeMismatch][StorageError::TypeMismatch] after the type of a key was
+/// changed), so use `try_get` where they need to be handled. The default expression is evaluated
+/// on every call of `default`, and may refer to items of the module the macro is called in.
+///
+/// # Example
+///
+/// ```no_run
+/// wups::storage::settings! {
+///     volume: i32 = 50,
+///     enabled: bool = true,
+///     name: String = String::from("Player"),
+/// }
+///
+/// if settings::enabled::get() {
+///     settings::volume::set(settings::volume::try_get()? + 10)?;
+/// }
+///
+/// let key = settings::volume::KEY.name();
+/// root.add(config::Range::new("Volume", key, settings::volume::default(), 0, 100))?;
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __storage_settings {
+    ($($name:ident: $type:ty = $default:expr),* $(,)?) => {
+        pub mod settings {
+            $(
+                pub mod $name {
+                    #[allow(unused_imports)]
+                    use super::super::*;
+
+                    type Value = <$type as ::wups::storage::StorageCompatible>::T;
+
+                    pub const KEY: ::wups::storage::Key<$type> =
+                        ::wups::storage::Key::new(::core::stringify!($name));
+
+                    pub fn default() -> Value {
+                        $default
+                    }
+
+                    pub fn try_get()
+                    -> ::core::result::Result<Value, ::wups::storage::StorageError> {
+                        ::wups::storage::load_or_store::<$type>(KEY.name(), default())
+                    }
+
+                    pub fn get() -> Value {
+                        try_get().unwrap_or_else(|_| default())
+                    }
+
+                    pub fn set(
+                        value: Value,
+                    ) -> ::core::result::Result<(), ::wups::storage::StorageError> {
+                        KEY.set(value)
+                    }
+                }
+            )*
+        }
+    };
+}
+
+pub use crate::__storage_settings as settings;
+
+// endregion
+
 /// Wipe the entire storage. **ALL DATA WILL BE LOST**.
 #[inline]
 pub fn reset() -> Result<(), StorageError> {
     let status = unsafe { sys::WUPSStorageAPI_WipeStorage() };
+    flush_cache();
     StorageError::try_from(status)?;
+    DIRTY.store(true, Ordering::Relaxed);
+    #[cfg(feature = "key-index")]
+    {
+        index::clear();
+        checksum::invalidate();
+    }
     Ok(())
 }
 
@@ -379,14 +2160,470 @@ pub fn reset() -> Result<(), StorageError> {
 #[inline]
 pub fn reload() -> Result<(), StorageError> {
     let status = unsafe { sys::WUPSStorageAPI_ForceReloadStorage() };
+    flush_cache();
     StorageError::try_from(status)?;
+    DIRTY.store(false, Ordering::Relaxed);
+    #[cfg(feature = "key-index")]
+    {
+        index::invalidate();
+        checksum::invalidate();
+    }
     Ok(())
 }
 
 /// Save the storage to disk
+///
+/// This always saves right away, regardless of the [debounce][set_save_debounce], and satisfies
+/// pending [save requests][request_save].
 #[inline]
 pub fn save(force: bool) -> Result<(), StorageError> {
     let status = unsafe { sys::WUPSStorageAPI_SaveStorage(force) };
     StorageError::try_from(status)?;
+    DIRTY.store(false, Ordering::Relaxed);
+    SAVE_REQUESTED.store(false, Ordering::Relaxed);
+    LAST_SAVE.store(now_millis() as u32, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Run `f` as a transaction: either all of its writes end up on disk or none of them.
+///
+/// If `f` succeeds the storage is saved, otherwise it is [reloaded][reload] from disk, which
+/// discards every write `f` made, and the error of `f` is returned. If saving fails, the storage is
+/// reloaded as well and the error of the save is returned.
+///
+/// # Caveats
+///
+/// Rolling back restores the storage as it was last saved, so if there are unsaved changes when
+/// the transaction starts, they are saved first. They end up on disk even if `f` fails.
+///
+/// The storage is global, so writes from other threads while `f` runs are part of the transaction
+/// and rolled back as well. If the rollback itself fails, its error is returned instead and the
+/// storage may be half-applied in memory (but not on disk).
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{self, StorageError};
+///
+/// let result = storage::transaction(|| {
+///     storage::store::<i32>("a", 1)?;
+///     storage::store::<i32>("b", 2)?;
+///     // fails, as strings are limited in length
+///     storage::store::<String>("c", "c".repeat(2048))?;
+///     Ok(())
+/// });
+///
+/// assert_eq!(result, Err(StorageError::BufferTooSmall));
+/// // "a" and "b" were rolled back
+/// assert_eq!(storage::load::<i32>("a"), Err(StorageError::NotFound));
+/// assert_eq!(storage::load::<i32>("b"), Err(StorageError::NotFound));
+/// ```
+pub fn transaction<R>(f: impl FnOnce() -> Result<R, StorageError>) -> Result<R, StorageError> {
+    if is_dirty() {
+        save(false)?;
+    }
+
+    match f().and_then(|r| save(false).map(|()| r)) {
+        Ok(r) => Ok(r),
+        Err(e) => {
+            reload()?;
+            Err(e)
+        }
+    }
+}
+
+// region: Debounce
+
+static SAVE_DEBOUNCE: AtomicU32 = AtomicU32::new(0);
+static SAVE_REQUESTED: AtomicBool = AtomicBool::new(false);
+/// Lower 32 bits of the time of the last save in milliseconds, compared with wrapping arithmetic.
+static LAST_SAVE: AtomicU32 = AtomicU32::new(0);
+
+/// Perform at most one save per `millis` milliseconds for [request_save]. Defaults to `0` (every
+/// request saves right away).
+///
+/// Requests within the interval are coalesced into one save, which is performed by the next
+/// [request_save] or [tick_save] after the interval has passed. An explicit [save] (e.g.
+/// `save(true)`) always writes immediately and counts as the last save.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage;
+///
+/// #[on_initialize]
+/// fn init() {
+///     storage::set_save_debounce(30_000);
+/// }
+///
+/// // called every second by a playtime tracker
+/// fn on_second(seconds: u64) {
+///     let _ = storage::store::<u64>("playtime", seconds);
+///     let _ = storage::request_save();
+/// }
+///
+/// // save a request which is still waiting for the interval when the game is closed
+/// #[on_application_request_exit]
+/// fn exit() {
+///     let _ = storage::flush_save_request();
+/// }
+/// ```
+pub fn set_save_debounce(millis: u32) {
+    SAVE_DEBOUNCE.store(millis, Ordering::Relaxed);
+}
+
+/// Request the storage to be saved, debounced by [set_save_debounce].
+///
+/// Saves right away if the interval since the last save has passed, otherwise the request stays
+/// pending until [tick_save] is called after the interval.
+pub fn request_save() -> Result<(), StorageError> {
+    SAVE_REQUESTED.store(true, Ordering::Relaxed);
+    tick_save()
+}
+
+/// Perform a pending [save request][request_save] if the debounce interval has passed.
+///
+/// Call this regularly, e.g. from a lifecycle hook. A request which is still pending when the
+/// plugin is unloaded is [flushed][flush_save_request] by the `FINI_WRAPPER` hook emitted by
+/// `WUPS_PLUGIN_NAME!`, so it isn't lost.
+pub fn tick_save() -> Result<(), StorageError> {
+    if !SAVE_REQUESTED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+
+    let elapsed = (now_millis() as u32).wrapping_sub(LAST_SAVE.load(Ordering::Relaxed));
+    if elapsed >= SAVE_DEBOUNCE.load(Ordering::Relaxed) {
+        save(false)?;
+    }
     Ok(())
 }
+
+/// Perform a pending [save request][request_save] right away, without waiting for the debounce
+/// interval. Does nothing if no request is pending.
+pub fn flush_save_request() -> Result<(), StorageError> {
+    if SAVE_REQUESTED.load(Ordering::Relaxed) {
+        save(false)?;
+    }
+    Ok(())
+}
+
+// endregion
+
+/// Reset all settings and cached state of this module, see [reset_statics][crate::reset_statics].
+pub(crate) fn reset_statics() {
+    INITIALIZED.store(false, Ordering::Release);
+    RETRIES.store(0, Ordering::Relaxed);
+    DIRTY.store(false, Ordering::Relaxed);
+    TRACK_MODIFIED.store(false, Ordering::Relaxed);
+    SAVE_DEBOUNCE.store(0, Ordering::Relaxed);
+    MAX_ITEM_SIZE.store(STORAGE_MAX_LENGTH as u32, Ordering::Relaxed);
+    SAVE_REQUESTED.store(false, Ordering::Relaxed);
+    LAST_SAVE.store(0, Ordering::Relaxed);
+    cache::reset();
+    defaults::reset();
+    #[cfg(feature = "audit-log")]
+    audit::reset();
+    #[cfg(feature = "key-index")]
+    {
+        index::invalidate();
+        checksum::reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod map {
+        use super::*;
+
+        type Map = BTreeMap<String, i32>;
+
+        #[test]
+        fn round_trip() {
+            let mut map = Map::new();
+            map.insert("a".to_string(), -1);
+            map.insert("bb".to_string(), 0);
+            map.insert("".to_string(), i32::MAX);
+            map.insert("Mario Kart 8".to_string(), 150);
+
+            assert_eq!(Map::decode(&Map::encode(&map)), Ok(map));
+        }
+
+        #[test]
+        fn empty() {
+            let map = Map::new();
+            assert!(Map::encode(&map).is_empty());
+            assert_eq!(Map::decode(&[]), Ok(map));
+        }
+
+        #[test]
+        fn nested() {
+            let mut inner = Map::new();
+            inner.insert("x".to_string(), 1);
+            let mut map = BTreeMap::new();
+            map.insert("inner".to_string(), inner);
+            map.insert("empty".to_string(), Map::new());
+
+            type Nested = BTreeMap<String, Map>;
+            assert_eq!(Nested::decode(&Nested::encode(&map)), Ok(map));
+        }
+
+        #[test]
+        fn truncated() {
+            let mut map = Map::new();
+            map.insert("key".to_string(), 1);
+            let bytes = Map::encode(&map);
+
+            for len in 1..bytes.len() {
+                assert_eq!(
+                    Map::decode(&bytes[..len]),
+                    Err(StorageError::UnexpectedDataType)
+                );
+            }
+        }
+
+        #[test]
+        fn part_too_long() {
+            let _lock = mock::lock();
+
+            let mut map = Map::new();
+            map.insert("k".repeat(u16::MAX as usize + 1), 1);
+            assert_eq!(Map::store("map", map), Err(StorageError::BufferTooSmall));
+
+            let mut inner = Map::new();
+            inner.insert("k".repeat(u16::MAX as usize + 1), 1);
+            let mut map = BTreeMap::new();
+            map.insert("inner".to_string(), inner);
+            assert_eq!(
+                BTreeMap::<String, Map>::store("map", map),
+                Err(StorageError::BufferTooSmall)
+            );
+        }
+    }
+
+    mod transaction {
+        use super::*;
+
+        #[test]
+        fn commits() {
+            let _lock = mock::lock();
+
+            let result = transaction(|| {
+                store::<i32>("a", 1)?;
+                store::<i32>("b", 2)
+            });
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(mock::saved("a"), Some(1i32.to_ne_bytes().to_vec()));
+            assert_eq!(mock::saved("b"), Some(2i32.to_ne_bytes().to_vec()));
+        }
+
+        #[test]
+        fn third_write_fails() {
+            let _lock = mock::lock();
+            store::<i32>("a", 0).unwrap();
+            save(false).unwrap();
+
+            let result = transaction(|| {
+                store::<i32>("a", 1)?;
+                store::<i32>("b", 2)?;
+                store::<String>("c", "c".repeat(2048))
+            });
+
+            assert_eq!(result, Err(StorageError::BufferTooSmall));
+            assert_eq!(load::<i32>("a"), Ok(0));
+            assert_eq!(load::<i32>("b"), Err(StorageError::NotFound));
+            assert_eq!(load::<String>("c"), Err(StorageError::NotFound));
+            assert!(!is_dirty());
+        }
+
+        #[test]
+        fn save_fails() {
+            let _lock = mock::lock();
+
+            let result = transaction(|| {
+                mock::fail_save(true);
+                store::<i32>("a", 1)
+            });
+
+            assert_eq!(result, Err(StorageError::IoError));
+            assert_eq!(load::<i32>("a"), Err(StorageError::NotFound));
+            assert!(!is_dirty());
+        }
+
+        #[test]
+        fn saves_unsaved_changes_first() {
+            let _lock = mock::lock();
+            store::<i32>("before", 1).unwrap();
+
+            let result = transaction(|| store::<String>("c", "c".repeat(2048)));
+
+            assert_eq!(result, Err(StorageError::BufferTooSmall));
+            assert_eq!(load::<i32>("before"), Ok(1));
+        }
+    }
+
+    mod debounce {
+        use super::*;
+
+        #[test]
+        fn flushes_pending_request() {
+            let _lock = mock::lock();
+            set_save_debounce(u32::MAX);
+            LAST_SAVE.store(now_millis() as u32, Ordering::Relaxed);
+
+            store::<i32>("a", 1).unwrap();
+            request_save().unwrap();
+            tick_save().unwrap();
+            assert_eq!(mock::saved("a"), None);
+
+            flush_save_request().unwrap();
+            assert_eq!(mock::saved("a"), Some(1i32.to_ne_bytes().to_vec()));
+
+            // nothing pending anymore
+            store::<i32>("a", 2).unwrap();
+            flush_save_request().unwrap();
+            assert_eq!(mock::saved("a"), Some(1i32.to_ne_bytes().to_vec()));
+        }
+    }
+
+    mod load {
+        use super::*;
+
+        #[test]
+        fn type_mismatch() {
+            let _lock = mock::lock();
+            store::<u64>("counter", 1).unwrap();
+
+            assert_eq!(
+                load::<u32>("counter"),
+                Err(StorageError::TypeMismatch {
+                    expected: StorageType::U32,
+                    actual: StorageType::U64,
+                })
+            );
+            assert_eq!(
+                load::<String>("counter"),
+                Err(StorageError::TypeMismatch {
+                    expected: StorageType::String,
+                    actual: StorageType::U64,
+                })
+            );
+            assert_eq!(load::<u64>("counter"), Ok(1));
+            assert_eq!(load::<u32>("missing"), Err(StorageError::NotFound));
+        }
+    }
+
+    mod item_size {
+        use super::*;
+
+        #[test]
+        fn large_blob() {
+            let _lock = mock::lock();
+            let blob: Vec<u8> = (0..2048).map(|i| i as u8).collect();
+
+            assert_eq!(
+                store::<Vec<u8>>("blob", blob.clone()),
+                Err(StorageError::BufferTooSmall)
+            );
+            assert_eq!(load::<Vec<u8>>("blob"), Err(StorageError::NotFound));
+
+            set_max_item_size(4096);
+            store::<Vec<u8>>("blob", blob.clone()).unwrap();
+            assert_eq!(load::<Vec<u8>>("blob"), Ok(blob));
+
+            let text = "x".repeat(3000);
+            store::<String>("text", text.clone()).unwrap();
+            assert_eq!(load::<String>("text"), Ok(text));
+        }
+
+        #[test]
+        fn limits() {
+            let _lock = mock::lock();
+            assert_eq!(max_item_size(), STORAGE_MAX_LENGTH as u32);
+
+            set_max_item_size(16);
+            assert_eq!(max_item_size(), STORAGE_MAX_LENGTH as u32);
+            set_max_item_size(u32::MAX);
+            assert_eq!(max_item_size(), STORAGE_ITEM_SIZE_LIMIT as u32);
+
+            let blob = alloc::vec![0; STORAGE_ITEM_SIZE_LIMIT];
+            assert_eq!(
+                store::<Vec<u8>>("blob", blob),
+                Err(StorageError::BufferTooSmall)
+            );
+        }
+    }
+
+    mod narrow_integers {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            let _lock = mock::lock();
+            store::<u8>("u8", u8::MAX).unwrap();
+            store::<i8>("i8", i8::MIN).unwrap();
+            store::<u16>("u16", u16::MAX).unwrap();
+            store::<i16>("i16", i16::MIN).unwrap();
+
+            assert_eq!(load::<u8>("u8"), Ok(u8::MAX));
+            assert_eq!(load::<i8>("i8"), Ok(i8::MIN));
+            assert_eq!(load::<u16>("u16"), Ok(u16::MAX));
+            assert_eq!(load::<i16>("i16"), Ok(i16::MIN));
+            // stored as the wider type
+            assert_eq!(load::<u32>("u8"), Ok(255));
+            assert_eq!(load::<i32>("i16"), Ok(-32768));
+
+            assert_eq!(u8::decode(&u8::encode(&u8::MAX)), Ok(u8::MAX));
+            assert_eq!(i16::decode(&i16::encode(&i16::MIN)), Ok(i16::MIN));
+        }
+
+        #[test]
+        fn out_of_range() {
+            let _lock = mock::lock();
+            store::<u32>("u8", 256).unwrap();
+            store::<i32>("i16", i16::MIN as i32 - 1).unwrap();
+
+            assert_eq!(load::<u8>("u8"), Err(StorageError::UnexpectedDataType));
+            assert_eq!(load::<i16>("i16"), Err(StorageError::UnexpectedDataType));
+            assert_eq!(
+                u8::decode(&u32::encode(&256)),
+                Err(StorageError::UnexpectedDataType)
+            );
+        }
+    }
+
+    mod wide_integers {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            let _lock = mock::lock();
+            store::<u128>("hash", u128::MAX).unwrap();
+            store::<i128>("offset", i128::MIN).unwrap();
+
+            assert_eq!(load::<u128>("hash"), Ok(u128::MAX));
+            assert_eq!(load::<i128>("offset"), Ok(i128::MIN));
+            // big-endian bytes in a binary item
+            let mut bytes = alloc::vec![0; 16];
+            bytes[15] = 1;
+            assert_eq!(u128::encode(&1), bytes);
+            assert_eq!(load::<Vec<u8>>("hash"), Ok(alloc::vec![0xff; 16]));
+        }
+
+        #[test]
+        fn wrong_length() {
+            let _lock = mock::lock();
+            store::<Vec<u8>>("hash", alloc::vec![0xff; 15]).unwrap();
+            store::<Vec<u8>>("offset", alloc::vec![0; 17]).unwrap();
+
+            assert_eq!(load::<u128>("hash"), Err(StorageError::UnexpectedDataType));
+            assert_eq!(
+                load::<i128>("offset"),
+                Err(StorageError::UnexpectedDataType)
+            );
+            assert_eq!(u128::decode(&[]), Err(StorageError::UnexpectedDataType));
+        }
+    }
+}
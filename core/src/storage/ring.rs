@@ -0,0 +1,273 @@
+//! Bounded log of binary entries, stored as a single item.
+
+use super::{STORAGE_MAX_LENGTH, StorageCompatible, StorageError};
+use wut::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Bytes in front of the entries: offset of the oldest entry and number of used bytes (both `u16`
+/// big-endian).
+const HEADER: usize = 4;
+
+/// Bytes in front of every entry: its length (`u16` big-endian).
+const PREFIX: usize = 2;
+
+/// Fixed-capacity log of binary entries, e.g. the last inputs or crash breadcrumbs.
+///
+/// The entries share `N` bytes, each of them taking its length plus two bytes. Once they are full,
+/// [push][RingBuffer::push] drops the oldest entries until the new one fits. The whole buffer is
+/// stored as one binary item, so `N` plus a four byte header must be below the maximum item size
+/// (1024 bytes), which is checked at compile time.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{RingBuffer, StorageError};
+///
+/// // room for three entries of four bytes
+/// let mut log = RingBuffer::<18>::open("crash_log")?;
+/// log.clear()?;
+/// for i in 0u32..5 {
+///     log.push(&i.to_be_bytes())?;
+/// }
+///
+/// // the first two entries were dropped to make room
+/// let entries: Vec<Vec<u8>> = log.iter().collect();
+/// assert_eq!(entries, [2u32, 3, 4].map(|i| i.to_be_bytes().to_vec()));
+///
+/// // the entries are persisted
+/// let log = RingBuffer::<18>::open("crash_log")?;
+/// assert_eq!(log.iter().collect::<Vec<_>>(), entries);
+/// # Ok::<(), StorageError>(())
+/// ```
+///
+/// `N` must match the capacity the item was stored with, otherwise [open][RingBuffer::open] fails
+/// with [UnexpectedDataType][StorageError::UnexpectedDataType].
+///
+/// ```compile_fail
+/// use wups::storage::RingBuffer;
+///
+/// // too large for a single item
+/// let log = RingBuffer::<1024>::open("log");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RingBuffer<const N: usize> {
+    name: String,
+    data: Vec<u8>,
+    /// Offset of the oldest entry.
+    head: usize,
+    /// Bytes used by all entries including their prefixes.
+    used: usize,
+}
+
+impl<const N: usize> RingBuffer<N> {
+    #[doc(hidden)]
+    const FITS: () = assert!(
+        N > PREFIX && N + HEADER < STORAGE_MAX_LENGTH,
+        "RingBuffer capacity must be larger than 2 and fit into a storage item"
+    );
+
+    /// Load the buffer stored as `name`, or create an empty one if the item doesn't exist.
+    ///
+    /// Nothing is stored until the first [push][RingBuffer::push].
+    pub fn open(name: &str) -> Result<Self, StorageError> {
+        let () = Self::FITS;
+
+        let mut ring = Self {
+            name: name.to_string(),
+            data: alloc::vec![0; N],
+            head: 0,
+            used: 0,
+        };
+        match Vec::<u8>::load(name) {
+            Ok(bytes) => ring.restore(&bytes)?,
+            Err(StorageError::NotFound) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(ring)
+    }
+
+    /// Append `bytes` as the newest entry and store the buffer.
+    ///
+    /// Fails with [BufferTooSmall][StorageError::BufferTooSmall] if the entry can never fit, i.e.
+    /// if it is longer than `N - 2` bytes. If storing fails, the entry stays in memory and is
+    /// stored with the next successful push.
+    pub fn push(&mut self, bytes: &[u8]) -> Result<(), StorageError> {
+        let size = PREFIX + bytes.len();
+        if size > N {
+            return Err(StorageError::BufferTooSmall);
+        }
+
+        while N - self.used < size {
+            self.pop_front();
+        }
+
+        let mut at = (self.head + self.used) % N;
+        for byte in (bytes.len() as u16).to_be_bytes().iter().chain(bytes) {
+            self.data[at] = *byte;
+            at = (at + 1) % N;
+        }
+        self.used += size;
+
+        self.store()
+    }
+
+    /// Entries from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let mut at = self.head;
+        let mut left = self.used;
+        core::iter::from_fn(move || {
+            if left == 0 {
+                return None;
+            }
+            let len = self.entry_len(at);
+            let entry = (0..len).map(|i| self.data[(at + PREFIX + i) % N]).collect();
+            at = (at + PREFIX + len) % N;
+            left -= PREFIX + len;
+            Some(entry)
+        })
+    }
+
+    /// Whether the buffer has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.used == 0
+    }
+
+    /// Remove all entries and store the empty buffer.
+    pub fn clear(&mut self) -> Result<(), StorageError> {
+        self.head = 0;
+        self.used = 0;
+        self.store()
+    }
+
+    fn entry_len(&self, at: usize) -> usize {
+        u16::from_be_bytes([self.data[at], self.data[(at + 1) % N]]) as usize
+    }
+
+    fn pop_front(&mut self) {
+        let size = PREFIX + self.entry_len(self.head);
+        self.head = (self.head + size) % N;
+        self.used -= size;
+    }
+
+    fn store(&self) -> Result<(), StorageError> {
+        let mut bytes = Vec::with_capacity(HEADER + N);
+        bytes.extend_from_slice(&(self.head as u16).to_be_bytes());
+        bytes.extend_from_slice(&(self.used as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.data);
+        Vec::<u8>::store(&self.name, bytes)
+    }
+
+    fn restore(&mut self, bytes: &[u8]) -> Result<(), StorageError> {
+        if bytes.len() != HEADER + N {
+            return Err(StorageError::UnexpectedDataType);
+        }
+        let head = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+        let used = u16::from_be_bytes([bytes[2], bytes[3]]) as usize;
+        if head >= N || used > N {
+            return Err(StorageError::UnexpectedDataType);
+        }
+        self.data.copy_from_slice(&bytes[HEADER..]);
+        self.head = head;
+
+        // the entries have to add up to the used bytes exactly
+        let mut at = head;
+        let mut left = used;
+        while left > 0 {
+            let size = PREFIX + self.entry_len(at);
+            if size > left {
+                return Err(StorageError::UnexpectedDataType);
+            }
+            at = (at + size) % N;
+            left -= size;
+        }
+        self.used = used;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{self, mock};
+    use alloc::vec;
+
+    fn entries<const N: usize>(ring: &RingBuffer<N>) -> Vec<Vec<u8>> {
+        ring.iter().collect()
+    }
+
+    #[test]
+    fn drops_oldest_entries() {
+        let _lock = mock::lock();
+        let mut ring = RingBuffer::<18>::open("log").unwrap();
+        assert!(ring.is_empty());
+
+        for i in 0u32..5 {
+            ring.push(&i.to_be_bytes()).unwrap();
+        }
+        assert_eq!(
+            entries(&ring),
+            [2u32, 3, 4].map(|i| i.to_be_bytes().to_vec())
+        );
+        assert_eq!(ring.push(&[0; 17]), Err(StorageError::BufferTooSmall));
+    }
+
+    #[test]
+    fn wraps_around() {
+        let _lock = mock::lock();
+        let mut ring = RingBuffer::<10>::open("log").unwrap();
+
+        // the prefix and bytes of later entries wrap around the end of the data
+        ring.push(&[1, 2, 3]).unwrap();
+        ring.push(&[4, 5, 6]).unwrap();
+        ring.push(&[7]).unwrap();
+        assert_eq!(entries(&ring), [vec![4, 5, 6], vec![7]]);
+        ring.push(&[8, 9, 10, 11]).unwrap();
+        assert_eq!(entries(&ring), [vec![7], vec![8, 9, 10, 11]]);
+        ring.push(&[]).unwrap();
+        assert_eq!(entries(&ring), [vec![8, 9, 10, 11], vec![]]);
+        ring.push(&[12, 13, 14, 15, 16, 17, 18, 19]).unwrap();
+        assert_eq!(entries(&ring), [vec![12, 13, 14, 15, 16, 17, 18, 19]]);
+    }
+
+    #[test]
+    fn persists() {
+        let _lock = mock::lock();
+        let mut ring = RingBuffer::<10>::open("log").unwrap();
+        for entry in [&[1, 2, 3][..], &[4, 5, 6], &[7], &[8, 9]] {
+            ring.push(entry).unwrap();
+        }
+        storage::save(false).unwrap();
+        // not saved, so it is gone after reloading
+        ring.push(&[10]).unwrap();
+        storage::reload().unwrap();
+
+        let mut ring = RingBuffer::<10>::open("log").unwrap();
+        assert_eq!(entries(&ring), [vec![7], vec![8, 9]]);
+        ring.push(&[10, 11]).unwrap();
+        assert_eq!(entries(&ring), [vec![8, 9], vec![10, 11]]);
+
+        ring.clear().unwrap();
+        assert!(RingBuffer::<10>::open("log").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_items() {
+        let _lock = mock::lock();
+        RingBuffer::<10>::open("log").unwrap().push(&[1]).unwrap();
+        assert!(matches!(
+            RingBuffer::<12>::open("log"),
+            Err(StorageError::UnexpectedDataType)
+        ));
+
+        // entries don't add up to the used bytes
+        let mut bytes = vec![0, 0, 0, 4];
+        bytes.extend([0, 1, 1, 0, 0, 0, 0, 0, 0, 0]);
+        Vec::<u8>::store("log", bytes).unwrap();
+        assert!(matches!(
+            RingBuffer::<10>::open("log"),
+            Err(StorageError::UnexpectedDataType)
+        ));
+    }
+}
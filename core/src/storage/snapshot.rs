@@ -0,0 +1,120 @@
+//! Copies of a set of keys, to undo changes to them.
+
+use super::{StorageError, StorageType};
+use wut::{ffi::CString, vec::Vec};
+
+/// Values of a fixed set of keys at one point in time.
+///
+/// [capture][Snapshot::capture] reads the listed keys, [restore][Snapshot::restore] writes them
+/// back, e.g. to undo the changes the user made in the config menu. **Only the listed keys are
+/// covered**: other keys, even ones created after the capture, are left as they are.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{self, Snapshot, StorageError};
+///
+/// storage::store::<i32>("volume", 50)?;
+/// let snapshot = Snapshot::capture(&["volume", "muted"])?;
+///
+/// storage::store::<i32>("volume", 80)?;
+/// storage::store::<bool>("muted", true)?;
+///
+/// snapshot.restore()?;
+/// assert_eq!(storage::load::<i32>("volume"), Ok(50));
+/// // didn't exist when the snapshot was captured
+/// assert_eq!(storage::load::<bool>("muted"), Err(StorageError::NotFound));
+/// # Ok::<(), StorageError>(())
+/// ```
+///
+/// # Config menu
+///
+/// The plugin menu has no "cancel", closing it always keeps the changes. A plugin can offer an
+/// undo by capturing its keys when the menu is opened and restoring them on request:
+///
+/// ```
+/// use wups::sync::Mutex;
+///
+/// static SNAPSHOT: Mutex<Option<storage::Snapshot>> = Mutex::new(None);
+///
+/// struct MyMenu;
+/// impl ConfigMenu for MyMenu {
+///     fn open(root: config::MenuRoot) -> Result<(), config::MenuError> {
+///         *SNAPSHOT.lock().unwrap() = Some(storage::Snapshot::capture(&["volume"])?);
+///         root.add(config::Range::new("Volume", "volume", 50, 0, 100))?;
+///         root.add(config::Toggle::new("Discard changes", "discard", false, "Yes", "No"))?;
+///         Ok(())
+///     }
+///
+///     fn close() -> Result<(), config::MenuError> {
+///         if storage::load_or_default::<bool>("discard") {
+///             storage::store::<bool>("discard", false)?;
+///             if let Some(snapshot) = SNAPSHOT.lock().unwrap().take() {
+///                 snapshot.restore()?;
+///             }
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Clone)]
+struct Item {
+    name: CString,
+    /// `None` if the key didn't exist.
+    value: Option<(StorageType, Vec<u8>)>,
+}
+
+impl Snapshot {
+    /// Read the current values of `keys`. Keys which don't exist are recorded as missing and
+    /// deleted again when the snapshot is restored.
+    pub fn capture(keys: &[&str]) -> Result<Self, StorageError> {
+        let mut items = Vec::with_capacity(keys.len());
+        for key in keys {
+            let name = CString::new(*key)?;
+            let value = match StorageType::of(&name) {
+                Some(item_type) => Some((item_type, read(&name, item_type)?)),
+                None => None,
+            };
+            items.push(Item { name, value });
+        }
+        Ok(Self { items })
+    }
+
+    /// Write the captured values back and delete the keys which didn't exist.
+    ///
+    /// The writes are applied in one [transaction][super::transaction], which saves the storage.
+    /// If one of them fails, none are applied.
+    pub fn restore(&self) -> Result<(), StorageError> {
+        super::transaction(|| {
+            for Item { name, value } in &self.items {
+                match value {
+                    Some((item_type, data)) => super::store_item(
+                        name,
+                        item_type.to_raw(),
+                        data.as_ptr() as *mut _,
+                        data.len() as u32,
+                    )?,
+                    None => match super::delete(&name.to_string_lossy()) {
+                        Ok(()) | Err(StorageError::NotFound) => {}
+                        Err(e) => return Err(e),
+                    },
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+fn read(name: &CString, item_type: StorageType) -> Result<Vec<u8>, StorageError> {
+    let mut data = super::read_bytes(name, item_type.to_raw(), super::get_item_raw)?;
+    // strings are read with their terminator, but stored without
+    if item_type == StorageType::String && data.last() == Some(&0) {
+        data.pop();
+    }
+    Ok(data)
+}
@@ -0,0 +1,263 @@
+//! Sub categories, which give keys their own namespace.
+
+#[cfg(feature = "key-index")]
+use super::index;
+use super::{STORAGE_MAX_LENGTH, StorageCompatible, StorageError, check_item_size, retry};
+#[cfg(feature = "key-index")]
+use alloc::collections::BTreeMap;
+use core::sync::atomic::Ordering;
+use wups_sys as sys;
+#[cfg(feature = "key-index")]
+use wut::string::String;
+use wut::{
+    ffi::{CStr, CString},
+    vec::Vec,
+};
+
+/// Sub category of the storage, whose keys don't collide with keys of the same name at the top
+/// level or in other sub categories.
+///
+/// The items are stored nested under the category's name, so a plugin can group the settings of
+/// unrelated features without prefixing every key by hand. Sub stores can be nested themselves.
+///
+/// Only the item functions are scoped: the [read cache][super::enable_cache],
+/// [modification tracking][super::set_track_modified] and the audit log cover top-level keys
+/// only, and [Snapshot][super::Snapshot] or [exists][super::exists] can't address keys of a sub
+/// store. With feature `key-index`, every sub store keeps its own index for [keys][Self::keys].
+///
+/// # Handles
+///
+/// A sub store wraps a handle of the plugin loader, which stays valid until the storage is
+/// [reloaded][super::reload] or [reset][super::reset]. Open the sub store again afterwards
+/// instead of keeping it around.
+///
+/// # Example
+///
+/// ```no_run
+/// use wups::storage::{StorageError, SubStore};
+///
+/// let audio = SubStore::open("audio")?;
+/// let video = SubStore::open("video")?;
+///
+/// audio.store::<bool>("enabled", true)?;
+/// video.store::<bool>("enabled", false)?;
+///
+/// assert_eq!(audio.load::<bool>("enabled"), Ok(true));
+/// assert_eq!(video.load::<bool>("enabled"), Ok(false));
+/// // the top level has no "enabled" of its own
+/// assert_eq!(wups::storage::load::<bool>("enabled"), Err(StorageError::NotFound));
+/// # Ok::<(), StorageError>(())
+/// ```
+#[derive(Debug)]
+pub struct SubStore {
+    handle: sys::wups_storage_item,
+}
+
+impl SubStore {
+    /// Open the top-level sub category `name`, creating it if it doesn't exist yet.
+    pub fn open(name: &str) -> Result<Self, StorageError> {
+        open(core::ptr::null_mut(), name)
+    }
+
+    /// Open the sub category `name` within this one, creating it if it doesn't exist yet.
+    pub fn sub_store(&self, name: &str) -> Result<Self, StorageError> {
+        open(self.handle, name)
+    }
+
+    /// Like [load][super::load], within this sub category.
+    pub fn load<T: StorageCompatible>(&self, name: &str) -> Result<T::T, StorageError> {
+        let name = CString::new(name)?;
+        let mut bytes = self.read(&name, T::ITEM_TYPE)?;
+        // strings are read with their terminator
+        if T::ITEM_TYPE == sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_STRING
+            && bytes.last() == Some(&0)
+        {
+            bytes.pop();
+        }
+        T::decode(&bytes)
+    }
+
+    /// Like [load_or_default][super::load_or_default], within this sub category. Defaults
+    /// [registered][super::set_default] for top-level keys don't apply.
+    pub fn load_or_default<T: StorageCompatible>(&self, name: &str) -> T::T {
+        self.load::<T>(name).unwrap_or_default()
+    }
+
+    /// Like [store][super::store], within this sub category.
+    pub fn store<T: StorageCompatible>(&self, name: &str, value: T::T) -> Result<(), StorageError> {
+        let name = CString::new(name)?;
+        let mut bytes = T::encode(&value);
+        check_item_size(bytes.len())?;
+
+        let status = retry(|| unsafe {
+            sys::WUPSStorageAPI_StoreItem(
+                self.handle,
+                name.as_ptr(),
+                T::ITEM_TYPE,
+                bytes.as_mut_ptr() as *mut _,
+                bytes.len() as u32,
+            )
+        });
+        StorageError::try_from(status)?;
+        super::DIRTY.store(true, Ordering::Relaxed);
+        #[cfg(feature = "key-index")]
+        self.update_index(|index| index.insert(name, T::ITEM_TYPE) != Some(T::ITEM_TYPE))?;
+        Ok(())
+    }
+
+    /// Like [delete][super::delete], within this sub category.
+    pub fn delete(&self, name: &str) -> Result<(), StorageError> {
+        let name = CString::new(name)?;
+        let status = unsafe { sys::WUPSStorageAPI_DeleteItem(self.handle, name.as_ptr()) };
+        StorageError::try_from(status)?;
+        super::DIRTY.store(true, Ordering::Relaxed);
+        #[cfg(feature = "key-index")]
+        self.update_index(|index| index.remove(&name).is_some())?;
+        Ok(())
+    }
+
+    /// Like [keys][super::keys], within this sub category. Nested sub categories aren't included.
+    #[cfg(feature = "key-index")]
+    pub fn keys(&self) -> Result<Vec<String>, StorageError> {
+        Ok(self
+            .index()?
+            .into_keys()
+            .map(|name| String::from_utf8_lossy(name.to_bytes()).into_owned())
+            .collect())
+    }
+
+    /// Index of the keys in this sub category, which isn't cached unlike the top-level one.
+    #[cfg(feature = "key-index")]
+    fn index(&self) -> Result<BTreeMap<CString, sys::WUPSStorageItemTypes::Type>, StorageError> {
+        match self.read(
+            index::INDEX_KEY,
+            sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY,
+        ) {
+            Ok(data) => Ok(index::decode(&data)),
+            Err(StorageError::NotFound) => Ok(BTreeMap::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(feature = "key-index")]
+    fn update_index(
+        &self,
+        f: impl FnOnce(&mut BTreeMap<CString, sys::WUPSStorageItemTypes::Type>) -> bool,
+    ) -> Result<(), StorageError> {
+        let mut index = self.index()?;
+        if !f(&mut index) {
+            return Ok(());
+        }
+
+        let mut data = index::encode(&index);
+        let status = retry(|| unsafe {
+            sys::WUPSStorageAPI_StoreItem(
+                self.handle,
+                index::INDEX_KEY.as_ptr(),
+                sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_BINARY,
+                data.as_mut_ptr() as *mut _,
+                data.len() as u32,
+            )
+        });
+        StorageError::try_from(status)?;
+        Ok(())
+    }
+
+    /// All bytes of item `name`, see `read_bytes` of the top level.
+    fn read(
+        &self,
+        name: &CStr,
+        item_type: sys::WUPSStorageItemTypes::Type,
+    ) -> Result<Vec<u8>, StorageError> {
+        let get = |data: &mut [u8]| {
+            let mut out = 0;
+            let status = retry(|| unsafe {
+                sys::WUPSStorageAPI_GetItem(
+                    self.handle,
+                    name.as_ptr(),
+                    item_type,
+                    data.as_mut_ptr() as *mut _,
+                    data.len() as u32,
+                    &mut out,
+                )
+            });
+            StorageError::try_from(status).map(|_| out as usize)
+        };
+
+        let mut buffer = [0u8; STORAGE_MAX_LENGTH];
+        match get(&mut buffer) {
+            Ok(out) => return Ok(buffer[..out].to_vec()),
+            Err(StorageError::BufferTooSmall) => {}
+            Err(e) => return Err(e),
+        }
+
+        let mut size = 0;
+        let status = unsafe {
+            sys::WUPSStorageAPI_GetItemSize(self.handle, name.as_ptr(), item_type, &mut size)
+        };
+        StorageError::try_from(status)?;
+
+        let mut data = alloc::vec![0u8; size as usize];
+        let out = get(&mut data)?;
+        data.truncate(out);
+        Ok(data)
+    }
+}
+
+fn open(parent: sys::wups_storage_item, name: &str) -> Result<SubStore, StorageError> {
+    let name = CString::new(name)?;
+    let mut handle = core::ptr::null_mut();
+
+    let status = unsafe { sys::WUPSStorageAPI_GetSubItem(parent, name.as_ptr(), &mut handle) };
+    match StorageError::try_from(status) {
+        Ok(_) => return Ok(SubStore { handle }),
+        Err(StorageError::NotFound) => {}
+        Err(e) => return Err(e),
+    }
+
+    let status = unsafe { sys::WUPSStorageAPI_CreateSubItem(parent, name.as_ptr(), &mut handle) };
+    StorageError::try_from(status)?;
+    super::DIRTY.store(true, Ordering::Relaxed);
+    Ok(SubStore { handle })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{self, mock};
+    use wut::string::String;
+
+    #[test]
+    fn namespaces_dont_collide() {
+        let _lock = mock::lock();
+        let audio = SubStore::open("audio").unwrap();
+        let video = SubStore::open("video").unwrap();
+
+        audio.store::<bool>("enabled", true).unwrap();
+        video.store::<bool>("enabled", false).unwrap();
+        storage::store::<i32>("enabled", 2).unwrap();
+
+        assert_eq!(audio.load::<bool>("enabled"), Ok(true));
+        assert_eq!(video.load::<bool>("enabled"), Ok(false));
+        assert_eq!(storage::load::<i32>("enabled"), Ok(2));
+
+        video.delete("enabled").unwrap();
+        assert_eq!(audio.load::<bool>("enabled"), Ok(true));
+        assert_eq!(video.load::<bool>("enabled"), Err(StorageError::NotFound));
+    }
+
+    #[test]
+    fn reopens_and_nests() {
+        let _lock = mock::lock();
+        let audio = SubStore::open("audio").unwrap();
+        audio.store::<String>("device", "TV".into()).unwrap();
+        let music = audio.sub_store("music").unwrap();
+        music.store::<String>("device", "Gamepad".into()).unwrap();
+
+        let audio = SubStore::open("audio").unwrap();
+        assert_eq!(audio.load::<String>("device"), Ok("TV".into()));
+        let music = audio.sub_store("music").unwrap();
+        assert_eq!(music.load::<String>("device"), Ok("Gamepad".into()));
+        assert_eq!(music.load_or_default::<u32>("volume"), 0);
+    }
+}
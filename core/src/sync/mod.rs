@@ -0,0 +1,35 @@
+//! Synchronization Primitives
+//!
+//! `std::sync` isn't available in `no_std`. This module collects the primitives used by this crate
+//! in one place, so plugins can use the same types without depending on where they come from:
+//! [Mutex] and [OnceLock] from `wut`, which mirror the API of their `std` counterparts, together
+//! with [Arc] and [atomic] from `alloc`/`core`.
+//!
+//! # Semantics
+//!
+//! - [Mutex] and [OnceLock] can be created in `const` context, so they can be used for statics.
+//!   This is how the crate keeps its global state (see [reset_statics][crate::reset_statics]).
+//! - Hooks and callbacks may run on several cores at once. Locks block the calling thread, so
+//!   keep them short and never hold one while calling code which may take it again (e.g.
+//!   `hooked` in a `#[function_hook]`): a `Mutex` isn't reentrant and would deadlock.
+//! - `lock()` returns a `Result` like in `std`. Plugins are built with `panic = "abort"`, so a lock
+//!   can't be poisoned by a panicking thread and `lock().unwrap()` doesn't fail in practice.
+//! - Statics live as long as the plugin is loaded, across applications. They are not reset when
+//!   the plugin is reloaded unless they are covered by [reset_statics][crate::reset_statics].
+//!
+//! # Example
+//!
+//! ```
+//! use wups::sync::Mutex;
+//!
+//! static HISTORY: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+//!
+//! #[on_application_start]
+//! fn started() {
+//!     HISTORY.lock().unwrap().push(title_id());
+//! }
+//! ```
+
+pub use alloc::sync::Arc;
+pub use core::sync::atomic;
+pub use wut::sync::{Mutex, OnceLock};
@@ -0,0 +1,94 @@
+//! Call Timing
+//!
+//! Durations of calls to the original function of a `#[function_hook]` with the `timing`
+//! attribute. Calls are measured with `OSGetTime`, whose resolution is 1/62.15625 µs.
+//!
+//! # Example
+//!
+//! ```
+//! #[function_hook(module = GX2, function = GX2DrawDone, timing)]
+//! fn my_GX2DrawDone() {
+//!     unsafe { hooked() }
+//! }
+//!
+//! let timing = my_GX2DrawDone_timing();
+//! println!("{} calls, avg {:?}, max {:?}", timing.calls, timing.avg, timing.max);
+//! ```
+
+use crate::sync::Mutex;
+use core::time::Duration;
+
+/// Timer ticks per 8 milliseconds (bus clock / 4 / 125).
+const TICKS_PER_8_MS: u64 = 497_250;
+
+/// Summary of the measured calls.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub calls: u32,
+    pub min: Duration,
+    pub avg: Duration,
+    pub max: Duration,
+}
+
+#[derive(Clone, Copy)]
+struct Ticks {
+    calls: u32,
+    total: u64,
+    min: u64,
+    max: u64,
+}
+
+/// Durations of the calls of a single function. Generated by `function_hook`.
+#[doc(hidden)]
+pub struct Timer(Mutex<Ticks>);
+
+impl Timer {
+    pub const fn new() -> Self {
+        Self(Mutex::new(Ticks {
+            calls: 0,
+            total: 0,
+            min: u64::MAX,
+            max: 0,
+        }))
+    }
+
+    /// Current time in ticks, to be passed to [record][Timer::record] after the call.
+    pub fn start() -> i64 {
+        unsafe { wut::sys::OSGetTime() }
+    }
+
+    /// Record a call which began at `start`.
+    pub fn record(&self, start: i64) {
+        let ticks = (unsafe { wut::sys::OSGetTime() } - start).max(0) as u64;
+
+        let mut t = self.0.lock().unwrap();
+        t.calls = t.calls.saturating_add(1);
+        t.total = t.total.saturating_add(ticks);
+        t.min = t.min.min(ticks);
+        t.max = t.max.max(ticks);
+    }
+
+    pub fn timing(&self) -> Timing {
+        let t = *self.0.lock().unwrap();
+        if t.calls == 0 {
+            return Timing::default();
+        }
+
+        Timing {
+            calls: t.calls,
+            min: duration(t.min),
+            avg: duration(t.total / t.calls as u64),
+            max: duration(t.max),
+        }
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn duration(ticks: u64) -> Duration {
+    Duration::from_nanos((ticks as u128 * 8_000_000 / TICKS_PER_8_MS as u128) as u64)
+}
@@ -1,9 +1,21 @@
 #![allow(non_snake_case)]
 
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{quote, quote_spanned};
 use syn::{parse_macro_input, parse_quote, spanned::Spanned};
 
+// region: Sections
+
+// Sections read by the plugin loader. All generated statics use these, so the macros can't disagree
+// on where an entry has to be placed.
+const SECTION_META: &str = ".wups.meta";
+const SECTION_HOOKS: &str = ".wups.hooks";
+const SECTION_LOAD: &str = ".wups.load";
+/// Empty statics which only exist for their symbol names, see `function_hook`.
+const SECTION_HOOK_REGISTRY: &str = ".wups.hook_registry";
+
+// endregion
+
 // region: wups_meta
 
 struct Meta {
@@ -47,16 +59,16 @@ pub fn wups_meta(input: TokenStream) -> TokenStream {
     };
 
     let value = syn::LitByteStr::new(
-        format!("{}={}\0", name.to_string(), value_str).as_bytes(),
+        format!("{name}={value_str}\0").as_bytes(),
         value.span(),
     );
     let len = value.value().len();
-    let name = syn::Ident::new(&format!("wups_meta_{}", name.to_string()), name.span());
+    let name = syn::Ident::new(&format!("wups_meta_{name}"), name.span());
 
     TokenStream::from(quote! {
         #[used]
         #[unsafe(no_mangle)]
-        #[unsafe(link_section = ".wups.meta")]
+        #[unsafe(link_section = #SECTION_META)]
         #[allow(non_upper_case_globals)]
         static #name: [u8; #len] = *#value;
     })
@@ -83,6 +95,43 @@ impl syn::parse::Parse for Hook {
     }
 }
 
+/// Function pointer type the loader calls a hook of `hook_type` as, if it is known.
+fn hook_signature(hook_type: &str) -> Option<proc_macro2::TokenStream> {
+    match hook_type {
+        "INIT_STORAGE" => Some(quote! {
+            unsafe extern "C" fn(::wups::sys::wups_loader_init_storage_args_t_)
+        }),
+        "INIT_CONFIG" => Some(quote! {
+            unsafe extern "C" fn(::wups::sys::wups_loader_init_config_args_t)
+        }),
+        "INIT_WUT_MALLOC"
+        | "FINI_WUT_MALLOC"
+        | "INIT_WUT_NEWLIB"
+        | "FINI_WUT_NEWLIB"
+        | "INIT_WUT_STDCPP"
+        | "FINI_WUT_STDCPP"
+        | "INIT_WUT_DEVOPTAB"
+        | "FINI_WUT_DEVOPTAB"
+        | "INIT_WUT_SOCKETS"
+        | "FINI_WUT_SOCKETS"
+        | "INIT_WRAPPER"
+        | "FINI_WRAPPER"
+        | "INIT_PLUGIN"
+        | "DEINIT_PLUGIN"
+        | "APPLICATION_STARTS"
+        | "RELEASE_FOREGROUND"
+        | "ACQUIRED_FOREGROUND"
+        | "APPLICATION_REQUESTS_EXIT"
+        | "APPLICATION_ENDS" => Some(quote! { unsafe extern "C" fn() }),
+        _ => None,
+    }
+}
+
+/// Registers `target` as hook of the given type, e.g. `wups_hook_ex!("INIT_PLUGIN", my_init)`.
+///
+/// For known hook types, `target` must coerce to the function pointer type the loader calls it as
+/// (e.g. `unsafe extern "C" fn()`). A function with another ABI or signature is a compile error
+/// instead of corrupting the stack when it is called.
 #[proc_macro]
 pub fn wups_hook_ex(input: TokenStream) -> TokenStream {
     let Hook {
@@ -90,6 +139,13 @@ pub fn wups_hook_ex(input: TokenStream) -> TokenStream {
         hook_target,
     } = parse_macro_input!(input as Hook);
 
+    let signature_check = match hook_signature(&hook_type.value()) {
+        Some(signature) => quote_spanned! {hook_target.span()=>
+            const _: #signature = #hook_target;
+        },
+        None => quote! {},
+    };
+
     let hook_type: syn::ExprPath = syn::parse_str(&format!(
         "::wups::sys::wups_loader_hook_type_t::WUPS_LOADER_HOOK_{}",
         hook_type.value()
@@ -110,9 +166,11 @@ pub fn wups_hook_ex(input: TokenStream) -> TokenStream {
     );
 
     TokenStream::from(quote! {
+        #signature_check
+
         #[used]
         #[unsafe(no_mangle)]
-        #[unsafe(link_section = ".wups.hooks")]
+        #[unsafe(link_section = #SECTION_HOOKS)]
         #[allow(non_upper_case_globals)]
         static #name: ::wups::sys::wups_loader_hook_t = ::wups::sys::wups_loader_hook_t {
             type_: #hook_type,
@@ -123,6 +181,51 @@ pub fn wups_hook_ex(input: TokenStream) -> TokenStream {
 
 // endregion
 
+struct PluginName {
+    name: syn::LitStr,
+    features: Option<Vec<syn::Ident>>,
+}
+
+impl syn::parse::Parse for PluginName {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let mut features = None;
+
+        if input.parse::<Option<syn::Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key != "features" {
+                return Err(syn::Error::new(key.span(), "Expected: features = [...]"));
+            }
+            _ = input.parse::<syn::Token![=]>()?;
+
+            let content;
+            syn::bracketed!(content in input);
+            let list = syn::punctuated::Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated(
+                &content,
+            )?;
+            for feature in &list {
+                if !WUT_FEATURES.contains(&feature.to_string().as_str()) {
+                    return Err(syn::Error::new(
+                        feature.span(),
+                        format!(
+                            "Unknown feature. Expected one of: {}",
+                            WUT_FEATURES.join(", ")
+                        ),
+                    ));
+                }
+            }
+            features = Some(list.into_iter().collect());
+
+            _ = input.parse::<Option<syn::Token![,]>>()?;
+        }
+
+        Ok(Self { name, features })
+    }
+}
+
+/// WUT subsystems which can be initialized by the plugin loader.
+const WUT_FEATURES: [&str; 5] = ["malloc", "sockets", "newlib", "stdcpp", "devoptab"];
+
 /// Setup important WUPS meta information.
 ///
 /// **This is required to be called in all plugin!**
@@ -141,22 +244,52 @@ pub fn wups_hook_ex(input: TokenStream) -> TokenStream {
 /// ```
 /// WUPS_PLUGIN_NAME!("Rust Plugin");
 /// ```
+///
+/// # Statics
+///
+/// The macro also defines `PLUGIN_NAME` and `PLUGIN_VERSION` (from `CARGO_PKG_VERSION`) as
+/// `pub static &str` where it is called, e.g. for a config menu [Header][wups::config::Header]:
+///
+/// ```
+/// WUPS_PLUGIN_NAME!("Rust Plugin");
+///
+/// let header = config::Header::new(PLUGIN_NAME, PLUGIN_VERSION);
+/// ```
+///
+/// # WUT Features
+///
+/// By default, the init and fini hooks for all WUT subsystems (`malloc`, `sockets`, `newlib`,
+/// `stdcpp` and `devoptab`) are emitted. Plugins which don't need all of them can list the required
+/// ones to avoid linking the unused ones:
+///
+/// ```
+/// WUPS_PLUGIN_NAME!("Rust Plugin", features = [malloc, newlib]);
+/// ```
+///
+/// Omitted subsystems are **not initialized**. E.g. a plugin using sockets without listing
+/// `sockets` will fail on every socket call, so only drop what you're sure isn't used (including
+/// by dependencies).
 #[proc_macro]
 pub fn WUPS_PLUGIN_NAME(input: TokenStream) -> TokenStream {
     let mut stream = TokenStream::new();
 
     // region: WUPS_META name, description, version, license, buildtimestamp
 
-    let name = parse_macro_input!(input as syn::LitStr);
+    let PluginName { name, features } = parse_macro_input!(input as PluginName);
+    let enabled = |feature: &str| {
+        features
+            .as_ref()
+            .is_none_or(|features| features.iter().any(|f| f == feature))
+    };
     let buildtimestamp = chrono::Utc::now().format("%b %d %Y %H:%M:%S").to_string(); // format as: "Feb 12 1996 23:59:01"
 
     stream.extend(TokenStream::from(quote! {
-        ::wups::wups_meta!(name, #name);
-        ::wups::wups_meta!(description, env!("CARGO_PKG_DESCRIPTION"));
-        ::wups::wups_meta!(version, env!("CARGO_PKG_VERSION"));
-        ::wups::wups_meta!(author, env!("CARGO_PKG_AUTHORS"));
-        ::wups::wups_meta!(license, env!("CARGO_PKG_LICENSE"));
-        ::wups::wups_meta!(buildtimestamp, #buildtimestamp);
+        ::wups::macros::wups_meta!(name, #name);
+        ::wups::macros::wups_meta!(description, env!("CARGO_PKG_DESCRIPTION"));
+        ::wups::macros::wups_meta!(version, env!("CARGO_PKG_VERSION"));
+        ::wups::macros::wups_meta!(author, env!("CARGO_PKG_AUTHORS"));
+        ::wups::macros::wups_meta!(license, env!("CARGO_PKG_LICENSE"));
+        ::wups::macros::wups_meta!(buildtimestamp, #buildtimestamp);
     }));
 
     // endregion
@@ -166,7 +299,7 @@ pub fn WUPS_PLUGIN_NAME(input: TokenStream) -> TokenStream {
     stream.extend(TokenStream::from(quote! {
         #[used]
         #[unsafe(no_mangle)]
-        #[unsafe(link_section = ".wups.meta")]
+        #[unsafe(link_section = #SECTION_META)]
         #[allow(non_upper_case_globals)]
         static wups_meta_wups: [u8; ::wups::sys::WUPS_VERSION_STR.to_bytes_with_nul().len()+5] = {
             let bytes = ::wups::sys::WUPS_VERSION_STR.to_bytes_with_nul();
@@ -190,135 +323,148 @@ pub fn WUPS_PLUGIN_NAME(input: TokenStream) -> TokenStream {
 
     // region: WUPS_USE_WUT_MALLOC
 
-    stream.extend(TokenStream::from(quote! {
-        extern "C" {
-            fn __init_wut_malloc();
-            fn __fini_wut_malloc();
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_init_wut_malloc() {
-            __init_wut_malloc();
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_fini_wut_malloc() {
-            __fini_wut_malloc();
-        }
+    if enabled("malloc") {
+        stream.extend(TokenStream::from(quote! {
+            unsafe extern "C" {
+                fn __init_wut_malloc();
+                fn __fini_wut_malloc();
+            }
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_init_wut_malloc() {
+                __init_wut_malloc();
+            }
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_fini_wut_malloc() {
+                __fini_wut_malloc();
+            }
 
-        ::wups::wups_hook_ex!("INIT_WUT_MALLOC", on_init_wut_malloc);
-        ::wups::wups_hook_ex!("FINI_WUT_MALLOC", on_fini_wut_malloc);
-    }));
+            ::wups::macros::wups_hook_ex!("INIT_WUT_MALLOC", on_init_wut_malloc);
+            ::wups::macros::wups_hook_ex!("FINI_WUT_MALLOC", on_fini_wut_malloc);
+        }));
+    }
 
     // endregion
 
     // region: WUPS_USE_WUT_SOCKETS
 
-    stream.extend(TokenStream::from(quote! {
-        extern "C" {
-            // #[linkage="weak"]
-            fn __init_wut_socket();
-            // #[linkage="weak"]
-            fn __fini_wut_socket();
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_init_wut_sockets() {
-            if __init_wut_socket as *const () != ::core::ptr::null() {
-                __init_wut_socket();
+    if enabled("sockets") {
+        stream.extend(TokenStream::from(quote! {
+            unsafe extern "C" {
+                // #[linkage="weak"]
+                fn __init_wut_socket();
+                // #[linkage="weak"]
+                fn __fini_wut_socket();
             }
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_fini_wut_sockets() {
-            if __fini_wut_socket as *const () != ::core::ptr::null() {
-                __fini_wut_socket();
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_init_wut_sockets() {
+                if __init_wut_socket as *const () != ::core::ptr::null() {
+                    __init_wut_socket();
+                }
+            }
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_fini_wut_sockets() {
+                if __fini_wut_socket as *const () != ::core::ptr::null() {
+                    __fini_wut_socket();
+                }
             }
-        }
 
-        ::wups::wups_hook_ex!("INIT_WUT_SOCKETS", on_init_wut_sockets);
-        ::wups::wups_hook_ex!("FINI_WUT_SOCKETS", on_fini_wut_sockets);
-    }));
+            ::wups::macros::wups_hook_ex!("INIT_WUT_SOCKETS", on_init_wut_sockets);
+            ::wups::macros::wups_hook_ex!("FINI_WUT_SOCKETS", on_fini_wut_sockets);
+        }));
+    }
 
     // endregion
 
     // region: WUPS_USE_WUT_NEWLIB
 
-    stream.extend(TokenStream::from(quote! {
-        extern "C" {
-            fn __init_wut_newlib();
-            fn __fini_wut_newlib();
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_init_wut_newlib() {
-            __init_wut_newlib();
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_fini_wut_newlib() {
-            __fini_wut_newlib();
-        }
+    if enabled("newlib") {
+        stream.extend(TokenStream::from(quote! {
+            unsafe extern "C" {
+                fn __init_wut_newlib();
+                fn __fini_wut_newlib();
+            }
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_init_wut_newlib() {
+                __init_wut_newlib();
+            }
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_fini_wut_newlib() {
+                __fini_wut_newlib();
+            }
 
-        ::wups::wups_hook_ex!("INIT_WUT_NEWLIB", on_init_wut_newlib);
-        ::wups::wups_hook_ex!("FINI_WUT_NEWLIB", on_fini_wut_newlib);
-    }));
+            ::wups::macros::wups_hook_ex!("INIT_WUT_NEWLIB", on_init_wut_newlib);
+            ::wups::macros::wups_hook_ex!("FINI_WUT_NEWLIB", on_fini_wut_newlib);
+        }));
+    }
 
     // endregion
 
     // region: WUPS_USE_WUT_STDCPP
 
-    stream.extend(TokenStream::from(quote! {
-        extern "C" {
-            fn __init_wut_stdcpp();
-            fn __fini_wut_stdcpp();
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_init_wut_stdcpp() {
-            __init_wut_stdcpp();
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_fini_wut_stdcpp() {
-            __fini_wut_stdcpp();
-        }
+    if enabled("stdcpp") {
+        stream.extend(TokenStream::from(quote! {
+            unsafe extern "C" {
+                fn __init_wut_stdcpp();
+                fn __fini_wut_stdcpp();
+            }
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_init_wut_stdcpp() {
+                __init_wut_stdcpp();
+            }
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_fini_wut_stdcpp() {
+                __fini_wut_stdcpp();
+            }
 
-        ::wups::wups_hook_ex!("INIT_WUT_STDCPP", on_init_wut_stdcpp);
-        ::wups::wups_hook_ex!("FINI_WUT_STDCPP", on_fini_wut_stdcpp);
-    }));
+            ::wups::macros::wups_hook_ex!("INIT_WUT_STDCPP", on_init_wut_stdcpp);
+            ::wups::macros::wups_hook_ex!("FINI_WUT_STDCPP", on_fini_wut_stdcpp);
+        }));
+    }
     // endregion
 
     // region: WUPS_USE_WUT_DEVOPTAB
 
-    stream.extend(TokenStream::from(quote! {
-        extern "C" {
-            fn __init_wut_devoptab();
-            fn __fini_wut_devoptab();
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_init_wut_devoptab() {
-            __init_wut_stdcpp();
-        }
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn on_fini_wut_devoptab() {
-            __fini_wut_stdcpp();
-        }
+    if enabled("devoptab") {
+        stream.extend(TokenStream::from(quote! {
+            unsafe extern "C" {
+                fn __init_wut_devoptab();
+                fn __fini_wut_devoptab();
+            }
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_init_wut_devoptab() {
+                __init_wut_devoptab();
+            }
+            #[unsafe(no_mangle)]
+            unsafe extern "C" fn on_fini_wut_devoptab() {
+                __fini_wut_devoptab();
+            }
 
-        ::wups::wups_hook_ex!("INIT_WUT_DEVOPTAB", on_init_wut_devoptab);
-        ::wups::wups_hook_ex!("FINI_WUT_DEVOPTAB", on_fini_wut_devoptab);
-    }));
+            ::wups::macros::wups_hook_ex!("INIT_WUT_DEVOPTAB", on_init_wut_devoptab);
+            ::wups::macros::wups_hook_ex!("FINI_WUT_DEVOPTAB", on_fini_wut_devoptab);
+        }));
+    }
 
     // endregion
 
     // region: WUPS___INIT_WRAPPER & WUPS___FINI_WRAPPER
 
     stream.extend(TokenStream::from(quote! {
-        extern "C" {
+        unsafe extern "C" {
             fn __init();
             fn __fini();
         }
         #[unsafe(no_mangle)]
         unsafe extern "C" fn __init_wrapper() {
-            if ::wups::sys::wut_get_thread_specific(0x13371337) != 0x42424242 {
+            if !::wups::verify_init_token() {
                 ::wups::sys::OSFatal(wups_meta_info_linking_order.as_ptr() as *const _);
             }
             __init();
         }
         #[unsafe(no_mangle)]
         unsafe extern "C" fn __fini_wrapper() {
+            // a debounced save request would be lost otherwise
+            let _ = ::wups::storage::flush_save_request();
+            ::wups::reset_statics();
             __fini();
         }
     }));
@@ -341,20 +487,9 @@ pub fn WUPS_PLUGIN_NAME(input: TokenStream) -> TokenStream {
 
     // region: WUPS_INIT_CONFIG_FUNCTIONS
 
+    // only emitted if the `config` feature of wups is enabled, which isn't visible from here
     stream.extend(TokenStream::from(quote! {
-        extern "C" {
-            fn WUPSConfigAPI_InitLibrary_Internal(
-                args: ::wups::sys::wups_loader_init_config_args_t,
-            ) -> ::wups::sys::WUPSConfigAPIStatus::Type;
-        }
-
-        #[unsafe(no_mangle)]
-        unsafe extern "C" fn wups_init_config_functions(args: ::wups::sys::wups_loader_init_config_args_t) {
-            WUPSConfigAPI_InitLibrary_Internal(args);
-        }
-
-        ::wups::macros::wups_hook_ex!("INIT_CONFIG", wups_init_config_functions);
-
+        ::wups::__init_config!();
     }));
 
     // endregion
@@ -374,6 +509,7 @@ pub fn WUPS_PLUGIN_NAME(input: TokenStream) -> TokenStream {
             if s != ::wups::sys::WUPSStorageError::WUPS_STORAGE_ERROR_SUCCESS {
                 panic!("Storage initialization failed: {:?}\n{:?}", ::wups::storage::StorageError::try_from(s), args.version as i32);
             }
+            ::wups::storage::set_initialized();
         }
 
         ::wups::macros::wups_hook_ex!("INIT_STORAGE", init_storage);
@@ -390,7 +526,7 @@ pub fn WUPS_PLUGIN_NAME(input: TokenStream) -> TokenStream {
     stream.extend(TokenStream::from(quote! {
         #[used]
         #[unsafe(no_mangle)]
-        #[unsafe(link_section = ".wups.meta")]
+        #[unsafe(link_section = #SECTION_META)]
         #[allow(non_upper_case_globals)]
         pub static wups_meta_plugin_name: [u8; #len] = *#plugin_name;
     }));
@@ -398,6 +534,7 @@ pub fn WUPS_PLUGIN_NAME(input: TokenStream) -> TokenStream {
     let plugin_name = syn::LitStr::new(name.value().as_str(), name.span());
     stream.extend(TokenStream::from(quote! {
         pub static PLUGIN_NAME: &str = #plugin_name;
+        pub static PLUGIN_VERSION: &str = env!("CARGO_PKG_VERSION");
     }));
 
     // endregion
@@ -445,10 +582,80 @@ pub fn WUPS_PLUGIN_NAME(input: TokenStream) -> TokenStream {
     stream
 }
 
+// region: WUPS_PLUGIN_TAGS
+
+/// Advertise capability tags of the plugin, e.g. for filtering in a plugin browser.
+///
+/// The tags are emitted as a single `tags=<tag>,<tag>,...` entry into the plugin's metadata (like
+/// the name and version), which the loader keeps but doesn't interpret. Can be used once per plugin.
+///
+/// # Tag format
+///
+/// Tags consist of lowercase ASCII letters, digits, `-`, `_` and `.`. A tag may be split into a
+/// key and a value with `=`, e.g. `category=performance` or `requires=network`. Plain tags like
+/// `input` are keys without a value.
+///
+/// # Example
+///
+/// ```
+/// WUPS_PLUGIN_NAME!("Rust Plugin");
+/// WUPS_PLUGIN_TAGS!(["performance", "input", "requires=network"]);
+/// ```
+#[proc_macro]
+pub fn WUPS_PLUGIN_TAGS(input: TokenStream) -> TokenStream {
+    let tags = parse_macro_input!(input as syn::ExprArray);
+
+    let mut values = Vec::new();
+    for tag in &tags.elems {
+        let syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(tag),
+            ..
+        }) = tag
+        else {
+            return syn::Error::new(tag.span(), "Expected a string literal")
+                .to_compile_error()
+                .into();
+        };
+
+        let value = tag.value();
+        let valid = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || "-_.=".contains(c);
+        if value.is_empty() || !value.chars().all(valid) || value.matches('=').count() > 1 {
+            return syn::Error::new(
+                tag.span(),
+                "Tags consist of `a-z`, `0-9`, `-`, `_` and `.`, optionally split by one `=`",
+            )
+            .to_compile_error()
+            .into();
+        }
+        values.push(value);
+    }
+
+    let tags = syn::LitStr::new(&values.join(","), tags.span());
+    wups_meta(
+        quote! {
+            tags, #tags
+        }
+        .into(),
+    )
+}
+
+// endregion
+
 fn generate_proc_macro_attribute(
     hook_type: &str,
     attr: TokenStream,
     item: TokenStream,
+) -> TokenStream {
+    generate_hook(hook_type, attr, item, quote! {})
+}
+
+/// Like [generate_proc_macro_attribute], but runs `epilogue` after the user's block (even if it
+/// returns early) and before the logger is deinitialized.
+fn generate_hook(
+    hook_type: &str,
+    attr: TokenStream,
+    item: TokenStream,
+    epilogue: proc_macro2::TokenStream,
 ) -> TokenStream {
     let args =
         parse_macro_input!(attr with syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)
@@ -483,17 +690,27 @@ fn generate_proc_macro_attribute(
         quote! {}
     };
 
+    let body = if epilogue.is_empty() {
+        quote! { #block }
+    } else {
+        quote! {
+            #[allow(clippy::redundant_closure_call)]
+            (|| #block)();
+            #epilogue
+        }
+    };
+
     let hook_type = syn::LitStr::new(hook_type, hook_type.span());
 
     TokenStream::from(quote! {
         #[unsafe(no_mangle)]
         extern "C" fn #func() {
             #logger_init
-            #block
+            #body
             #logger_deinit
         }
 
-        ::wups::wups_hook_ex!(#hook_type, #func);
+        ::wups::macros::wups_hook_ex!(#hook_type, #func);
     })
 }
 
@@ -504,9 +721,44 @@ pub fn on_initialize(attr: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 /// Called when plugin is unloaded.
+///
+/// # Saving storage
+///
+/// With the `save_storage` marker, `wups::storage::save(true)` is called after the function body
+/// (also if it returns early), so changes which weren't saved yet aren't lost when the plugin is
+/// unloaded. The body still runs first and can store final values. Errors while saving are
+/// ignored, as there is no one left to report them to.
+///
+/// Leave it out if the plugin saves on its own, to avoid writing the file twice.
+///
+/// ```
+/// #[on_deinitialize(save_storage, Udp)]
+/// fn deinit() {
+///     storage::store::<u32>("launches", launches).unwrap();
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn on_deinitialize(attr: TokenStream, item: TokenStream) -> TokenStream {
-    generate_proc_macro_attribute("DEINIT_PLUGIN", attr, item)
+    let args = parse_macro_input!(attr with syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated);
+
+    let (save, channels): (Vec<_>, Vec<_>) = args
+        .into_iter()
+        .partition(|arg| arg.path().is_ident("save_storage"));
+
+    let epilogue = if save.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            let _ = ::wups::storage::save(true);
+        }
+    };
+
+    generate_hook(
+        "DEINIT_PLUGIN",
+        quote! { #(#channels),* }.into(),
+        item,
+        epilogue,
+    )
 }
 
 /// Called when an (foreground?) application is opened.
@@ -539,6 +791,120 @@ pub fn on_application_exit(attr: TokenStream, item: TokenStream) -> TokenStream
     generate_proc_macro_attribute("APPLICATION_ENDS", attr, item)
 }
 
+/// Pairs [on_application_start] and [on_application_exit] around a shared application state.
+///
+/// Applied to the state type. `start` is called when an application starts and returns the state,
+/// `exit` receives the state by value when the application ends. In between, the state can be
+/// accessed via the generated `with` function, which returns `None` while no application is
+/// running.
+///
+/// The state lives from `APPLICATION_STARTS` until `APPLICATION_ENDS` and is created anew for every
+/// application. Logger channels can be passed like for the other hooks.
+///
+/// # Example
+///
+/// ```
+/// #[on_application(start = setup, exit = teardown, Udp)]
+/// struct Session {
+///     frames: u32,
+/// }
+///
+/// fn setup() -> Session {
+///     Session { frames: 0 }
+/// }
+///
+/// fn teardown(session: Session) {
+///     println!("{} frames", session.frames);
+/// }
+///
+/// // e.g. inside a function hook
+/// Session::with(|session| session.frames += 1);
+/// ```
+#[proc_macro_attribute]
+pub fn on_application(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr with syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated);
+
+    let mut start = None;
+    let mut exit = None;
+    let mut channels = Vec::new();
+
+    for arg in args {
+        match arg {
+            syn::Meta::NameValue(nv) if nv.path.is_ident("start") => start = Some(nv.value),
+            syn::Meta::NameValue(nv) if nv.path.is_ident("exit") => exit = Some(nv.value),
+            syn::Meta::Path(path) => channels.push(path),
+            arg => {
+                return syn::Error::new(
+                    arg.span(),
+                    "Expected: start = ..., exit = ..., Cafe, Console, Module, Udp",
+                )
+                .to_compile_error()
+                .into();
+            }
+        }
+    }
+
+    let input = parse_macro_input!(item as syn::DeriveInput);
+    let (Some(start), Some(exit)) = (start, exit) else {
+        return syn::Error::new(input.span(), "Expected both `start = ...` and `exit = ...`")
+            .to_compile_error()
+            .into();
+    };
+
+    let ty = &input.ident;
+    let state = syn::Ident::new(&format!("__WUPS_APPLICATION_STATE_{}", ty), ty.span());
+    let start_func = syn::Ident::new(&format!("__wups_application_start_{}", ty), ty.span());
+    let exit_func = syn::Ident::new(&format!("__wups_application_exit_{}", ty), ty.span());
+
+    let mut stream = TokenStream::from(quote! {
+        #input
+
+        #[allow(non_upper_case_globals)]
+        static #state: ::wups::sync::OnceLock<::wups::sync::Mutex<Option<#ty>>> =
+            ::wups::sync::OnceLock::new();
+
+        impl #ty {
+            /// Access the state of the running application.
+            ///
+            /// Returns `None` if no application is running.
+            pub fn with<R>(f: impl FnOnce(&mut Self) -> R) -> Option<R> {
+                let mut state = #state.get()?.lock().unwrap();
+                state.as_mut().map(f)
+            }
+        }
+    });
+
+    let channels = quote! { #(#channels),* };
+
+    stream.extend(generate_proc_macro_attribute(
+        "APPLICATION_STARTS",
+        channels.clone().into(),
+        quote! {
+            fn #start_func() {
+                let state = #start();
+                *#state.get_or_init(|| ::wups::sync::Mutex::new(None)).lock().unwrap() = Some(state);
+            }
+        }
+        .into(),
+    ));
+
+    stream.extend(generate_proc_macro_attribute(
+        "APPLICATION_ENDS",
+        channels.into(),
+        quote! {
+            fn #exit_func() {
+                let state = #state.get().and_then(|state| state.lock().unwrap().take());
+                if let Some(state) = state {
+                    #exit(state);
+                }
+            }
+        }
+        .into(),
+    ));
+
+    stream
+}
+
 /// A macro to hook a WUT function.
 ///
 /// Provides lightweight access to WUT functions which would degrade performance when called from within a plugin. Additionally allows to completely overwrite function behavior.
@@ -551,6 +917,9 @@ pub fn on_application_exit(attr: TokenStream, item: TokenStream) -> TokenStream
 ///
 /// - `module`: One of `wups::sys::wups_loader_library_type_t`.
 /// - `function`: A function from the respective `module` which should be hooked.
+/// - `timing` (optional): Measure how long the calls to `hooked` take, see below.
+/// - `state = <type>` (optional): Keep state of the given type across calls, see below.
+/// - `process = Game | Menu | All` (optional): Processes the hook is installed in, see below.
 ///
 /// # Example
 ///
@@ -567,35 +936,209 @@ pub fn on_application_exit(attr: TokenStream, item: TokenStream) -> TokenStream
 ///     status
 /// }
 /// ```
+///
+/// # Duplicate hooks
+///
+/// A function can only be hooked once per plugin; which replacement the loader would use is
+/// undefined. Every hook defines the empty static
+/// `__wups_duplicate_function_hook__<module>__<function>` (in the section `.wups.hook_registry`),
+/// so hooking the same function twice fails to build with an error naming it, e.g.
+///
+/// ```text
+/// error: symbol `__wups_duplicate_function_hook__VPAD__VPADRead` is already defined
+/// ```
+///
+/// The error comes from code generation, so `cargo check` doesn't report it. If the hooks are in
+/// different crates of the plugin, the linker reports it as a multiple definition instead. Combine
+/// the hooks into one, e.g. with a body which calls both.
+///
+/// # Toggling
+///
+/// Every hook gets a function `<name>_set_enabled(bool)` (e.g. `my_VPADRead_set_enabled`) with the
+/// visibility of the hook itself. Hooks are **enabled** by default. While disabled, the body isn't
+/// run at all: every call is passed through to `hooked` with the original arguments and its result
+/// is returned unchanged. The function replacement itself stays installed.
+///
+/// ```
+/// // e.g. from the close callback of the config menu
+/// my_VPADRead_set_enabled(storage::load_or_default::<bool>("vpad_patch"));
+/// ```
+///
+/// # Timing
+///
+/// With `timing`, every call to `hooked` is timed and the hook gets a function `<name>_timing()`
+/// returning a [Timing](../wups/timing/struct.Timing.html) with the call count and the
+/// min/avg/max duration. Only the original function is measured, not the body of the hook.
+///
+/// ```
+/// #[function_hook(module = GX2, function = GX2DrawDone, timing)]
+/// fn my_GX2DrawDone() {
+///     unsafe { hooked() }
+/// }
+///
+/// let timing = my_GX2DrawDone_timing();
+/// ```
+///
+/// # State
+///
+/// With `state = T`, the hook gets a function `<name>_state()` returning a
+/// `&'static Mutex<T>`, so the body can keep data between calls (e.g. a frame counter or a
+/// smoothing filter) without declaring its own global. `T` must implement `Default` and `Send`.
+///
+/// The state is created with `T::default()` on the first call of `<name>_state()` and then lives
+/// as long as the plugin; it isn't reset between applications. Hooks can be called from several
+/// threads (and cores) at once, so only hold the lock briefly and not while calling `hooked`, which
+/// may end up calling the hook again.
+///
+/// ```
+/// #[derive(Default)]
+/// struct Smoothing {
+///     frames: u32,
+///     stick_x: f32,
+/// }
+///
+/// #[function_hook(module = VPAD, function = VPADRead, state = Smoothing)]
+/// fn my_VPADRead(
+///     chan: ::wut::sys::VPADChan::Type,
+///     buffers: *mut ::wut::sys::VPADStatus,
+///     count: u32,
+///     error: *mut ::wut::sys::VPADReadError::Type,
+/// ) -> i32 {
+///     let read = unsafe { hooked(chan, buffers, count, error) };
+///     if read > 0 {
+///         let status = unsafe { &mut *buffers };
+///         let mut state = my_VPADRead_state().lock().unwrap();
+///         state.frames += 1;
+///         state.stick_x = state.stick_x * 0.8 + status.leftStick.x * 0.2;
+///         status.leftStick.x = state.stick_x;
+///     }
+///     read
+/// }
+/// ```
+///
+/// # Target process
+///
+/// By default, hooks are installed while a game or the Wii U Menu runs
+/// (`WUPS_FP_TARGET_PROCESS_GAME_AND_MENU`). `process` restricts them to one of both or extends
+/// them to every process:
+///
+/// - `Game`: Only the foreground game (`WUPS_FP_TARGET_PROCESS_GAME`).
+/// - `Menu`: Only the Wii U Menu (`WUPS_FP_TARGET_PROCESS_WII_U_MENU`).
+/// - `All`: Every process, including e.g. the settings or the browser
+///   (`WUPS_FP_TARGET_PROCESS_ALL`).
+///
+/// In other processes the original function is called as if the hook didn't exist.
+///
+/// ```
+/// #[function_hook(module = VPAD, function = VPADRead, process = Game)]
+/// fn my_VPADRead(
+///     chan: ::wut::sys::VPADChan::Type,
+///     buffers: *mut ::wut::sys::VPADStatus,
+///     count: u32,
+///     error: *mut ::wut::sys::VPADReadError::Type,
+/// ) -> i32 {
+///     unsafe { hooked(chan, buffers, count, error) }
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn function_hook(attr: TokenStream, item: TokenStream) -> TokenStream {
     // region: Attributes
 
     struct Attributes {
         module: syn::Path,
+        /// `module` as written, e.g. `VPAD`.
+        module_name: syn::Ident,
         function: syn::Ident,
+        timing: bool,
+        state: Option<syn::Type>,
+        /// Variant of `WUPSFPTargetProcess`.
+        process: syn::Path,
     }
 
     impl syn::parse::Parse for Attributes {
         fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-            input.parse::<syn::Ident>()?; // Expect `module`
-            input.parse::<syn::Token![=]>()?; // Expect `=`
-            let module: syn::Ident = input.parse()?; // Expect module name
+            let mut module = None;
+            let mut function = None;
+            let mut timing = false;
+            let mut state = None;
+            let mut process = None;
+
+            // `key = value` pairs and flags, in any order
+            while !input.is_empty() {
+                let key: syn::Ident = input.parse()?;
+                match key.to_string().as_str() {
+                    "module" => {
+                        input.parse::<syn::Token![=]>()?;
+                        module = Some(input.parse::<syn::Ident>()?);
+                    }
+                    "function" => {
+                        input.parse::<syn::Token![=]>()?;
+                        function = Some(input.parse::<syn::Ident>()?);
+                    }
+                    "timing" => timing = true,
+                    "state" => {
+                        input.parse::<syn::Token![=]>()?;
+                        state = Some(input.parse::<syn::Type>()?);
+                    }
+                    "process" => {
+                        input.parse::<syn::Token![=]>()?;
+                        let value = input.parse::<syn::Ident>()?;
+                        let variant = match value.to_string().as_str() {
+                            "Game" => "WUPS_FP_TARGET_PROCESS_GAME",
+                            "Menu" => "WUPS_FP_TARGET_PROCESS_WII_U_MENU",
+                            "All" => "WUPS_FP_TARGET_PROCESS_ALL",
+                            _ => {
+                                return Err(syn::Error::new(
+                                    value.span(),
+                                    "Expected one of: Game, Menu, All",
+                                ));
+                            }
+                        };
+                        process = Some(syn::Ident::new(variant, value.span()));
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "Expected one of: module = ..., function = ..., timing, state = ..., process = ...",
+                        ));
+                    }
+                }
+
+                if !input.is_empty() {
+                    input.parse::<syn::Token![,]>()?;
+                }
+            }
 
-            input.parse::<syn::Token![,]>()?; // Expect `,`
-            input.parse::<syn::Ident>()?; // Expect `function`
-            input.parse::<syn::Token![=]>()?; // Expect `=`
-            let function: syn::Ident = input.parse()?; // Expect function name
+            let module = module.ok_or_else(|| input.error("Missing: module = ..."))?;
+            let function = function.ok_or_else(|| input.error("Missing: function = ..."))?;
 
+            let module_name = module;
             let module = syn::Ident::new(
-                &format!("WUPS_LOADER_LIBRARY_{}", module.to_string()),
-                module.span(),
+                &format!("WUPS_LOADER_LIBRARY_{module_name}"),
+                module_name.span(),
             );
             let module = parse_quote! {
                 ::wups::sys::wups_loader_library_type_t::#module
             };
 
-            Ok(Self { module, function })
+            let process = process.unwrap_or_else(|| {
+                syn::Ident::new(
+                    "WUPS_FP_TARGET_PROCESS_GAME_AND_MENU",
+                    proc_macro2::Span::call_site(),
+                )
+            });
+            let process = parse_quote! {
+                ::wups::sys::WUPSFPTargetProcess::#process
+            };
+
+            Ok(Self {
+                module,
+                module_name,
+                function,
+                timing,
+                state,
+                process,
+            })
         }
     }
 
@@ -606,11 +1149,22 @@ pub fn function_hook(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let mut stream = TokenStream::new();
 
-    let real_func = syn::Ident::new(
-        &format!("real_{}", attr.function.to_string()),
-        attr.function.span(),
-    );
-    let signature = &item.sig.inputs;
+    // arguments are renamed, so they can be passed through to `hooked` (and appear in function
+    // pointer types) regardless of their patterns
+    let mut func = item.sig.clone();
+    let mut args = Vec::new();
+    let mut bindings = Vec::new();
+    for (i, input) in func.inputs.iter_mut().enumerate() {
+        if let syn::FnArg::Typed(pat_type) = input {
+            let arg = syn::Ident::new(&format!("__wups_arg{i}"), pat_type.pat.span());
+            let pat = core::mem::replace(&mut *pat_type.pat, parse_quote! { #arg });
+            bindings.push(quote! { let #pat = #arg; });
+            args.push(arg);
+        }
+    }
+
+    let real_func = syn::Ident::new(&format!("real_{}", attr.function), attr.function.span());
+    let signature = &func.inputs;
     let output = &item.sig.output;
 
     stream.extend(TokenStream::from(quote! {
@@ -623,9 +1177,22 @@ pub fn function_hook(attr: TokenStream, item: TokenStream) -> TokenStream {
         > = None;
     }));
 
-    let func = &item.sig;
     let block = &item.block;
 
+    let enabled_flag = syn::Ident::new(
+        &format!("__wups_enabled_{}", item.sig.ident),
+        item.sig.ident.span(),
+    );
+    let set_enabled = syn::Ident::new(
+        &format!("{}_set_enabled", item.sig.ident),
+        item.sig.ident.span(),
+    );
+    let vis = &item.vis;
+    let set_enabled_doc = format!(
+        "Enable or disable the hook `{}`. While disabled, calls are passed through to `{}` unchanged.",
+        item.sig.ident, attr.function
+    );
+
     let wrapped_func = &attr.function;
     let wrapped_func: syn::Path = parse_quote! {
         ::wut::sys::#wrapped_func
@@ -633,10 +1200,100 @@ pub fn function_hook(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let wrapped_func_name = syn::LitStr::new(&attr.function.to_string(), attr.function.span());
 
+    // with `timing`, `hooked` is a wrapper which measures the call
+    let (hooked, timing) = if attr.timing {
+        let timer = syn::Ident::new(
+            &format!("__wups_timer_{}", item.sig.ident),
+            item.sig.ident.span(),
+        );
+        let timing_fn =
+            syn::Ident::new(&format!("{}_timing", item.sig.ident), item.sig.ident.span());
+        let timing_doc = format!(
+            "Durations of the calls to `{}` made by the hook `{}`.",
+            attr.function, item.sig.ident
+        );
+
+        let timing = quote! {
+            #[allow(non_upper_case_globals)]
+            static #timer: ::wups::timing::Timer = ::wups::timing::Timer::new();
+
+            #[doc = #timing_doc]
+            #[allow(non_snake_case)]
+            #vis fn #timing_fn() -> ::wups::timing::Timing {
+                #timer.timing()
+            }
+        };
+
+        let hooked = quote! {
+            unsafe fn hooked(#signature) #output {
+                let real = unsafe { #real_func.expect(&format!("The function \"{}\" was not properly hooked.", #wrapped_func_name)) };
+                let start = ::wups::timing::Timer::start();
+                let result = unsafe { real(#(#args),*) };
+                #timer.record(start);
+                result
+            }
+        };
+
+        (hooked, timing)
+    } else {
+        let hooked = quote! {
+            let hooked = unsafe { #real_func.expect(&format!("The function \"{}\" was not properly hooked.", #wrapped_func_name)) };
+        };
+
+        (hooked, quote! {})
+    };
+
+    let state = match &attr.state {
+        Some(ty) => {
+            let cell = syn::Ident::new(
+                &format!("__wups_state_{}", item.sig.ident),
+                item.sig.ident.span(),
+            );
+            let state_fn =
+                syn::Ident::new(&format!("{}_state", item.sig.ident), item.sig.ident.span());
+            let state_doc = format!(
+                "State of the hook `{}`, created with `Default::default()` on first access.",
+                item.sig.ident
+            );
+
+            quote_spanned! {ty.span()=>
+                #[allow(non_upper_case_globals)]
+                static #cell: ::wups::sync::OnceLock<::wups::sync::Mutex<#ty>> =
+                    ::wups::sync::OnceLock::new();
+
+                #[doc = #state_doc]
+                #[allow(non_snake_case)]
+                #vis fn #state_fn() -> &'static ::wups::sync::Mutex<#ty> {
+                    #cell.get_or_init(|| ::wups::sync::Mutex::new(<#ty as ::core::default::Default>::default()))
+                }
+            }
+        }
+        None => quote! {},
+    };
+
     stream.extend(TokenStream::from(quote! {
+        #[allow(non_upper_case_globals)]
+        static #enabled_flag: ::core::sync::atomic::AtomicBool =
+            ::core::sync::atomic::AtomicBool::new(true);
+
+        #[doc = #set_enabled_doc]
+        #[allow(non_snake_case)]
+        #vis fn #set_enabled(enabled: bool) {
+            #enabled_flag.store(enabled, ::core::sync::atomic::Ordering::Relaxed);
+        }
+
+        #timing
+
+        #state
+
         #[unsafe(no_mangle)]
         extern "C" #func {
-            let hooked = unsafe { #real_func.expect(&format!("The function \"{}\" was not properly hooked.", #wrapped_func_name)) };
+            #hooked
+
+            if !#enabled_flag.load(::core::sync::atomic::Ordering::Relaxed) {
+                return unsafe { hooked(#(#args),*) };
+            }
+            #(#bindings)*
 
             #block
         }
@@ -647,22 +1304,20 @@ pub fn function_hook(attr: TokenStream, item: TokenStream) -> TokenStream {
     }));
 
     let library = attr.module;
+    let process = attr.process;
     let target: &syn::Ident = &item.sig.ident;
     let hooked_func_name = syn::LitByteStr::new(
-        format!("{}\0", attr.function.to_string()).as_bytes(),
+        format!("{}\0", attr.function).as_bytes(),
         attr.function.span(),
     );
     let my_func_name =
         syn::LitByteStr::new(format!("{}\0", &item.sig.ident).as_bytes(), item.span());
-    let loader_name = syn::Ident::new(
-        &format!("wups_loader_{}", target.to_string()),
-        target.span(),
-    );
+    let loader_name = syn::Ident::new(&format!("wups_loader_{target}"), target.span());
 
     stream.extend(TokenStream::from(quote! {
         #[used]
         #[unsafe(no_mangle)]
-        #[unsafe(link_section = ".wups.load")]
+        #[unsafe(link_section = #SECTION_LOAD)]
         #[allow(non_upper_case_globals)]
         static #loader_name: ::wups::sys::wups_loader_entry_t =
             ::wups::sys::wups_loader_entry_t {
@@ -675,11 +1330,377 @@ pub fn function_hook(attr: TokenStream, item: TokenStream) -> TokenStream {
                     my_function_name: #my_func_name.as_ptr()  as *const _,
                     target: #target as *const ::core::ffi::c_void,
                     call_addr: ::core::ptr::addr_of!(#real_func) as *const _ as *const ::core::ffi::c_void,
-                    targetProcess:
-                        ::wups::sys::WUPSFPTargetProcess::WUPS_FP_TARGET_PROCESS_GAME_AND_MENU,
+                    targetProcess: #process,
                 },
             };
     }));
 
+    // one symbol per hooked function, so hooking it twice fails to build with an error naming it
+    let duplicate_guard = syn::Ident::new(
+        &format!(
+            "__wups_duplicate_function_hook__{}__{}",
+            attr.module_name, attr.function
+        ),
+        attr.function.span(),
+    );
+
+    stream.extend(TokenStream::from(quote! {
+        #[used]
+        #[unsafe(no_mangle)]
+        #[unsafe(link_section = #SECTION_HOOK_REGISTRY)]
+        #[allow(non_upper_case_globals)]
+        static #duplicate_guard: [u8; 0] = [];
+    }));
+
     stream
 }
+
+// region: Storable
+
+/// Implements `wups::storage::Storable` for a struct with named fields.
+///
+/// Every field is stored under its own name and must implement `StorageCompatible` and [Clone].
+/// Fields which don't exist in storage yet are taken from the struct's [Default] implementation,
+/// or from `#[storable(default = ...)]` if the field has one.
+/// [entries][wups::storage::Storable::entries] lists every field, so a `SettingsMenu` shows them
+/// without reading them back from storage.
+///
+/// # Example
+///
+/// ```
+/// #[derive(Default, Storable)]
+/// struct Settings {
+///     enabled: bool,
+///     volume: i32,
+/// }
+/// ```
+///
+/// # Versions
+///
+/// `#[storable(version = N)]` gives the struct a schema version, which is stored as `u32` under
+/// the key `"<Struct>.__version"` (or `#[storable(version_key = "...")]`). Fields added later are
+/// marked with the version they were added in, `#[storable(since = N)]`; fields without it exist
+/// since version 1, and a missing version key counts as version 1, i.e. as data written before
+/// the attribute was added.
+///
+/// ```
+/// #[derive(Default, Storable)]
+/// #[storable(version = 2)]
+/// struct Settings {
+///     enabled: bool,
+///     #[storable(since = 2, default = 50)]
+///     volume: i32,
+/// }
+/// ```
+///
+/// Loading data with an older version:
+///
+/// 1. fields added after it are set to their default, **even if a key with their name exists**
+///    (it may be left over from something else),
+/// 2. these defaults and the current version are written to storage, so the upgrade only happens
+///    once.
+///
+/// [store][wups::storage::Storable::store] writes the current version as well. Data with a newer
+/// version (after a downgrade of the plugin) is loaded as usual and its version is kept, so fields
+/// unknown to the older plugin aren't reset when it is upgraded again.
+#[proc_macro_derive(Storable, attributes(storable))]
+pub fn derive_storable(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::DeriveInput);
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return syn::Error::new(input.span(), "Storable requires a struct with named fields")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    let mut version: Option<u32> = None;
+    let mut version_key = syn::LitStr::new(&format!("{name}.__version"), name.span());
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("storable")) {
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("version") {
+                let v: u32 = meta.value()?.parse::<syn::LitInt>()?.base10_parse()?;
+                if v == 0 {
+                    return Err(meta.error("versions start at 1"));
+                }
+                version = Some(v);
+            } else if meta.path.is_ident("version_key") {
+                version_key = meta.value()?.parse()?;
+            } else {
+                return Err(meta.error("expected `version = ...` or `version_key = ...`"));
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            return e.to_compile_error().into();
+        }
+    }
+
+    let mut since = Vec::new();
+    let mut defaults = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let mut field_since = 1u32;
+        let mut field_default: Option<syn::Expr> = None;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("storable")) {
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("since") {
+                    let v: u32 = meta.value()?.parse::<syn::LitInt>()?.base10_parse()?;
+                    match version {
+                        None => {
+                            return Err(meta.error("`since` requires #[storable(version = ...)]"));
+                        }
+                        Some(version) if v == 0 || v > version => {
+                            return Err(meta.error(format!(
+                                "`since` must be between 1 and the struct's version {version}"
+                            )));
+                        }
+                        _ => field_since = v,
+                    }
+                } else if meta.path.is_ident("default") {
+                    field_default = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `since = ...` or `default = ...`"));
+                }
+                Ok(())
+            });
+            if let Err(e) = result {
+                return e.to_compile_error().into();
+            }
+        }
+        since.push(field_since);
+        defaults.push(match field_default {
+            Some(expr) => quote! { #expr },
+            None => quote! { default.#ident },
+        });
+    }
+
+    let idents: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let keys: Vec<_> = idents
+        .iter()
+        .map(|ident| syn::LitStr::new(&ident.to_string(), ident.span()))
+        .collect();
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let entries = quote! {
+        fn entries(
+            &self,
+        ) -> ::wut::vec::Vec<(
+            ::wut::string::String,
+            ::wups::sys::WUPSStorageItemTypes::Type,
+            ::wut::vec::Vec<u8>,
+        )> {
+            ::wut::vec::Vec::from([
+                #((
+                    ::wut::string::String::from(#keys),
+                    <#types as ::wups::storage::StorageCompatible>::ITEM_TYPE,
+                    <#types as ::wups::storage::StorageCompatible>::encode(&self.#idents),
+                ),)*
+            ])
+        }
+    };
+
+    let Some(version) = version else {
+        return TokenStream::from(quote! {
+            impl #impl_generics ::wups::storage::Storable for #name #ty_generics #where_clause {
+                fn load() -> Result<Self, ::wups::storage::StorageError> {
+                    #[allow(unused_variables)]
+                    let default = <Self as ::core::default::Default>::default();
+                    Ok(Self {
+                        #(
+                            #idents: match ::wups::storage::load::<#types>(#keys) {
+                                Ok(v) => v,
+                                Err(::wups::storage::StorageError::NotFound) => #defaults,
+                                Err(e) => return Err(e),
+                            },
+                        )*
+                    })
+                }
+
+                fn store(&self) -> Result<(), ::wups::storage::StorageError> {
+                    #(
+                        ::wups::storage::store::<#types>(#keys, ::core::clone::Clone::clone(&self.#idents))?;
+                    )*
+                    Ok(())
+                }
+
+                #entries
+            }
+        });
+    };
+
+    TokenStream::from(quote! {
+        impl #impl_generics ::wups::storage::Storable for #name #ty_generics #where_clause {
+            fn load() -> Result<Self, ::wups::storage::StorageError> {
+                let stored_version = match ::wups::storage::load::<u32>(#version_key) {
+                    Ok(v) => v,
+                    Err(::wups::storage::StorageError::NotFound) => 1,
+                    Err(e) => return Err(e),
+                };
+
+                #[allow(unused_variables)]
+                let default = <Self as ::core::default::Default>::default();
+                let value = Self {
+                    #(
+                        #idents: if stored_version < #since {
+                            #defaults
+                        } else {
+                            match ::wups::storage::load::<#types>(#keys) {
+                                Ok(v) => v,
+                                Err(::wups::storage::StorageError::NotFound) => #defaults,
+                                Err(e) => return Err(e),
+                            }
+                        },
+                    )*
+                };
+
+                if stored_version < #version {
+                    #(
+                        if stored_version < #since {
+                            ::wups::storage::store::<#types>(#keys, ::core::clone::Clone::clone(&value.#idents))?;
+                        }
+                    )*
+                    ::wups::storage::store::<u32>(#version_key, #version)?;
+                }
+                Ok(value)
+            }
+
+            fn store(&self) -> Result<(), ::wups::storage::StorageError> {
+                #(
+                    ::wups::storage::store::<#types>(#keys, ::core::clone::Clone::clone(&self.#idents))?;
+                )*
+                match ::wups::storage::load::<u32>(#version_key) {
+                    // written by a newer version of the plugin
+                    Ok(v) if v > #version => Ok(()),
+                    _ => ::wups::storage::store::<u32>(#version_key, #version),
+                }
+            }
+
+            #entries
+        }
+    })
+}
+
+// endregion
+
+// region: StorageEnum
+
+/// Implements `wups::storage::StorageEnum` and `wups::storage::StorageCompatible` for a fieldless
+/// enum.
+///
+/// The discriminant (including explicitly assigned ones) is stored as `u32`. The enum must
+/// implement [Default], which is used by `StorageCompatible`.
+///
+/// # Example
+///
+/// ```
+/// #[derive(Default, StorageEnum)]
+/// enum Quality {
+///     Low,
+///     #[default]
+///     Medium,
+///     High = 10,
+/// }
+/// ```
+#[proc_macro_derive(StorageEnum)]
+pub fn derive_storage_enum(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::DeriveInput);
+    let name = &input.ident;
+
+    let variants = match &input.data {
+        syn::Data::Enum(data) => &data.variants,
+        _ => {
+            return syn::Error::new(input.span(), "StorageEnum requires an enum")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    if let Some(v) = variants
+        .iter()
+        .find(|v| !matches!(v.fields, syn::Fields::Unit))
+    {
+        return syn::Error::new(
+            v.span(),
+            "StorageEnum requires all variants to be fieldless",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let idents: Vec<_> = variants.iter().map(|v| &v.ident).collect();
+    let names: Vec<_> = idents
+        .iter()
+        .map(|ident| syn::LitStr::new(&ident.to_string(), ident.span()))
+        .collect();
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    TokenStream::from(quote! {
+        impl #impl_generics ::wups::storage::StorageEnum for #name #ty_generics #where_clause {
+            const VARIANTS: &'static [Self] = &[#(Self::#idents),*];
+
+            fn to_discriminant(&self) -> u32 {
+                match self {
+                    #(Self::#idents => Self::#idents as u32,)*
+                }
+            }
+
+            fn from_discriminant(value: u32) -> Option<Self> {
+                #(
+                    if value == Self::#idents as u32 {
+                        return Some(Self::#idents);
+                    }
+                )*
+                None
+            }
+
+            fn name(&self) -> &'static str {
+                match self {
+                    #(Self::#idents => #names,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::wups::storage::StorageCompatible for #name #ty_generics #where_clause {
+            type T = Self;
+            const ITEM_TYPE: ::wups::sys::WUPSStorageItemTypes::Type =
+                ::wups::sys::WUPSStorageItemTypes::WUPS_STORAGE_ITEM_U32;
+
+            fn load(name: &str) -> Result<Self::T, ::wups::storage::StorageError> {
+                let value = ::wups::storage::load::<u32>(name)?;
+                <Self as ::wups::storage::StorageEnum>::from_discriminant(value)
+                    .ok_or(::wups::storage::StorageError::UnexpectedDataType)
+            }
+
+            fn store(name: &str, value: Self::T) -> Result<(), ::wups::storage::StorageError> {
+                ::wups::storage::store::<u32>(
+                    name,
+                    ::wups::storage::StorageEnum::to_discriminant(&value),
+                )
+            }
+
+            fn encode(value: &Self::T) -> ::wut::vec::Vec<u8> {
+                <u32 as ::wups::storage::StorageCompatible>::encode(
+                    &::wups::storage::StorageEnum::to_discriminant(value),
+                )
+            }
+
+            fn decode(bytes: &[u8]) -> Result<Self::T, ::wups::storage::StorageError> {
+                let value = <u32 as ::wups::storage::StorageCompatible>::decode(bytes)?;
+                <Self as ::wups::storage::StorageEnum>::from_discriminant(value)
+                    .ok_or(::wups::storage::StorageError::UnexpectedDataType)
+            }
+        }
+    })
+}
+
+// endregion
@@ -5,6 +5,8 @@ pub use wups_macros as macros;
 pub use wups_sys as sys;
 
 pub mod prelude {
-    pub use wups_core::config::{Attachable, ConfigMenu};
-    pub use wups_macros::WUPS_PLUGIN_NAME;
+    #[cfg(feature = "config")]
+    pub use wups_core::config::{Attachable, ConfigMenu, SettingsMenu, StatefulConfigMenu};
+    pub use wups_macros::{WUPS_PLUGIN_NAME, WUPS_PLUGIN_TAGS};
+    pub use wups_macros::{Storable, StorageEnum};
 }
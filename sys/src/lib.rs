@@ -2,3 +2,41 @@
 
 pub mod bindings;
 pub use bindings::*;
+
+// region: Layout
+
+// `layout_tests` are disabled in build.rs because bindgen emits them for the (64-bit) host. These
+// hand-written checks cover the structs the loader reads directly and verify them against the
+// 32-bit PowerPC ABI, so binding drift fails the build instead of corrupting the loader tables.
+#[cfg(target_arch = "powerpc")]
+const _: () = {
+    use core::mem::{offset_of, size_of};
+
+    assert!(size_of::<wups_loader_hook_t>() == 8);
+    assert!(offset_of!(wups_loader_hook_t, type_) == 0);
+    assert!(offset_of!(wups_loader_hook_t, target) == 4);
+
+    assert!(size_of::<wups_loader_entry_t>() == 36);
+    assert!(offset_of!(wups_loader_entry_t, type_) == 0);
+    assert!(offset_of!(wups_loader_entry_t, _function) == 4);
+    assert!(offset_of!(wups_loader_entry_t__bindgen_ty_1, physical_address) == 0);
+    assert!(offset_of!(wups_loader_entry_t__bindgen_ty_1, virtual_address) == 4);
+    assert!(offset_of!(wups_loader_entry_t__bindgen_ty_1, name) == 8);
+    assert!(offset_of!(wups_loader_entry_t__bindgen_ty_1, library) == 12);
+    assert!(offset_of!(wups_loader_entry_t__bindgen_ty_1, my_function_name) == 16);
+    assert!(offset_of!(wups_loader_entry_t__bindgen_ty_1, target) == 20);
+    assert!(offset_of!(wups_loader_entry_t__bindgen_ty_1, call_addr) == 24);
+    assert!(offset_of!(wups_loader_entry_t__bindgen_ty_1, targetProcess) == 28);
+
+    assert!(size_of::<WUPSConfigAPIOptionsV1>() == 4);
+    assert!(size_of::<WUPSConfigAPICreateCategoryOptionsV1>() == 4);
+    assert!(size_of::<WUPSConfigAPICreateCategoryOptions>() == 8);
+    assert!(offset_of!(WUPSConfigAPICreateCategoryOptions, data) == 4);
+
+    assert!(size_of::<ConfigItemMultipleValuesPair>() == 8);
+    assert!(offset_of!(ConfigItemMultipleValuesPair, valueName) == 4);
+
+    assert!(size_of::<wups_storage_item>() == 4);
+};
+
+// endregion